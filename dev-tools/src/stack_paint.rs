@@ -19,6 +19,9 @@ pub fn get_stack_pointer() -> *const u32 {
     sp
 }
 
+/// # Safety
+///
+/// Writes below the current stack pointer, must be called before the stack is in use.
 #[inline(never)]
 #[allow(unsafe_op_in_unsafe_fn)]
 #[allow(static_mut_refs)]
@@ -39,6 +42,9 @@ pub unsafe fn paint_stack(context: &str) {
     }
 }
 
+/// # Safety
+///
+/// Reads the whole stack region, `paint_stack` must have been called before.
 #[inline(never)]
 #[allow(unsafe_op_in_unsafe_fn)]
 #[allow(static_mut_refs)]
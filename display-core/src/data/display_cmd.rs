@@ -0,0 +1,241 @@
+use heapless::{Vec, String};
+use embedded_graphics::text::Alignment;
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::epd::three_color::ThreeColor;
+use crate::data::screen::{FontSize, Screen};
+
+pub const STRING_CAPACITY: usize = 80;
+pub const MAX_BODY_LINES: usize = 10;
+// Always fits into the largest QR code version supported by the widget.
+pub const QR_TEXT_CAPACITY: usize = 128;
+pub const MAX_CHART_VALUES: usize = 48;
+pub const UNIT_CAPACITY: usize = 8;
+
+/// A line or value didn't fit into its fixed capacity.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CapacityError;
+
+/// Line of text, drawn in the large proportional font and left aligned unless set otherwise.
+pub struct TextLine {
+    text: String<STRING_CAPACITY>,
+    color: ThreeColor,
+    font_size: FontSize,
+    alignment: Alignment,
+}
+
+impl TextLine {
+    pub fn new(text: &str, color: ThreeColor) -> Self {
+        let text = string_from(text).unwrap_or(String::new());
+        TextLine { text, color, font_size: FontSize::Large, alignment: Alignment::Left }
+    }
+
+    pub fn with_font_size(mut self, font_size: FontSize) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn color(&self) -> ThreeColor {
+        self.color
+    }
+
+    pub fn font_size(&self) -> FontSize {
+        self.font_size
+    }
+
+    pub fn alignment(&self) -> Alignment {
+        self.alignment
+    }
+}
+
+pub struct QrCodeContent {
+    text: String<QR_TEXT_CAPACITY>,
+    color: ThreeColor
+}
+
+impl QrCodeContent {
+    pub fn new(text: &str, color: ThreeColor) -> Self {
+        let text = string_from(text).unwrap_or(String::new());
+        QrCodeContent { text, color }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn color(&self) -> ThreeColor {
+        self.color
+    }
+}
+
+pub struct TextPanelContent {
+    title: TextLine,
+    body: Vec<TextLine, MAX_BODY_LINES>,
+    qr_code: Option<QrCodeContent>,
+}
+
+impl TextPanelContent {
+    pub fn new(title: TextLine) -> Self {
+        TextPanelContent {
+            title, body: Vec::new(), qr_code: None,
+        }
+    }
+
+    pub fn title(&self) -> &TextLine {
+        &self.title
+    }
+
+    pub fn body_len(&self) -> usize {
+        self.body.len()
+    }
+
+    pub fn body_line(&self, i: usize) -> Option<&TextLine> {
+        self.body.get(i)
+    }
+
+    pub fn add_body_line(&mut self, body_line: TextLine) -> Result<(), CapacityError> {
+        self.body.push(body_line).or(Err(CapacityError))
+    }
+
+    pub fn qr_code(&self) -> Option<&QrCodeContent> {
+        self.qr_code.as_ref()
+    }
+
+    pub fn set_qr_code(&mut self, qr_code: QrCodeContent) {
+        self.qr_code = Some(qr_code);
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum ChartKind {
+    Sparkline,
+    Bars,
+    Gauge,
+}
+
+pub struct ChartContent {
+    title: TextLine,
+    kind: ChartKind,
+    values: Vec<f32, MAX_CHART_VALUES>,
+    min: Option<f32>,
+    max: Option<f32>,
+    threshold: Option<f32>,
+    unit: String<UNIT_CAPACITY>,
+}
+
+impl ChartContent {
+    pub fn new(title: TextLine, kind: ChartKind) -> Self {
+        ChartContent {
+            title, kind, values: Vec::new(), min: None, max: None, threshold: None, unit: String::new(),
+        }
+    }
+
+    pub fn title(&self) -> &TextLine {
+        &self.title
+    }
+
+    pub fn kind(&self) -> ChartKind {
+        self.kind
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    pub fn add_value(&mut self, value: f32) -> Result<(), CapacityError> {
+        self.values.push(value).or(Err(CapacityError))
+    }
+
+    pub fn min(&self) -> Option<f32> {
+        self.min
+    }
+
+    pub fn set_min(&mut self, min: f32) {
+        self.min = Some(min);
+    }
+
+    pub fn max(&self) -> Option<f32> {
+        self.max
+    }
+
+    pub fn set_max(&mut self, max: f32) {
+        self.max = Some(max);
+    }
+
+    pub fn threshold(&self) -> Option<f32> {
+        self.threshold
+    }
+
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = Some(threshold);
+    }
+
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    pub fn set_unit(&mut self, unit: &str) {
+        self.unit = string_from(unit).unwrap_or(String::new());
+    }
+}
+
+fn string_from<const CAPACITY: usize>(slice: &str) -> Option<String<CAPACITY>> {
+    if slice.len() <= CAPACITY {
+        String::try_from(slice).ok()
+    } else {
+        let last = slice.floor_char_boundary(CAPACITY);
+        String::try_from(&slice[0..last]).ok()
+    }
+}
+
+/// How `DisplayCmd::Refresh` shows the current image again.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RefreshMode {
+    /// Cleans the display against ghosting first.
+    Full,
+    /// Skips the cleaning and only refreshes the image, requested as `partial`. The three-color panel
+    /// has no partial waveform, so it still runs the full one.
+    Plain,
+}
+
+/// What `DisplayCmd::ShowStatus` shows, collected by the display task when it draws.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct StatusContent {
+    /// Seconds since start.
+    pub uptime: u64,
+    /// Sequence number of the latest command.
+    pub sequence: u32,
+    /// Refreshes since the last cleaning.
+    pub refreshes: u32,
+    pub orientation: DisplayOrientation,
+}
+
+/// Commands up to `ShowStatus` replace the image on the display, the others act on the current one.
+#[allow(clippy::large_enum_variant)]
+pub enum DisplayCmd {
+    None,
+    TextPanel(TextPanelContent),
+    Chart(ChartContent),
+    /// Layout of widgets.
+    Screen(Screen),
+    /// The `Planes` of the bitmap uploads, which the display task copies to the display.
+    Bitmap,
+    /// Blanks the display.
+    Clear,
+    /// The `StatusContent` of the device.
+    ShowStatus,
+    /// Keeps the display asleep, without maintenance refreshes, until the next command. The panel is
+    /// put into deep sleep after every refresh already, so this only stops the maintenance.
+    Sleep,
+    Refresh(RefreshMode),
+    /// Draws the current image again in the orientation, which stays for the later commands.
+    SetOrientation(DisplayOrientation),
+}
@@ -0,0 +1,39 @@
+use serde::Deserialize;
+use epd_display::epd::three_color::ThreeColor;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageColor {
+    Black,
+    White,
+    #[serde(alias = "red")]
+    Chromatic,
+}
+
+impl From<MessageColor> for ThreeColor {
+    fn from(color: MessageColor) -> Self {
+        match color {
+            MessageColor::Black => ThreeColor::Black,
+            MessageColor::White => ThreeColor::White,
+            MessageColor::Chromatic => ThreeColor::Chromatic,
+        }
+    }
+}
+
+/// Color of content that has to stand out from the white background, like QR codes.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum InkColor {
+    Black,
+    #[serde(alias = "red")]
+    Chromatic,
+}
+
+impl From<InkColor> for ThreeColor {
+    fn from(color: InkColor) -> Self {
+        match color {
+            InkColor::Black => ThreeColor::Black,
+            InkColor::Chromatic => ThreeColor::Chromatic,
+        }
+    }
+}
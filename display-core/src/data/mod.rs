@@ -0,0 +1,3 @@
+pub mod display_cmd;
+pub mod message_color;
+pub mod screen;
@@ -0,0 +1,522 @@
+use core::fmt::{self, Display, Formatter};
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+use heapless::{String, Vec};
+use serde::{Deserialize, Deserializer};
+use serde::de::{Error as _, SeqAccess, Visitor};
+
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::layout::leaf::Paragraph;
+use epd_display::layout::node::{Length, Node, Padding};
+use epd_display::text::font::Font;
+use epd_display::text::font_text_style::FontTextStyle;
+use epd_display::text::fonts::{FONT_10X20, FONT_10X20_PROPORTIONAL, FONT_6X10, FONT_6X10_PROPORTIONAL, FONT_8X13, FONT_8X13_PROPORTIONAL};
+use epd_display::widgets::chart::bar_chart::BarChart;
+use epd_display::widgets::chart::gauge::Gauge;
+use epd_display::widgets::chart::scale::Scale;
+use epd_display::widgets::chart::sparkline::Sparkline;
+use epd_display::widgets::qr_code::QrCode;
+
+use crate::data::display_cmd::{MAX_CHART_VALUES, STRING_CAPACITY};
+use crate::data::message_color::MessageColor;
+
+pub const MAX_SCREEN_WIDGETS: usize = 12;
+/// Capacity of the texts of all widgets together.
+pub const SCREEN_TEXT_CAPACITY: usize = 256;
+/// Capacity of the values of all charts together.
+pub const MAX_SCREEN_VALUES: usize = MAX_CHART_VALUES;
+pub const MAX_CHILDREN: usize = 8;
+pub const MAX_DEPTH: usize = 6;
+// QR codes are encoded while drawing and take about 400 bytes of stack each.
+pub const MAX_QR_CODES: usize = 2;
+
+/// Screen described as a tree of widgets.
+///
+/// Containers refer to their children by index, children have to come after their parent and
+/// can only have one parent. Widgets that aren't a child of another widget are drawn into their
+/// `bounds`, or the whole display if they have none.
+///
+/// ```json
+/// {"screen": {"widgets": [
+///     {"type": "column", "children": [1, 2, 3]},
+///     {"type": "text", "text": "Shelf 12", "font": "10x20", "padding": 4},
+///     {"type": "line", "color": "red", "stroke": 3},
+///     {"type": "sparkline", "values": [1, 4, 2, 6], "threshold": 5, "flex": 2}
+/// ]}}
+/// ```
+///
+/// The texts and chart values of all widgets are kept together, so a screen with many widgets
+/// doesn't take more memory than a text message.
+pub struct Screen {
+    pub widgets: Vec<Widget, MAX_SCREEN_WIDGETS>,
+    texts: String<SCREEN_TEXT_CAPACITY>,
+    values: Vec<f32, MAX_SCREEN_VALUES>,
+}
+
+pub struct Widget {
+    pub kind: WidgetType,
+    pub children: Vec<u8, MAX_CHILDREN>,
+    pub bounds: Option<Bounds>,
+    pub length: Option<u32>,
+    pub flex: Option<u32>,
+    pub padding: Option<u32>,
+    pub spacing: Option<u32>,
+    pub font: Option<FontSize>,
+    pub proportional: Option<bool>,
+    pub align: Option<Align>,
+    pub color: Option<MessageColor>,
+    pub stroke: Option<u32>,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub threshold: Option<f32>,
+    text: Span,
+    values: Span,
+}
+
+/// Range of a widget's text or values in the screen.
+#[derive(Copy, Clone)]
+struct Span {
+    start: u16,
+    end: u16,
+}
+
+// A widget as it is deserialized, before its text and values are moved to the screen.
+#[derive(Deserialize)]
+struct WidgetMessage {
+    #[serde(rename = "type")]
+    pub kind: WidgetType,
+    #[serde(default)]
+    pub children: Vec<u8, MAX_CHILDREN>,
+    pub bounds: Option<Bounds>,
+    pub length: Option<u32>,
+    pub flex: Option<u32>,
+    pub padding: Option<u32>,
+    pub spacing: Option<u32>,
+    pub text: Option<String<STRING_CAPACITY>>,
+    pub font: Option<FontSize>,
+    pub proportional: Option<bool>,
+    pub align: Option<Align>,
+    pub color: Option<MessageColor>,
+    pub stroke: Option<u32>,
+    #[serde(default)]
+    pub values: Vec<f32, MAX_CHART_VALUES>,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub threshold: Option<f32>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WidgetType {
+    Row,
+    Column,
+    Spacer,
+    Text,
+    Line,
+    Qr,
+    Sparkline,
+    Bars,
+    Gauge,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct Bounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum FontSize {
+    #[serde(rename = "6x10")]
+    Small,
+    #[serde(rename = "8x13")]
+    Medium,
+    #[serde(rename = "10x20")]
+    Large,
+}
+
+impl FontSize {
+    pub fn font(self, proportional: bool) -> &'static dyn Font {
+        match (self, proportional) {
+            (FontSize::Small, false) => &FONT_6X10,
+            (FontSize::Small, true) => &FONT_6X10_PROPORTIONAL,
+            (FontSize::Medium, false) => &FONT_8X13,
+            (FontSize::Medium, true) => &FONT_8X13_PROPORTIONAL,
+            (FontSize::Large, false) => &FONT_10X20,
+            (FontSize::Large, true) => &FONT_10X20_PROPORTIONAL,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl From<Align> for Alignment {
+    fn from(align: Align) -> Self {
+        match align {
+            Align::Left => Alignment::Left,
+            Align::Center => Alignment::Center,
+            Align::Right => Alignment::Right,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ScreenError {
+    Empty,
+    InvalidChild(usize),
+    TooDeep,
+    TooManyQrCodes,
+    InvalidQrCode(usize),
+    MissingValues(usize),
+    OutOfBounds(usize),
+    TooLarge(usize),
+    DoesNotFit(usize),
+}
+
+impl Display for ScreenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScreenError::Empty => write!(f, "Screen has no widgets"),
+            ScreenError::InvalidChild(i) => write!(f, "Widget {}: invalid child", i),
+            ScreenError::TooDeep => write!(f, "Nested deeper than {} levels", MAX_DEPTH),
+            ScreenError::TooManyQrCodes => write!(f, "More than {} QR codes", MAX_QR_CODES),
+            ScreenError::InvalidQrCode(i) => write!(f, "Widget {}: QR code text too long", i),
+            ScreenError::MissingValues(i) => write!(f, "Widget {}: chart without values", i),
+            ScreenError::OutOfBounds(i) => write!(f, "Widget {}: bounds outside display", i),
+            ScreenError::TooLarge(i) => write!(f, "Widget {}: size larger than display", i),
+            ScreenError::DoesNotFit(i) => write!(f, "Widget {}: content doesn't fit", i),
+        }
+    }
+}
+
+// Widgets without a parent, each level of the tree is laid out in breadth first order.
+type Level = Vec<u8, MAX_SCREEN_WIDGETS>;
+
+impl Screen {
+    /// Checks the structure of the tree and that all widgets fit on a display of the given size.
+    pub fn validate(&self, size: Size) -> Result<(), ScreenError> {
+        let widgets = &self.widgets;
+
+        if widgets.is_empty() {
+            return Err(ScreenError::Empty);
+        }
+
+        let mut has_parent = [false; MAX_SCREEN_WIDGETS];
+
+        for (i, widget) in widgets.iter().enumerate() {
+            let is_container = matches!(widget.kind, WidgetType::Row | WidgetType::Column);
+
+            if !is_container && !widget.children.is_empty() {
+                return Err(ScreenError::InvalidChild(i));
+            }
+
+            for &child in &widget.children {
+                let child = child as usize;
+
+                if child <= i || child >= widgets.len() || has_parent[child] {
+                    return Err(ScreenError::InvalidChild(i));
+                }
+
+                has_parent[child] = true;
+            }
+
+            let is_chart = matches!(widget.kind, WidgetType::Sparkline | WidgetType::Bars | WidgetType::Gauge);
+
+            if is_chart && self.values(widget).is_empty() {
+                return Err(ScreenError::MissingValues(i));
+            }
+
+            // The flex weight is relative to the other widgets and may be larger.
+            let sizes = [widget.length, widget.padding, widget.spacing, widget.stroke];
+
+            if sizes.into_iter().flatten().any(|value| value > size.width.max(size.height)) {
+                return Err(ScreenError::TooLarge(i));
+            }
+        }
+
+        let mut level = self.top_level();
+        let mut depth = 1;
+
+        while !level.is_empty() {
+            if depth > MAX_DEPTH {
+                return Err(ScreenError::TooDeep);
+            }
+
+            level = self.next_level(&level);
+            depth += 1;
+        }
+
+        let qr_codes = widgets.iter().filter(|widget| widget.kind == WidgetType::Qr).count();
+
+        if qr_codes > MAX_QR_CODES {
+            return Err(ScreenError::TooManyQrCodes);
+        }
+
+        for &i in &self.top_level() {
+            let Some(Bounds { x, y, width, height }) = self.widgets[i as usize].bounds else { continue };
+            let within = |start: i32, length: u32, size: u32| {
+                u32::try_from(start).is_ok_and(|start| start < size && start.checked_add(length).is_some_and(|end| end <= size))
+            };
+
+            if !within(x, width, size.width) || !within(y, height, size.height) {
+                return Err(ScreenError::OutOfBounds(i as usize));
+            }
+        }
+
+        let mut result = Ok(());
+
+        self.with_nodes(&mut |nodes, qr_error| {
+            result = match qr_error {
+                Some(i) => Err(ScreenError::InvalidQrCode(i)),
+                None => self.top_level().iter().zip(nodes)
+                    .find(|&(&i, node)| !node.fits(self.bounds(i as usize, size)))
+                    .map_or(Ok(()), |(&i, _)| Err(ScreenError::DoesNotFit(i as usize))),
+            };
+        });
+
+        result
+    }
+
+    /// Draws the screen, it has to be validated against the size of the target first.
+    pub fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        let size = target.bounding_box().size;
+        let mut result = Ok(());
+
+        self.with_nodes(&mut |nodes, _| {
+            result = self.top_level().iter()
+                .zip(nodes)
+                .try_for_each(|(&i, node)| node.draw(self.bounds(i as usize, size), target));
+        });
+
+        result
+    }
+
+    /// Text of a widget of the screen, empty if it has none.
+    pub fn text(&self, widget: &Widget) -> &str {
+        &self.texts[widget.text.start as usize..widget.text.end as usize]
+    }
+
+    /// Chart values of a widget of the screen.
+    pub fn values(&self, widget: &Widget) -> &[f32] {
+        &self.values[widget.values.start as usize..widget.values.end as usize]
+    }
+
+    // Adds the widget and moves its text and values to the screen, `None` if the screen is full.
+    fn push(&mut self, widget: WidgetMessage) -> Option<()> {
+        let text_start = self.texts.len() as u16;
+        self.texts.push_str(widget.text.as_deref().unwrap_or("")).ok()?;
+        let values_start = self.values.len() as u16;
+        self.values.extend_from_slice(&widget.values).ok()?;
+
+        self.widgets.push(Widget {
+            kind: widget.kind,
+            children: widget.children,
+            bounds: widget.bounds,
+            length: widget.length,
+            flex: widget.flex,
+            padding: widget.padding,
+            spacing: widget.spacing,
+            font: widget.font,
+            proportional: widget.proportional,
+            align: widget.align,
+            color: widget.color,
+            stroke: widget.stroke,
+            min: widget.min,
+            max: widget.max,
+            threshold: widget.threshold,
+            text: Span { start: text_start, end: self.texts.len() as u16 },
+            values: Span { start: values_start, end: self.values.len() as u16 },
+        }).ok()
+    }
+
+    fn top_level(&self) -> Level {
+        let mut has_parent = [false; MAX_SCREEN_WIDGETS];
+
+        for widget in &self.widgets {
+            for &child in &widget.children {
+                if let Some(has_parent) = has_parent.get_mut(child as usize) {
+                    *has_parent = true;
+                }
+            }
+        }
+
+        (0..self.widgets.len() as u8).filter(|&i| !has_parent[i as usize]).collect()
+    }
+
+    fn next_level(&self, level: &[u8]) -> Level {
+        level.iter()
+            .flat_map(|&i| self.widgets[i as usize].children.iter().copied())
+            .collect()
+    }
+
+    fn bounds(&self, i: usize, size: Size) -> Rectangle {
+        match self.widgets[i].bounds {
+            Some(Bounds { x, y, width, height }) => Rectangle::new(Point::new(x, y), Size::new(width, height)),
+            None => Rectangle::new(Point::zero(), size),
+        }
+    }
+
+    // Builds the layout nodes and calls `f` with the nodes of the top level widgets and the index
+    // of a QR code widget that couldn't be encoded.
+    fn with_nodes(&self, f: &mut dyn FnMut(&[Node<'_>], Option<usize>)) {
+        let paragraphs: Vec<Paragraph, MAX_SCREEN_WIDGETS> = self.widgets.iter()
+            .map(|widget| (self.text(widget), FontTextStyle::new(widget.font(), widget.color())))
+            .collect();
+
+        let mut qr_codes: Vec<QrCode, MAX_QR_CODES> = Vec::new();
+        let mut qr_error = None;
+
+        for (i, widget) in self.widgets.iter().enumerate().filter(|(_, widget)| widget.kind == WidgetType::Qr) {
+            match QrCode::new(self.text(widget), Point::zero()) {
+                Ok(qr_code) => { let _ = qr_codes.push(qr_code.with_color(widget.color())); }
+                Err(_) => qr_error = qr_error.or(Some(i)),
+            }
+        }
+
+        let resources = Resources { paragraphs: &paragraphs, qr_codes: &qr_codes };
+        self.build_level(&self.top_level(), &resources, &mut |nodes| f(nodes, qr_error));
+    }
+
+    // Builds the nodes of a level after building the levels below, so the children of each
+    // container are a contiguous slice of the next level.
+    fn build_level(&self, level: &[u8], resources: &Resources<'_>, f: &mut dyn FnMut(&[Node<'_>])) {
+        let next = self.next_level(level);
+
+        let mut build = |children: &[Node<'_>]| {
+            let mut offset = 0;
+            let nodes: Vec<Node, MAX_SCREEN_WIDGETS> = level.iter()
+                .map(|&i| {
+                    let widget = &self.widgets[i as usize];
+                    let count = widget.children.len();
+                    let node = self.node(i as usize, resources, &children[offset..offset + count]);
+                    offset += count;
+                    node
+                })
+                .collect();
+
+            f(&nodes);
+        };
+
+        if next.is_empty() {
+            build(&[]);
+        } else {
+            self.build_level(&next, resources, &mut build);
+        }
+    }
+
+    fn node<'a>(&'a self, i: usize, resources: &Resources<'a>, children: &'a [Node<'a>]) -> Node<'a> {
+        let widget = &self.widgets[i];
+        let scale = |default: Scale| Scale::new(widget.min.unwrap_or(default.min()), widget.max.unwrap_or(default.max()));
+        let threshold = widget.threshold.unwrap_or(f32::INFINITY);
+        let values = self.values(widget);
+        let last = values.last().copied().unwrap_or(0.0);
+
+        let node = match widget.kind {
+            WidgetType::Row => Node::row(children),
+            WidgetType::Column => Node::column(children),
+            WidgetType::Spacer => Node::spacer(),
+            WidgetType::Text => Node::text(&resources.paragraphs[i..i + 1], widget.align()),
+            WidgetType::Line => Node::line(widget.color(), widget.stroke.unwrap_or(1)),
+            WidgetType::Qr => {
+                let slot = self.widgets[..i].iter().filter(|widget| widget.kind == WidgetType::Qr).count();
+                resources.qr_codes.get(slot).map_or(Node::spacer(), Node::qr_code)
+            }
+            WidgetType::Sparkline => Node::chart(Sparkline::new(values, Rectangle::zero())
+                .with_scale(scale(Scale::fit(values)))
+                .with_threshold(threshold)
+                .with_color(widget.color())
+                .with_stroke_width(widget.stroke.unwrap_or(1))),
+            WidgetType::Bars => Node::chart(BarChart::new(values, Rectangle::zero())
+                .with_scale(scale(Scale::fit(values).including(0.0)))
+                .with_threshold(threshold)
+                .with_color(widget.color())),
+            WidgetType::Gauge => Node::chart(Gauge::new(last, Point::zero(), 0)
+                .with_scale(scale(Scale::new(0.0, 100.0)))
+                .with_threshold(threshold)
+                .with_color(widget.color())),
+        };
+
+        let node = match (widget.length, widget.flex, widget.kind) {
+            (Some(length), _, _) => node.with_length(Length::Fixed(length)),
+            (None, Some(weight), _) => node.with_length(Length::Flex(weight)),
+            // Charts have no natural size, so they take the remaining space by default.
+            (None, None, WidgetType::Sparkline | WidgetType::Bars | WidgetType::Gauge) => node.with_length(Length::Flex(1)),
+            (None, None, _) => node,
+        };
+
+        node.with_padding(Padding::all(widget.padding.unwrap_or(0)))
+            .with_spacing(widget.spacing.unwrap_or(0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Screen {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct ScreenMessage {
+            widgets: Widgets,
+        }
+
+        ScreenMessage::deserialize(deserializer).map(|message| message.widgets.0)
+    }
+}
+
+// The widgets are deserialized one at a time and added to the screen, so only one widget with its
+// own text and values is on the stack.
+struct Widgets(Screen);
+
+impl<'de> Deserialize<'de> for Widgets {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct WidgetsVisitor;
+
+        impl<'de> Visitor<'de> for WidgetsVisitor {
+            type Value = Widgets;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("a sequence of widgets")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Widgets, A::Error> {
+                let mut screen = Screen { widgets: Vec::new(), texts: String::new(), values: Vec::new() };
+
+                while let Some(widget) = seq.next_element::<WidgetMessage>()? {
+                    screen.push(widget).ok_or_else(|| A::Error::custom("screen too large"))?;
+                }
+
+                Ok(Widgets(screen))
+            }
+        }
+
+        deserializer.deserialize_seq(WidgetsVisitor)
+    }
+}
+
+struct Resources<'a> {
+    paragraphs: &'a [Paragraph<'a>],
+    qr_codes: &'a [QrCode],
+}
+
+impl Widget {
+    fn font(&self) -> &'static dyn Font {
+        self.font.unwrap_or(FontSize::Large).font(self.proportional.unwrap_or(true))
+    }
+
+    fn color(&self) -> ThreeColor {
+        self.color.map_or(ThreeColor::Black, ThreeColor::from)
+    }
+
+    fn align(&self) -> Alignment {
+        self.align.map_or(Alignment::Left, Alignment::from)
+    }
+}
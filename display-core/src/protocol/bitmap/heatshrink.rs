@@ -0,0 +1,83 @@
+/// Size of the window as power of two, which the encoder has to use as well.
+pub const WINDOW_BITS: u32 = 8;
+/// Size of the lookahead as power of two.
+pub const LOOKAHEAD_BITS: u32 = 4;
+
+const WINDOW_LEN: usize = 1 << WINDOW_BITS;
+
+// Bits of a literal and of a back reference, with their flag.
+const LITERAL_LEN: u32 = 1 + 8;
+const REFERENCE_LEN: u32 = 1 + WINDOW_BITS + LOOKAHEAD_BITS;
+
+/// Decodes heatshrink chunk by chunk, with the window as the only buffer. The input is a stream of
+/// bits, most significant first: `1` and 8 bits is a literal byte, `0`, `WINDOW_BITS` bits of the
+/// distance minus one and `LOOKAHEAD_BITS` bits of the count minus one repeats earlier output.
+pub struct Heatshrink {
+    /// Input bits that don't form a whole literal or back reference yet, the last `len` bits.
+    bits: u32,
+    len: u32,
+    window: [u8; WINDOW_LEN],
+    head: usize,
+}
+
+impl Heatshrink {
+    pub const fn new() -> Self {
+        Heatshrink { bits: 0, len: 0, window: [0; WINDOW_LEN], head: 0 }
+    }
+
+    /// Decodes the next chunk of the input, a literal or back reference may continue in the next
+    /// chunk.
+    pub fn decode(&mut self, input: &[u8], mut output: impl FnMut(u8)) {
+        for &byte in input {
+            self.bits = (self.bits << 8) | u32::from(byte);
+            self.len += 8;
+
+            while self.len > 0 {
+                let literal = self.peek(1) == 1;
+                let len = if literal { LITERAL_LEN } else { REFERENCE_LEN };
+
+                if self.len < len {
+                    break;
+                }
+
+                let item = self.peek(len) as usize;
+                self.len -= len;
+                self.bits &= (1 << self.len) - 1;
+
+                if literal {
+                    output(self.push(item as u8));
+                } else {
+                    let distance = ((item >> LOOKAHEAD_BITS) & (WINDOW_LEN - 1)) + 1;
+                    let count = (item & ((1 << LOOKAHEAD_BITS) - 1)) + 1;
+
+                    for _ in 0..count {
+                        let byte = self.window[self.head.wrapping_sub(distance) % WINDOW_LEN];
+                        output(self.push(byte));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The input so far ended with at most the zero bits that pad the last byte.
+    pub fn is_complete(&self) -> bool {
+        self.len < 8 && self.bits == 0
+    }
+
+    // The next `count` bits of the input.
+    fn peek(&self, count: u32) -> u32 {
+        (self.bits >> (self.len - count)) & ((1 << count) - 1)
+    }
+
+    fn push(&mut self, byte: u8) -> u8 {
+        self.window[self.head % WINDOW_LEN] = byte;
+        self.head = self.head.wrapping_add(1);
+        byte
+    }
+}
+
+impl Default for Heatshrink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
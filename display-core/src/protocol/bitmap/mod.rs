@@ -0,0 +1,225 @@
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_async::spi::SpiDevice;
+use epd_display::epd::epd_2in66b::{Epd2in66b, PlaneBuffer, HEIGHT, WIDTH};
+
+use crate::data::display_cmd::DisplayCmd;
+use super::connection::Handler;
+use super::response::Response;
+use super::MessageError;
+use heatshrink::Heatshrink;
+use packbits::PackBits;
+
+pub mod heatshrink;
+pub mod packbits;
+
+/// First bytes of a bitmap upload.
+pub const BITMAP_MAGIC: &[u8; 4] = b"EPDP";
+
+/// Magic, flags, and x, y, width and height as big endian `u16`.
+pub const BITMAP_HEADER_LEN: usize = 13;
+
+/// The display refreshes after the planes are copied, otherwise they wait for a later upload.
+pub const REFRESH_FLAG: u8 = 0x01;
+/// The planes are compressed with PackBits.
+pub const PACKBITS_FLAG: u8 = 0x02;
+/// The planes are compressed with heatshrink.
+pub const HEATSHRINK_FLAG: u8 = 0x04;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Encoding {
+    Raw,
+    PackBits,
+    Heatshrink,
+}
+
+/// Area of the planes in the layout of the display buffers, i.e. in portrait orientation with
+/// `x` and `width` multiples of 8.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct PlaneArea {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// Header of a bitmap upload, followed by the packed black and white and chromatic planes of the
+/// area, one bit per pixel with the most significant bit leftmost and `width / 8` bytes per row.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BitmapHeader {
+    pub area: PlaneArea,
+    pub refresh: bool,
+    pub encoding: Encoding,
+}
+
+impl BitmapHeader {
+    /// Decoded length of each plane.
+    pub fn plane_len(&self) -> usize {
+        self.area.width / 8 * self.area.height
+    }
+}
+
+/// Black and white and chromatic buffer of the display that uploads are decoded into.
+pub trait PlaneBuffers {
+    fn planes_mut(&mut self) -> (&mut PlaneBuffer, &mut PlaneBuffer);
+}
+
+impl<SPI, DC, RST, BUSY> PlaneBuffers for Epd2in66b<SPI, DC, RST, BUSY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+{
+    fn planes_mut(&mut self) -> (&mut PlaneBuffer, &mut PlaneBuffer) {
+        Epd2in66b::planes_mut(self)
+    }
+}
+
+impl<T: PlaneBuffers + ?Sized> PlaneBuffers for &mut T {
+    fn planes_mut(&mut self) -> (&mut PlaneBuffer, &mut PlaneBuffer) {
+        (**self).planes_mut()
+    }
+}
+
+/// Decodes the planes of an upload chunk by chunk straight into the buffers of the display, so an
+/// upload doesn't have to fit into the receive buffer.
+pub struct BitmapDecoder {
+    header: BitmapHeader,
+    decoder: Decoder,
+    /// Bytes decoded so far, including those beyond the planes.
+    len: usize,
+}
+
+// Only one exists at a time, so the window of heatshrink stays inline.
+#[allow(clippy::large_enum_variant)]
+enum Decoder {
+    Raw,
+    PackBits(PackBits),
+    Heatshrink(Heatshrink),
+}
+
+impl BitmapDecoder {
+    pub fn new(header: BitmapHeader) -> Self {
+        let decoder = match header.encoding {
+            Encoding::Raw => Decoder::Raw,
+            Encoding::PackBits => Decoder::PackBits(PackBits::new()),
+            Encoding::Heatshrink => Decoder::Heatshrink(Heatshrink::new()),
+        };
+
+        BitmapDecoder { header, decoder, len: 0 }
+    }
+
+    /// Decodes the next chunk of the data into the area, bytes beyond the planes are dropped.
+    pub fn decode(&mut self, data: &[u8], planes: &mut impl PlaneBuffers) {
+        let (bw, chromatic) = planes.planes_mut();
+        let (area, plane_len) = (self.header.area, self.header.plane_len());
+        let len = &mut self.len;
+
+        let mut write = |byte: u8| {
+            let i = *len;
+            *len = len.saturating_add(1);
+
+            if i >= 2 * plane_len {
+                return;
+            }
+
+            let (plane, i) = if i < plane_len { (&mut *bw, i) } else { (&mut *chromatic, i - plane_len) };
+            let index = (area.y + i / (area.width / 8)) * PlaneBuffer::STRIDE + area.x / 8 + i % (area.width / 8);
+
+            if let Some(target) = plane.buffer.get_mut(index) {
+                *target = byte;
+            }
+        };
+
+        match &mut self.decoder {
+            Decoder::Raw => data.iter().for_each(|&byte| write(byte)),
+            Decoder::PackBits(decoder) => decoder.decode(data, write),
+            Decoder::Heatshrink(decoder) => decoder.decode(data, write),
+        }
+    }
+
+    /// The data decoded so far is longer than both planes.
+    pub fn is_too_long(&self) -> bool {
+        self.len > 2 * self.header.plane_len()
+    }
+
+    /// The data decoded to exactly both planes and ended after a complete run.
+    pub fn is_complete(&self) -> bool {
+        let complete = match &self.decoder {
+            Decoder::Raw => true,
+            Decoder::PackBits(decoder) => decoder.is_complete(),
+            Decoder::Heatshrink(decoder) => decoder.is_complete(),
+        };
+
+        complete && self.len == 2 * self.header.plane_len()
+    }
+}
+
+/// Tells bitmap uploads apart from JSON messages.
+pub fn is_bitmap(buf: &[u8]) -> bool {
+    buf.starts_with(BITMAP_MAGIC)
+}
+
+/// Parses the header at the start of the buffer of an upload that is `len` bytes long in total. The
+/// area has to be inside the display, and uncompressed planes exactly as long as the area.
+pub fn parse_header(buf: &[u8], len: usize) -> Result<BitmapHeader, MessageError> {
+    let header = buf.get(..BITMAP_HEADER_LEN).ok_or(MessageError::InvalidBitmap)?;
+    let field = |i: usize| usize::from(u16::from_be_bytes([header[5 + 2 * i], header[6 + 2 * i]]));
+    let area = PlaneArea { x: field(0), y: field(1), width: field(2), height: field(3) };
+
+    let encoding = match header[4] & !REFRESH_FLAG {
+        0 => Encoding::Raw,
+        PACKBITS_FLAG => Encoding::PackBits,
+        HEATSHRINK_FLAG => Encoding::Heatshrink,
+        _ => return Err(MessageError::InvalidBitmap),
+    };
+
+    let aligned = area.x.is_multiple_of(8) && area.width.is_multiple_of(8) && area.width > 0 && area.height > 0;
+    let inside = area.x + area.width <= WIDTH && area.y + area.height <= HEIGHT;
+
+    if !is_bitmap(header) || !aligned || !inside {
+        return Err(MessageError::InvalidBitmap);
+    }
+
+    let header = BitmapHeader { area, refresh: header[4] & REFRESH_FLAG != 0, encoding };
+    let data_len = len.saturating_sub(BITMAP_HEADER_LEN);
+
+    match encoding {
+        Encoding::Raw if data_len != 2 * header.plane_len() => Err(MessageError::InvalidBitmap),
+        _ if data_len == 0 => Err(MessageError::InvalidBitmap),
+        _ => Ok(header),
+    }
+}
+
+/// Decodes an upload that was received as a whole into the planes of the handler, and shows them if
+/// the upload asks for a refresh.
+pub(super) async fn upload_bitmap<H: Handler>(buf: &[u8], handler: &mut H) -> Response {
+    let header = match parse_header(buf, buf.len()) {
+        Ok(header) => header,
+        Err(error) => return rejected(handler, error),
+    };
+
+    let mut decoder = BitmapDecoder::new(header);
+    decoder.decode(&buf[BITMAP_HEADER_LEN..], &mut handler.planes().await);
+
+    finish_upload(&decoder, handler)
+}
+
+/// Shows the planes of a decoded upload if it asks for a refresh. An upload that didn't decode to
+/// exactly both planes is rejected, what it decoded stays in the planes until they are drawn over.
+pub(super) fn finish_upload<H: Handler>(decoder: &BitmapDecoder, handler: &mut H) -> Response {
+    if !decoder.is_complete() {
+        return rejected(handler, MessageError::InvalidBitmap);
+    }
+
+    if decoder.header.refresh {
+        handler.show(DisplayCmd::Bitmap);
+    }
+
+    Response::accepted(None)
+}
+
+pub(super) fn rejected<H: Handler>(handler: &mut H, error: MessageError) -> Response {
+    handler.rejected(error);
+    Response::rejected(None, error)
+}
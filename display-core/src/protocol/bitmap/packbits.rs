@@ -0,0 +1,53 @@
+/// Decodes PackBits chunk by chunk. A header byte `n` is followed by `n + 1` literal bytes for
+/// `0..=127`, and by one byte repeated `1 - n` times for `-127..=-1`, `-128` is skipped.
+pub struct PackBits {
+    run: Run,
+}
+
+#[derive(Copy, Clone)]
+enum Run {
+    /// The next byte is a header.
+    Header,
+    /// Number of literal bytes left.
+    Literal(usize),
+    /// How often the next byte is repeated.
+    Repeat(usize),
+}
+
+impl PackBits {
+    pub const fn new() -> Self {
+        PackBits { run: Run::Header }
+    }
+
+    /// Decodes the next chunk of the input, a run may continue in the next chunk.
+    pub fn decode(&mut self, input: &[u8], mut output: impl FnMut(u8)) {
+        for &byte in input {
+            self.run = match self.run {
+                Run::Header => match byte as i8 {
+                    header @ 0.. => Run::Literal(header as usize + 1),
+                    -128 => Run::Header,
+                    header => Run::Repeat((1 - header as isize) as usize),
+                },
+                Run::Literal(left) => {
+                    output(byte);
+                    if left > 1 { Run::Literal(left - 1) } else { Run::Header }
+                }
+                Run::Repeat(count) => {
+                    (0..count).for_each(|_| output(byte));
+                    Run::Header
+                }
+            };
+        }
+    }
+
+    /// The input so far ended after a complete run.
+    pub fn is_complete(&self) -> bool {
+        matches!(self.run, Run::Header)
+    }
+}
+
+impl Default for PackBits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,68 @@
+use embedded_io_async::{Read, Write};
+
+use crate::data::display_cmd::DisplayCmd;
+use super::bitmap::PlaneBuffers;
+use super::framing::{Frame, FrameReader};
+use super::response::{Response, MAX_RESPONSE_LEN};
+use super::{parse_request, MessageError, Request};
+
+/// Receives the commands of a connection.
+pub trait Handler {
+    /// Passes the command on to the display and returns its sequence number.
+    fn show(&mut self, cmd: DisplayCmd) -> u32;
+
+    /// Waits until the command with the sequence number, or a later one, is shown on the display.
+    fn refreshed(&mut self, sequence: u32) -> impl Future<Output = ()>;
+
+    /// Buffers of the display that bitmap uploads are decoded into.
+    type Planes<'a>: PlaneBuffers where Self: 'a;
+
+    /// Locks the buffers of the display for a bitmap upload, the display isn't drawn or refreshed
+    /// until they are dropped.
+    fn planes(&mut self) -> impl Future<Output = Self::Planes<'_>>;
+
+    fn rejected(&mut self, _error: MessageError) {}
+}
+
+/// The command with the `refreshed` sequence number is the one with `sequence` or a later one. The
+/// sequence numbers wrap around, commands up to half the range ahead count as later.
+pub fn is_refreshed(refreshed: u32, sequence: u32) -> bool {
+    refreshed.wrapping_sub(sequence) as i32 >= 0
+}
+
+/// Handles a connection until the peer closes it. Messages are newline-delimited JSON and every
+/// message is answered with a `Response` line. Messages asking for `notify_refresh` get a second
+/// response once they are shown, the next message is read after that.
+pub async fn serve<C, H>(connection: &mut C, buf: &mut [u8], handler: &mut H) -> Result<(), C::Error>
+where
+    C: Read + Write,
+    H: Handler,
+{
+    let mut frames = FrameReader::new(buf);
+    let mut response = [0; MAX_RESPONSE_LEN];
+
+    while let Some(frame) = frames.next(connection).await? {
+        let request = match frame {
+            Frame::Message(message) => parse_request(message),
+            Frame::TooLong => Request { id: None, notify_refresh: false, cmd: Err(MessageError::TooLong) },
+        };
+
+        match request.cmd {
+            Ok(cmd) => {
+                let sequence = handler.show(cmd);
+                connection.write_all(Response::accepted(request.id).write_line(&mut response)).await?;
+
+                if request.notify_refresh {
+                    handler.refreshed(sequence).await;
+                    connection.write_all(Response::refreshed(request.id).write_line(&mut response)).await?;
+                }
+            }
+            Err(error) => {
+                handler.rejected(error);
+                connection.write_all(Response::rejected(request.id, error).write_line(&mut response)).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
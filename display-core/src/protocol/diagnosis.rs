@@ -0,0 +1,131 @@
+use core::fmt;
+use core::marker::PhantomData;
+use serde::Deserialize;
+use serde::de::{DeserializeOwned, Deserializer, SeqAccess, Visitor};
+
+use crate::data::display_cmd::{MAX_BODY_LINES, QR_TEXT_CAPACITY, STRING_CAPACITY};
+use super::MessageError;
+
+// The capacity errors of heapless all end up as `CustomError` in serde-json-core, so a rejected
+// text message is parsed again with lengths and counts only.
+
+#[derive(Deserialize)]
+#[serde(bound(deserialize = "T: LineLength, B: LineLength"))]
+struct TextShape<T, B> {
+    pub title: T,
+    pub body: Lines<B>,
+    pub qr: Option<QrCodeShape>,
+}
+
+#[derive(Deserialize)]
+struct QrCodeShape {
+    pub text: Length,
+}
+
+#[derive(Deserialize)]
+struct LineShape {
+    pub text: Length,
+}
+
+/// Length of a string without storing it.
+struct Length(usize);
+
+/// Number of lines and the length of the longest one.
+struct Lines<B> {
+    count: usize,
+    longest: usize,
+    line: PhantomData<B>,
+}
+
+trait LineLength: DeserializeOwned {
+    fn length(&self) -> usize;
+}
+
+impl LineLength for Length {
+    fn length(&self) -> usize {
+        self.0
+    }
+}
+
+impl LineLength for LineShape {
+    fn length(&self) -> usize {
+        self.text.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Length {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LengthVisitor;
+
+        impl Visitor<'_> for LengthVisitor {
+            type Value = Length;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E>(self, text: &str) -> Result<Length, E> {
+                Ok(Length(text.len()))
+            }
+        }
+
+        deserializer.deserialize_str(LengthVisitor)
+    }
+}
+
+impl<'de, B: LineLength> Deserialize<'de> for Lines<B> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LinesVisitor<B>(PhantomData<B>);
+
+        impl<'de, B: LineLength> Visitor<'de> for LinesVisitor<B> {
+            type Value = Lines<B>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence of lines")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Lines<B>, A::Error> {
+                let mut lines = Lines { count: 0, longest: 0, line: PhantomData };
+
+                while let Some(line) = seq.next_element::<B>()? {
+                    lines.count += 1;
+                    lines.longest = lines.longest.max(line.length());
+                }
+
+                Ok(lines)
+            }
+        }
+
+        deserializer.deserialize_seq(LinesVisitor(PhantomData))
+    }
+}
+
+/// Tells why a message that isn't a valid command was rejected.
+pub(super) fn diagnose(buf: &[u8]) -> MessageError {
+    let mut unescape_buffer = [0; QR_TEXT_CAPACITY];
+
+    check::<Length, Length>(buf, &mut unescape_buffer)
+        .or_else(|| check::<Length, LineShape>(buf, &mut unescape_buffer))
+        .or_else(|| check::<LineShape, Length>(buf, &mut unescape_buffer))
+        .or_else(|| check::<LineShape, LineShape>(buf, &mut unescape_buffer))
+        .unwrap_or(MessageError::Invalid)
+}
+
+fn check<T: LineLength, B: LineLength>(buf: &[u8], unescape_buffer: &mut [u8]) -> Option<MessageError> {
+    let shape = match serde_json_core::from_slice_escaped::<TextShape<T, B>>(buf, unescape_buffer) {
+        Ok((shape, _)) => shape,
+        Err(serde_json_core::de::Error::EscapedStringIsTooLong) => return Some(MessageError::StringTooLong),
+        Err(_) => return None,
+    };
+
+    let longest = shape.title.length().max(shape.body.longest);
+    let qr_code = shape.qr.map_or(0, |qr| qr.text.0);
+
+    if shape.body.count > MAX_BODY_LINES {
+        Some(MessageError::TooManyLines)
+    } else if longest > STRING_CAPACITY || qr_code > QR_TEXT_CAPACITY {
+        Some(MessageError::StringTooLong)
+    } else {
+        Some(MessageError::Invalid)
+    }
+}
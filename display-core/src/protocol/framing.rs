@@ -0,0 +1,80 @@
+use embedded_io_async::Read;
+
+/// One newline-delimited frame of the stream.
+#[derive(PartialEq, Debug)]
+pub enum Frame<'a> {
+    /// Content of the frame without the line ending and surrounding whitespace.
+    Message(&'a [u8]),
+    /// The frame didn't fit into the buffer and was dropped.
+    TooLong,
+}
+
+/// Splits a stream into newline-delimited frames, independent of how the stream is split into reads.
+/// A frame, without its newline, has to be shorter than the buffer. Empty lines are skipped and a
+/// frame without newline at the end of the stream still counts.
+pub struct FrameReader<'a> {
+    buf: &'a mut [u8],
+    start: usize,
+    end: usize,
+    discarding: bool,
+}
+
+impl<'a> FrameReader<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        FrameReader { buf, start: 0, end: 0, discarding: false }
+    }
+
+    /// Next frame, or `None` at the end of the stream.
+    pub async fn next<R: Read>(&mut self, reader: &mut R) -> Result<Option<Frame<'_>>, R::Error> {
+        loop {
+            if let Some(pos) = self.buf[self.start..self.end].iter().position(|&byte| byte == b'\n') {
+                let frame = self.start..self.start + pos;
+                self.start = frame.end + 1;
+
+                if self.discarding {
+                    self.discarding = false;
+                    return Ok(Some(Frame::TooLong));
+                }
+
+                if self.buf[frame.clone()].trim_ascii().is_empty() {
+                    continue;
+                }
+
+                return Ok(Some(Frame::Message(self.buf[frame].trim_ascii())));
+            }
+
+            // Keep the incomplete frame at the start of the buffer.
+            if self.discarding {
+                self.end = self.start;
+            }
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+
+            if self.end == self.buf.len() {
+                self.discarding = true;
+                self.end = 0;
+            }
+
+            let n = reader.read(&mut self.buf[self.end..]).await?;
+
+            if n == 0 {
+                return Ok(self.finish());
+            }
+
+            self.end += n;
+        }
+    }
+
+    fn finish(&mut self) -> Option<Frame<'_>> {
+        let frame = self.start..self.end;
+        self.start = self.end;
+
+        if core::mem::take(&mut self.discarding) {
+            return Some(Frame::TooLong);
+        }
+
+        let message = self.buf[frame].trim_ascii();
+        (!message.is_empty()).then_some(Frame::Message(message))
+    }
+}
@@ -0,0 +1,370 @@
+use core::fmt::Write as _;
+use embedded_io_async::{Read, Write};
+use heapless::String;
+use serde::Serialize;
+
+use crate::data::display_cmd::DisplayCmd;
+use super::bitmap::{finish_upload, parse_header, rejected, BitmapDecoder, BITMAP_HEADER_LEN};
+use super::connection::Handler;
+use super::response::{Response, MAX_RESPONSE_LEN};
+use super::{parse_request, MessageError};
+
+// Length of the `DisplayStatus` with the largest values.
+const MAX_STATUS_LEN: usize = 128;
+
+/// Page for composing a text message, served at `GET /`.
+pub const INDEX_PAGE: &[u8] = include_bytes!("../../web/index.html");
+
+const JSON: &str = "application/json";
+const HTML: &str = "text/html; charset=utf-8";
+
+/// Handler for the routes of the REST API besides the commands.
+pub trait HttpHandler: Handler {
+    fn status(&mut self) -> DisplayStatus;
+}
+
+/// Body of `GET /status`.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct DisplayStatus {
+    /// Sequence number of the latest command.
+    pub sequence: u32,
+    /// Sequence number of the command on the display.
+    pub refreshed: u32,
+    pub sleeping: bool,
+    /// Seconds since start.
+    pub uptime: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Method {
+    Get,
+    Post,
+    Other,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Route {
+    Index,
+    Display,
+    Bitmap,
+    Status,
+    Clear,
+    Sleep,
+}
+
+impl Route {
+    fn from_path(path: &[u8]) -> Option<Self> {
+        let path = path.split(|&byte| byte == b'?').next().unwrap_or(path);
+
+        match path {
+            b"/" | b"/index.html" => Some(Route::Index),
+            b"/display" => Some(Route::Display),
+            b"/bitmap" => Some(Route::Bitmap),
+            b"/status" => Some(Route::Status),
+            b"/clear" => Some(Route::Clear),
+            b"/sleep" => Some(Route::Sleep),
+            _ => None,
+        }
+    }
+
+    fn method(self) -> (Method, &'static str) {
+        match self {
+            Route::Index | Route::Status => (Method::Get, "GET"),
+            Route::Display | Route::Bitmap | Route::Clear | Route::Sleep => (Method::Post, "POST"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum HttpStatus {
+    Ok,
+    BadRequest,
+    NotFound,
+    /// With the method that is allowed.
+    MethodNotAllowed(&'static str),
+    LengthRequired,
+    PayloadTooLarge,
+    HeadersTooLarge,
+    NotImplemented,
+    VersionNotSupported,
+}
+
+impl HttpStatus {
+    fn code(self) -> u16 {
+        match self {
+            HttpStatus::Ok => 200,
+            HttpStatus::BadRequest => 400,
+            HttpStatus::NotFound => 404,
+            HttpStatus::MethodNotAllowed(_) => 405,
+            HttpStatus::LengthRequired => 411,
+            HttpStatus::PayloadTooLarge => 413,
+            HttpStatus::HeadersTooLarge => 431,
+            HttpStatus::NotImplemented => 501,
+            HttpStatus::VersionNotSupported => 505,
+        }
+    }
+
+    fn reason(self) -> &'static str {
+        match self {
+            HttpStatus::Ok => "OK",
+            HttpStatus::BadRequest => "Bad Request",
+            HttpStatus::NotFound => "Not Found",
+            HttpStatus::MethodNotAllowed(_) => "Method Not Allowed",
+            HttpStatus::LengthRequired => "Length Required",
+            HttpStatus::PayloadTooLarge => "Payload Too Large",
+            HttpStatus::HeadersTooLarge => "Request Header Fields Too Large",
+            HttpStatus::NotImplemented => "Not Implemented",
+            HttpStatus::VersionNotSupported => "HTTP Version Not Supported",
+        }
+    }
+}
+
+/// Request line and the headers that matter.
+struct Head {
+    method: Method,
+    route: Option<Route>,
+    content_length: Option<usize>,
+}
+
+/// Handles one HTTP/1.1 request and closes the connection with the response. The request head and
+/// body each have to fit into the buffer, except the body of a bitmap upload, which is decoded while
+/// it is received.
+///
+/// - `GET /` answers with the `INDEX_PAGE`
+/// - `POST /display` takes a message like the TCP protocol and answers with its `Response`
+/// - `POST /bitmap` takes a bitmap upload and answers with its `Response`
+/// - `GET /status` answers with the `DisplayStatus`
+/// - `POST /clear` blanks the display
+/// - `POST /sleep` keeps the display asleep until the next command
+pub async fn serve_http<C, H>(connection: &mut C, buf: &mut [u8], handler: &mut H) -> Result<(), C::Error>
+where
+    C: Read + Write,
+    H: HttpHandler,
+{
+    let mut filled = 0;
+    let head_len = loop {
+        if let Some(pos) = buf[..filled].windows(4).position(|window| window == b"\r\n\r\n") {
+            break pos + 4;
+        }
+
+        if filled == buf.len() {
+            return respond(connection, HttpStatus::HeadersTooLarge, b"").await;
+        }
+
+        let n = connection.read(&mut buf[filled..]).await?;
+
+        if n == 0 {
+            return Ok(());
+        }
+
+        filled += n;
+    };
+
+    let head = match parse_head(&buf[..head_len]) {
+        Ok(head) => head,
+        Err(status) => return respond(connection, status, b"").await,
+    };
+
+    let Some(route) = head.route else {
+        return respond(connection, HttpStatus::NotFound, b"").await;
+    };
+
+    let (method, method_name) = route.method();
+
+    if head.method != method {
+        return respond(connection, HttpStatus::MethodNotAllowed(method_name), b"").await;
+    }
+
+    let mut response = [0; MAX_RESPONSE_LEN];
+
+    match route {
+        Route::Index => respond_with(connection, HttpStatus::Ok, HTML, INDEX_PAGE).await,
+        Route::Display | Route::Bitmap => {
+            let Some(content_length) = head.content_length else {
+                return respond(connection, HttpStatus::LengthRequired, b"").await;
+            };
+
+            // Part of the body may have been read with the head.
+            buf.copy_within(head_len..filled, 0);
+            filled = (filled - head_len).min(content_length);
+
+            if route == Route::Bitmap {
+                let Some(answer) = receive_bitmap(connection, buf, filled, content_length, handler).await? else { return Ok(()) };
+                let status = if answer.error.is_some() { HttpStatus::BadRequest } else { HttpStatus::Ok };
+
+                return respond(connection, status, answer.write_line(&mut response)).await;
+            }
+
+            if content_length > buf.len() {
+                let body = Response::rejected(None, MessageError::TooLong).write_line(&mut response);
+                return respond(connection, HttpStatus::PayloadTooLarge, body).await;
+            }
+
+            while filled < content_length {
+                let n = connection.read(&mut buf[filled..content_length]).await?;
+
+                if n == 0 {
+                    return Ok(());
+                }
+
+                filled += n;
+            }
+
+            let answer = show_message(&buf[..content_length], handler).await;
+            let status = if answer.error.is_some() { HttpStatus::BadRequest } else { HttpStatus::Ok };
+
+            respond(connection, status, answer.write_line(&mut response)).await
+        }
+        Route::Status => {
+            let mut body = [0; MAX_STATUS_LEN];
+            let len = serde_json_core::to_slice(&handler.status(), &mut body).unwrap_or(0);
+
+            respond(connection, HttpStatus::Ok, &body[..len]).await
+        }
+        Route::Clear | Route::Sleep => {
+            handler.show(if route == Route::Clear { DisplayCmd::Clear } else { DisplayCmd::Sleep });
+            respond(connection, HttpStatus::Ok, Response::accepted(None).write_line(&mut response)).await
+        }
+    }
+}
+
+async fn show_message<H: Handler>(body: &[u8], handler: &mut H) -> Response {
+    let request = parse_request(body);
+
+    match request.cmd {
+        Ok(cmd) => {
+            let sequence = handler.show(cmd);
+
+            if request.notify_refresh {
+                handler.refreshed(sequence).await;
+                Response::refreshed(request.id)
+            } else {
+                Response::accepted(request.id)
+            }
+        }
+        Err(error) => {
+            handler.rejected(error);
+            Response::rejected(request.id, error)
+        }
+    }
+}
+
+/// Decodes the bitmap upload in the body while it is received, with the first `filled` bytes of the
+/// body in the buffer. `None` if the peer closed the connection before the end of the body.
+async fn receive_bitmap<C, H>(connection: &mut C, buf: &mut [u8], mut filled: usize, content_length: usize, handler: &mut H) -> Result<Option<Response>, C::Error>
+where
+    C: Read,
+    H: Handler,
+{
+    let header_len = BITMAP_HEADER_LEN.min(content_length).min(buf.len());
+
+    while filled < header_len {
+        let n = connection.read(&mut buf[filled..header_len]).await?;
+
+        if n == 0 {
+            return Ok(None);
+        }
+
+        filled += n;
+    }
+
+    let header = match parse_header(&buf[..filled], content_length) {
+        Ok(header) => header,
+        Err(error) => return Ok(Some(rejected(handler, error))),
+    };
+
+    let mut decoder = BitmapDecoder::new(header);
+    let mut planes = handler.planes().await;
+    decoder.decode(&buf[BITMAP_HEADER_LEN..filled], &mut planes);
+
+    // Data that decodes to more than the planes is rejected without reading the rest.
+    while filled < content_length && !decoder.is_too_long() {
+        let len = (content_length - filled).min(buf.len());
+        let n = connection.read(&mut buf[..len]).await?;
+
+        if n == 0 {
+            return Ok(None);
+        }
+
+        decoder.decode(&buf[..n], &mut planes);
+        filled += n;
+    }
+
+    drop(planes);
+    Ok(Some(finish_upload(&decoder, handler)))
+}
+
+fn parse_head(head: &[u8]) -> Result<Head, HttpStatus> {
+    let mut lines = head.split(|&byte| byte == b'\n').map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+    let request_line = lines.next().unwrap_or_default();
+
+    let mut parts = request_line.split(|&byte| byte == b' ');
+    let (Some(method), Some(path), Some(version), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return Err(HttpStatus::BadRequest);
+    };
+
+    match version {
+        b"HTTP/1.1" | b"HTTP/1.0" => {}
+        _ if version.starts_with(b"HTTP/") => return Err(HttpStatus::VersionNotSupported),
+        _ => return Err(HttpStatus::BadRequest),
+    }
+
+    let method = match method {
+        b"GET" => Method::Get,
+        b"POST" => Method::Post,
+        _ => Method::Other,
+    };
+
+    let mut content_length = None;
+
+    for line in lines.filter(|line| !line.is_empty()) {
+        let Some(colon) = line.iter().position(|&byte| byte == b':') else {
+            return Err(HttpStatus::BadRequest);
+        };
+        let (name, value) = (&line[..colon], line[colon + 1..].trim_ascii());
+
+        if name.eq_ignore_ascii_case(b"content-length") {
+            let length = core::str::from_utf8(value).ok().and_then(|value| value.parse().ok());
+            let length = length.ok_or(HttpStatus::BadRequest)?;
+
+            // A repeated header has to agree, or the end of the body is ambiguous.
+            if content_length.is_some_and(|content_length| content_length != length) {
+                return Err(HttpStatus::BadRequest);
+            }
+
+            content_length = Some(length);
+        } else if name.eq_ignore_ascii_case(b"transfer-encoding") {
+            return Err(HttpStatus::NotImplemented);
+        }
+    }
+
+    Ok(Head { method, route: Route::from_path(path), content_length })
+}
+
+async fn respond<C: Write>(connection: &mut C, status: HttpStatus, body: &[u8]) -> Result<(), C::Error> {
+    respond_with(connection, status, JSON, body).await
+}
+
+async fn respond_with<C: Write>(
+    connection: &mut C,
+    status: HttpStatus,
+    content_type: &str,
+    body: &[u8],
+) -> Result<(), C::Error> {
+    let mut head: String<160> = String::new();
+    let _ = write!(head, "HTTP/1.1 {} {}\r\n", status.code(), status.reason());
+
+    if let HttpStatus::MethodNotAllowed(method) = status {
+        let _ = write!(head, "Allow: {}\r\n", method);
+    }
+
+    if !body.is_empty() {
+        let _ = write!(head, "Content-Type: {}\r\n", content_type);
+    }
+
+    let _ = write!(head, "Content-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+
+    connection.write_all(head.as_bytes()).await?;
+    connection.write_all(body).await?;
+    connection.flush().await
+}
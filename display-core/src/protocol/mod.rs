@@ -0,0 +1,303 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::Alignment;
+use heapless::{String, Vec};
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::epd::epd_2in66b::{HEIGHT, WIDTH};
+use epd_display::epd::three_color::ThreeColor;
+use crate::data::display_cmd::{ChartContent, ChartKind, DisplayCmd, QrCodeContent, RefreshMode, TextLine, TextPanelContent, MAX_BODY_LINES, MAX_CHART_VALUES, QR_TEXT_CAPACITY, STRING_CAPACITY, UNIT_CAPACITY};
+use crate::data::message_color::{InkColor, MessageColor};
+use crate::data::screen::{Align, FontSize, Screen};
+
+pub mod bitmap;
+pub mod connection;
+mod diagnosis;
+pub mod framing;
+pub mod http;
+pub mod mqtt;
+pub mod response;
+
+/// Reason a message was rejected.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageError {
+    /// The message isn't a valid JSON message.
+    Invalid,
+    TooManyLines,
+    StringTooLong,
+    /// The message is longer than the receive buffer.
+    TooLong,
+    /// The widgets of a screen don't form a valid tree, or don't fit the display in any
+    /// orientation.
+    InvalidScreen,
+    /// The bitmap upload has an invalid header, an area that isn't byte aligned or outside the
+    /// display, or planes of the wrong length.
+    InvalidBitmap,
+}
+
+/// Fields that every message may have besides its content.
+#[derive(Deserialize, Default)]
+struct Envelope {
+    pub id: Option<u32>,
+    pub notify_refresh: Option<bool>,
+}
+
+/// A message with its command.
+pub struct Request {
+    /// Id chosen by the client, repeated in the responses.
+    pub id: Option<u32>,
+    /// The client wants a second response once the command is shown on the display.
+    pub notify_refresh: bool,
+    pub cmd: Result<DisplayCmd, MessageError>,
+}
+
+// Title and body lines are either plain strings or `LineMessage` objects.
+#[derive(Deserialize)]
+struct TextMessage<T, B> {
+    pub title: T,
+    pub body: Vec<B, MAX_BODY_LINES>,
+    pub qr: Option<QrCodeMessage>,
+}
+
+#[derive(Deserialize)]
+struct LineMessage {
+    pub text: String<STRING_CAPACITY>,
+    pub color: Option<MessageColor>,
+    pub font: Option<FontSize>,
+    pub align: Option<Align>,
+}
+
+trait Line: DeserializeOwned {
+    fn text_line(&self) -> TextLine;
+}
+
+impl Line for String<STRING_CAPACITY> {
+    fn text_line(&self) -> TextLine {
+        TextLine::new(self, ThreeColor::Black)
+    }
+}
+
+impl Line for LineMessage {
+    fn text_line(&self) -> TextLine {
+        TextLine::new(&self.text, self.color.map_or(ThreeColor::Black, ThreeColor::from))
+            .with_font_size(self.font.unwrap_or(FontSize::Large))
+            .with_alignment(self.align.map_or(Alignment::Left, Alignment::from))
+    }
+}
+
+#[derive(Deserialize)]
+struct QrCodeMessage {
+    pub text: String<QR_TEXT_CAPACITY>,
+    pub color: Option<InkColor>,
+}
+
+#[derive(Deserialize)]
+struct ScreenMessage {
+    pub screen: Screen,
+}
+
+#[derive(Deserialize)]
+struct ChartMessage {
+    pub title: String<STRING_CAPACITY>,
+    pub chart: ChartData,
+}
+
+#[derive(Deserialize)]
+struct ChartData {
+    #[serde(rename = "type")]
+    pub kind: ChartType,
+    pub values: Vec<f32, MAX_CHART_VALUES>,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    pub threshold: Option<f32>,
+    pub unit: Option<String<UNIT_CAPACITY>>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum ChartType {
+    Sparkline,
+    Bars,
+    Gauge,
+}
+
+impl From<ChartType> for ChartKind {
+    fn from(kind: ChartType) -> Self {
+        match kind {
+            ChartType::Sparkline => ChartKind::Sparkline,
+            ChartType::Bars => ChartKind::Bars,
+            ChartType::Gauge => ChartKind::Gauge,
+        }
+    }
+}
+
+/// Command without content, e.g. `{"command": "orientation", "orientation": "portrait"}`.
+#[derive(Deserialize)]
+struct CommandMessage {
+    pub command: CommandType,
+    pub mode: Option<RefreshType>,
+    pub orientation: Option<OrientationType>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum CommandType {
+    Clear,
+    Sleep,
+    Refresh,
+    Orientation,
+    Status,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum RefreshType {
+    Full,
+    #[serde(alias = "plain")]
+    Partial,
+}
+
+impl From<RefreshType> for RefreshMode {
+    fn from(mode: RefreshType) -> Self {
+        match mode {
+            RefreshType::Full => RefreshMode::Full,
+            RefreshType::Partial => RefreshMode::Plain,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum OrientationType {
+    Portrait,
+    Landscape,
+    PortraitFlipped,
+    LandscapeFlipped,
+}
+
+impl From<OrientationType> for DisplayOrientation {
+    fn from(orientation: OrientationType) -> Self {
+        match orientation {
+            OrientationType::Portrait => DisplayOrientation::Portrait,
+            OrientationType::Landscape => DisplayOrientation::Landscape,
+            OrientationType::PortraitFlipped => DisplayOrientation::PortraitFlipped,
+            OrientationType::LandscapeFlipped => DisplayOrientation::LandscapeFlipped,
+        }
+    }
+}
+
+/// Parses a message and its optional `id` and `notify_refresh` fields.
+pub fn parse_request(buf: &[u8]) -> Request {
+    let mut unescape_buffer = [0; QR_TEXT_CAPACITY];
+    let envelope = serde_json_core::from_slice_escaped::<Envelope>(buf, &mut unescape_buffer)
+        .map_or(Envelope::default(), |(envelope, _)| envelope);
+
+    Request {
+        id: envelope.id,
+        notify_refresh: envelope.notify_refresh.unwrap_or(false),
+        cmd: parse_message(buf),
+    }
+}
+
+/// Parses a JSON text, chart, screen or command message.
+pub fn parse_message(buf: &[u8]) -> Result<DisplayCmd, MessageError> {
+    let cmd = parse(buf).ok_or_else(|| diagnosis::diagnose(buf))?;
+
+    match &cmd {
+        DisplayCmd::Screen(screen) if !fits_display(screen) => Err(MessageError::InvalidScreen),
+        _ => Ok(cmd),
+    }
+}
+
+// The orientation may change until the screen is drawn, so it only has to fit one of them. It is
+// validated against the orientation of the display again when drawn.
+fn fits_display(screen: &Screen) -> bool {
+    let portrait = Size::new(WIDTH as u32, HEIGHT as u32);
+    let landscape = Size::new(HEIGHT as u32, WIDTH as u32);
+
+    screen.validate(portrait).is_ok() || screen.validate(landscape).is_ok()
+}
+
+fn parse(buf: &[u8]) -> Option<DisplayCmd> {
+    // Strings are unescaped one at a time, so the buffer only needs to hold the longest string.
+    let mut unescape_buffer = [0; QR_TEXT_CAPACITY];
+
+    // Command, screen and chart messages are told apart from text messages by their required
+    // `command`, `screen` and `chart` fields.
+    if let Ok((message, _)) = serde_json_core::from_slice_escaped::<CommandMessage>(buf, &mut unescape_buffer) {
+        return command(message);
+    }
+
+    if let Ok((ScreenMessage { screen }, _)) = serde_json_core::from_slice_escaped::<ScreenMessage>(buf, &mut unescape_buffer) {
+        return Some(DisplayCmd::Screen(screen));
+    }
+
+    if let Ok((message, _)) = serde_json_core::from_slice_escaped::<ChartMessage>(buf, &mut unescape_buffer) {
+        return Some(DisplayCmd::Chart(chart_content(message)));
+    }
+
+    // serde-json-core can't tell strings and objects apart while parsing, so every combination of
+    // plain and styled title and body lines is tried. All body lines have to be of the same kind.
+    type Plain = String<STRING_CAPACITY>;
+
+    text_panel::<Plain, Plain>(buf, &mut unescape_buffer)
+        .or_else(|| text_panel::<Plain, LineMessage>(buf, &mut unescape_buffer))
+        .or_else(|| text_panel::<LineMessage, Plain>(buf, &mut unescape_buffer))
+        .or_else(|| text_panel::<LineMessage, LineMessage>(buf, &mut unescape_buffer))
+        .map(DisplayCmd::TextPanel)
+}
+
+fn text_panel<T: Line, B: Line>(buf: &[u8], unescape_buffer: &mut [u8]) -> Option<TextPanelContent> {
+    let (TextMessage::<T, B> { title, body, qr }, _) = serde_json_core::from_slice_escaped(buf, unescape_buffer).ok()?;
+    let mut content = TextPanelContent::new(title.text_line());
+
+    for body_line in body {
+        content.add_body_line(body_line.text_line()).ok()?;
+    }
+
+    if let Some(QrCodeMessage { text, color }) = qr {
+        let color = color.map_or(ThreeColor::Black, ThreeColor::from);
+        content.set_qr_code(QrCodeContent::new(&text, color));
+    }
+
+    Some(content)
+}
+
+fn command(message: CommandMessage) -> Option<DisplayCmd> {
+    match message.command {
+        CommandType::Clear => Some(DisplayCmd::Clear),
+        CommandType::Sleep => Some(DisplayCmd::Sleep),
+        CommandType::Refresh => Some(DisplayCmd::Refresh(message.mode.map_or(RefreshMode::Full, RefreshMode::from))),
+        CommandType::Orientation => message.orientation.map(|orientation| DisplayCmd::SetOrientation(orientation.into())),
+        CommandType::Status => Some(DisplayCmd::ShowStatus),
+    }
+}
+
+fn chart_content(message: ChartMessage) -> ChartContent {
+    let ChartMessage { title, chart } = message;
+    let mut content = ChartContent::new(TextLine::new(&title, ThreeColor::Black), chart.kind.into());
+
+    for value in chart.values {
+        let _ = content.add_value(value);
+    }
+
+    if let Some(min) = chart.min {
+        content.set_min(min);
+    }
+
+    if let Some(max) = chart.max {
+        content.set_max(max);
+    }
+
+    if let Some(threshold) = chart.threshold {
+        content.set_threshold(threshold);
+    }
+
+    if let Some(unit) = chart.unit {
+        content.set_unit(&unit);
+    }
+
+    content
+}
@@ -0,0 +1,182 @@
+use embassy_futures::select::{select, Either};
+use embedded_io_async::{Read, Write};
+use serde::Serialize;
+
+use super::bitmap::upload_bitmap;
+use super::connection::{is_refreshed, Handler};
+use super::response::{Response, MAX_RESPONSE_LEN};
+use super::{parse_request, MessageError, Request};
+use packet::{Packet, PacketReader, Will};
+
+mod packet;
+
+// Topics below the base topic of the device.
+const DISPLAY_TOPIC: &str = "/display";
+const BITMAP_TOPIC: &str = "/bitmap";
+const STATUS_TOPIC: &str = "/status";
+const REFRESHED_TOPIC: &str = "/refreshed";
+const RESPONSE_TOPIC: &str = "/response";
+
+const ONLINE: &str = "online";
+const OFFLINE: &str = "offline";
+
+const SUBSCRIBE_ID: u16 = 1;
+
+// Length of the `Refresh` with the largest values.
+const MAX_REFRESH_LEN: usize = 48;
+
+pub struct MqttConfig<'a> {
+    pub client_id: &'a str,
+    /// Base topic of the device, e.g. `display/door`.
+    pub topic: &'a str,
+    pub username: Option<&'a str>,
+    /// Only sent with a username.
+    pub password: Option<&'a str>,
+    /// Seconds the broker waits for a packet before it disconnects the client, 0 turns it off.
+    pub keep_alive: u16,
+}
+
+/// Payload of the refreshed topic.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct Refresh {
+    /// Sequence number of the command on the display.
+    pub sequence: u32,
+    /// Seconds since start at the refresh.
+    pub uptime: u64,
+}
+
+pub trait MqttHandler: Handler {
+    /// Waits up to the number of seconds for the next refresh of the display.
+    fn next_refresh(&mut self, timeout: u16) -> impl Future<Output = Option<Refresh>>;
+}
+
+#[derive(PartialEq, Debug)]
+pub enum MqttError<E> {
+    Connection(E),
+    /// The broker refused the connection with the return code of its CONNACK.
+    Refused(u8),
+    /// The broker refused the subscription of the display or bitmap topic.
+    SubscriptionRefused,
+    /// The broker didn't answer a ping within the keep alive.
+    Timeout,
+    /// The broker sent something that isn't MQTT 3.1.1.
+    Protocol,
+}
+
+/// Runs an MQTT 3.1.1 session on a connection to the broker until the broker closes it.
+///
+/// Messages published to the display topic are shown like messages of the TCP protocol, and bitmap
+/// uploads published to the bitmap topic like `POST /bitmap`, with their `Response` published to the
+/// response topic. The status topic holds `online`, or `offline` as
+/// will once the connection is lost, and the refreshed topic the latest `Refresh`, both retained.
+pub async fn serve_mqtt<C, H>(connection: &mut C, buf: &mut [u8], config: &MqttConfig<'_>, handler: &mut H) -> Result<(), MqttError<C::Error>>
+where
+    C: Read + Write,
+    H: MqttHandler,
+{
+    let topic = |suffix| (config.topic, suffix);
+    let mut packets = PacketReader::new(buf);
+    let mut response = [0; MAX_RESPONSE_LEN];
+
+    let will = Will { topic: topic(STATUS_TOPIC), message: OFFLINE };
+    packet::connect(connection, config.client_id, config.username, config.password, will, config.keep_alive).await.map_err(MqttError::Connection)?;
+
+    match packets.next(connection).await.map_err(MqttError::Connection)? {
+        None => return Ok(()),
+        Some(Ok(Packet::ConnAck { return_code: 0 })) => {}
+        Some(Ok(Packet::ConnAck { return_code })) => return Err(MqttError::Refused(return_code)),
+        Some(_) => return Err(MqttError::Protocol),
+    }
+
+    packet::subscribe(connection, SUBSCRIBE_ID, [topic(DISPLAY_TOPIC), topic(BITMAP_TOPIC)]).await.map_err(MqttError::Connection)?;
+    packet::publish(connection, topic(STATUS_TOPIC), ONLINE.as_bytes(), true).await.map_err(MqttError::Connection)?;
+
+    // Pings keep the connection alive while nothing else is sent.
+    let ping_interval = match config.keep_alive {
+        0 => u16::MAX,
+        keep_alive => (keep_alive / 2).max(1),
+    };
+    let mut ping_sent = false;
+    // Sequence number and id of the latest command that asked for `notify_refresh`.
+    let mut notify = None;
+
+    loop {
+        let packet = match select(packets.next(connection), handler.next_refresh(ping_interval)).await {
+            Either::First(packet) => packet.map_err(MqttError::Connection)?,
+            Either::Second(Some(refresh)) => {
+                let mut payload = [0; MAX_REFRESH_LEN];
+                let len = serde_json_core::to_slice(&refresh, &mut payload).unwrap_or(0);
+                packet::publish(connection, topic(REFRESHED_TOPIC), &payload[..len], true).await.map_err(MqttError::Connection)?;
+
+                if let Some((_, id)) = notify.filter(|&(sequence, _)| is_refreshed(refresh.sequence, sequence)) {
+                    notify = None;
+                    let line = Response::refreshed(id).write_line(&mut response);
+                    packet::publish(connection, topic(RESPONSE_TOPIC), line.trim_ascii_end(), false).await.map_err(MqttError::Connection)?;
+                }
+
+                continue;
+            }
+            Either::Second(None) => {
+                if ping_sent {
+                    return Err(MqttError::Timeout);
+                }
+
+                packet::ping(connection).await.map_err(MqttError::Connection)?;
+                ping_sent = true;
+                continue;
+            }
+        };
+
+        let request = match packet {
+            None => return Ok(()),
+            Some(Err(_)) => return Err(MqttError::Protocol),
+            Some(Ok(Packet::PingResp)) => {
+                ping_sent = false;
+                continue;
+            }
+            Some(Ok(Packet::SubAck { refused: true })) => return Err(MqttError::SubscriptionRefused),
+            Some(Ok(Packet::Publish { topic: published, packet_id, payload })) => {
+                if let Some(packet_id) = packet_id {
+                    packet::puback(connection, packet_id).await.map_err(MqttError::Connection)?;
+                }
+
+                if is_topic(published, topic(BITMAP_TOPIC)) {
+                    let line = upload_bitmap(payload, handler).await.write_line(&mut response);
+                    packet::publish(connection, topic(RESPONSE_TOPIC), line.trim_ascii_end(), false).await.map_err(MqttError::Connection)?;
+                    continue;
+                }
+
+                if !is_topic(published, topic(DISPLAY_TOPIC)) {
+                    continue;
+                }
+
+                parse_request(payload)
+            }
+            Some(Ok(Packet::TooLong)) => Request { id: None, notify_refresh: false, cmd: Err(MessageError::TooLong) },
+            Some(Ok(_)) => continue,
+        };
+
+        let answer = match request.cmd {
+            Ok(cmd) => {
+                let sequence = handler.show(cmd);
+
+                if request.notify_refresh {
+                    notify = Some((sequence, request.id));
+                }
+
+                Response::accepted(request.id)
+            }
+            Err(error) => {
+                handler.rejected(error);
+                Response::rejected(request.id, error)
+            }
+        };
+
+        let line = answer.write_line(&mut response);
+        packet::publish(connection, topic(RESPONSE_TOPIC), line.trim_ascii_end(), false).await.map_err(MqttError::Connection)?;
+    }
+}
+
+fn is_topic(published: &[u8], (base, suffix): (&str, &str)) -> bool {
+    published.strip_prefix(base.as_bytes()) == Some(suffix.as_bytes())
+}
@@ -0,0 +1,283 @@
+use embedded_io_async::{Read, Write};
+
+// Control packet types in the upper four bits of the first byte.
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xc0;
+const PINGRESP: u8 = 0xd0;
+
+const RETAIN: u8 = 0x01;
+// Bits of the QoS in the first byte of a PUBLISH.
+const QOS: u8 = 0x06;
+
+/// Field of an outgoing packet.
+pub(super) enum Field<'a> {
+    Byte(u8),
+    Word(u16),
+    /// String with its length in front.
+    Str(&'a str),
+    /// Topic of two parts, written as one string.
+    Topic(&'a str, &'a str),
+    /// Bytes without length, i.e. a payload.
+    Raw(&'a [u8]),
+}
+
+impl Field<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Field::Byte(_) => 1,
+            Field::Word(_) => 2,
+            Field::Str(text) => 2 + text.len(),
+            Field::Topic(base, suffix) => 2 + base.len() + suffix.len(),
+            Field::Raw(bytes) => bytes.len(),
+        }
+    }
+
+    async fn write<C: Write>(&self, connection: &mut C) -> Result<(), C::Error> {
+        match self {
+            Field::Byte(byte) => connection.write_all(&[*byte]).await,
+            Field::Word(word) => connection.write_all(&word.to_be_bytes()).await,
+            Field::Str(text) => {
+                connection.write_all(&(text.len() as u16).to_be_bytes()).await?;
+                connection.write_all(text.as_bytes()).await
+            }
+            Field::Topic(base, suffix) => {
+                connection.write_all(&((base.len() + suffix.len()) as u16).to_be_bytes()).await?;
+                connection.write_all(base.as_bytes()).await?;
+                connection.write_all(suffix.as_bytes()).await
+            }
+            Field::Raw(bytes) => connection.write_all(bytes).await,
+        }
+    }
+}
+
+/// Writes a packet with the type and flags in the first byte.
+pub(super) async fn send<C: Write>(connection: &mut C, first: u8, fields: &[Field<'_>]) -> Result<(), C::Error> {
+    let mut remaining = fields.iter().map(Field::len).sum::<usize>();
+    let mut header = [first, 0, 0, 0, 0];
+    let mut len = 1;
+
+    // The remaining length is encoded in 7 bits per byte, least significant first.
+    loop {
+        header[len] = (remaining % 128) as u8;
+        remaining /= 128;
+
+        if remaining > 0 {
+            header[len] |= 0x80;
+        }
+
+        len += 1;
+
+        if remaining == 0 || len == header.len() {
+            break;
+        }
+    }
+
+    connection.write_all(&header[..len]).await?;
+
+    for field in fields {
+        field.write(connection).await?;
+    }
+
+    Ok(())
+}
+
+pub(super) struct Will<'a> {
+    pub topic: (&'a str, &'a str),
+    pub message: &'a str,
+}
+
+pub(super) async fn connect<C: Write>(
+    connection: &mut C,
+    client_id: &str,
+    username: Option<&str>,
+    password: Option<&str>,
+    will: Will<'_>,
+    keep_alive: u16,
+) -> Result<(), C::Error> {
+    // Clean session, and a retained will with QoS 0. MQTT 3.1.1 only allows a password together
+    // with a username.
+    let mut flags = 0x02 | 0x04 | 0x20;
+    let mut len = 7;
+
+    if username.is_some() {
+        flags |= 0x80;
+        len += 1;
+
+        if password.is_some() {
+            flags |= 0x40;
+            len += 1;
+        }
+    }
+
+    let fields = [
+        Field::Str("MQTT"),
+        Field::Byte(4),
+        Field::Byte(flags),
+        Field::Word(keep_alive),
+        Field::Str(client_id),
+        Field::Topic(will.topic.0, will.topic.1),
+        Field::Str(will.message),
+        Field::Str(username.unwrap_or_default()),
+        Field::Str(password.unwrap_or_default()),
+    ];
+
+    send(connection, CONNECT, &fields[..len]).await
+}
+
+/// Subscribes to two topics with QoS 1.
+pub(super) async fn subscribe<C: Write>(connection: &mut C, packet_id: u16, topics: [(&str, &str); 2]) -> Result<(), C::Error> {
+    let [first, second] = topics;
+    let fields = [
+        Field::Word(packet_id),
+        Field::Topic(first.0, first.1),
+        Field::Byte(1),
+        Field::Topic(second.0, second.1),
+        Field::Byte(1),
+    ];
+
+    send(connection, SUBSCRIBE, &fields).await
+}
+
+/// Publishes with QoS 0.
+pub(super) async fn publish<C: Write>(connection: &mut C, topic: (&str, &str), payload: &[u8], retain: bool) -> Result<(), C::Error> {
+    let first = if retain { PUBLISH | RETAIN } else { PUBLISH };
+    send(connection, first, &[Field::Topic(topic.0, topic.1), Field::Raw(payload)]).await
+}
+
+pub(super) async fn puback<C: Write>(connection: &mut C, packet_id: u16) -> Result<(), C::Error> {
+    send(connection, PUBACK, &[Field::Word(packet_id)]).await
+}
+
+pub(super) async fn ping<C: Write>(connection: &mut C) -> Result<(), C::Error> {
+    send(connection, PINGREQ, &[]).await
+}
+
+/// Incoming packet.
+#[derive(PartialEq, Debug)]
+pub(super) enum Packet<'a> {
+    ConnAck { return_code: u8 },
+    /// `refused` if the broker refused any of the topics.
+    SubAck { refused: bool },
+    Publish { topic: &'a [u8], packet_id: Option<u16>, payload: &'a [u8] },
+    PingResp,
+    /// The packet didn't fit into the buffer and was dropped.
+    TooLong,
+    Other,
+}
+
+/// The stream isn't valid MQTT.
+#[derive(PartialEq, Debug)]
+pub(super) struct Malformed;
+
+/// Splits a stream into packets, independent of how the stream is split into reads. Dropping
+/// `next` before it is ready loses nothing, so it can be raced against other futures.
+pub(super) struct PacketReader<'a> {
+    buf: &'a mut [u8],
+    start: usize,
+    end: usize,
+    /// Bytes of a dropped packet that are still to be skipped.
+    discarding: usize,
+}
+
+impl<'a> PacketReader<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        PacketReader { buf, start: 0, end: 0, discarding: 0 }
+    }
+
+    /// Next packet, or `None` at the end of the stream.
+    pub async fn next<R: Read>(&mut self, reader: &mut R) -> Result<Option<Result<Packet<'_>, Malformed>>, R::Error> {
+        loop {
+            if self.discarding > 0 {
+                let skipped = self.discarding.min(self.end - self.start);
+                self.start += skipped;
+                self.discarding -= skipped;
+
+                if self.discarding == 0 {
+                    return Ok(Some(Ok(Packet::TooLong)));
+                }
+            }
+
+            match fixed_header(&self.buf[self.start..self.end]) {
+                Ok(Some((header_len, remaining))) => {
+                    let packet = self.start..self.start + header_len + remaining;
+
+                    if packet.len() > self.buf.len() {
+                        self.discarding = packet.len();
+                        continue;
+                    }
+
+                    if packet.end <= self.end {
+                        self.start = packet.end;
+                        let first = self.buf[packet.start];
+                        return Ok(Some(parse(first, &self.buf[packet.start + header_len..packet.end])));
+                    }
+                }
+                Ok(None) => {}
+                Err(Malformed) => return Ok(Some(Err(Malformed))),
+            }
+
+            // Keep the incomplete packet at the start of the buffer.
+            self.buf.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+
+            let n = reader.read(&mut self.buf[self.end..]).await?;
+
+            if n == 0 {
+                return Ok(None);
+            }
+
+            self.end += n;
+        }
+    }
+}
+
+/// Length of the fixed header and the remaining length, if the fixed header is complete.
+fn fixed_header(buf: &[u8]) -> Result<Option<(usize, usize)>, Malformed> {
+    let mut remaining = 0;
+
+    for i in 1..5 {
+        let Some(&byte) = buf.get(i) else { return Ok(None) };
+        remaining |= usize::from(byte & 0x7f) << (7 * (i - 1));
+
+        if byte & 0x80 == 0 {
+            return Ok(Some((i + 1, remaining)));
+        }
+    }
+
+    Err(Malformed)
+}
+
+fn parse(first: u8, body: &[u8]) -> Result<Packet<'_>, Malformed> {
+    match first & 0xf0 {
+        CONNACK => match body {
+            [_, return_code] => Ok(Packet::ConnAck { return_code: *return_code }),
+            _ => Err(Malformed),
+        },
+        SUBACK => match body {
+            [_, _, return_codes @ ..] if !return_codes.is_empty() => {
+                Ok(Packet::SubAck { refused: return_codes.iter().any(|code| code & 0x80 != 0) })
+            }
+            _ => Err(Malformed),
+        },
+        PUBLISH => {
+            let [high, low, rest @ ..] = body else { return Err(Malformed) };
+            let topic_len = usize::from(u16::from_be_bytes([*high, *low]));
+            let (topic, rest) = rest.split_at_checked(topic_len).ok_or(Malformed)?;
+
+            if first & QOS == 0 {
+                Ok(Packet::Publish { topic, packet_id: None, payload: rest })
+            } else {
+                let [high, low, payload @ ..] = rest else { return Err(Malformed) };
+                Ok(Packet::Publish { topic, packet_id: Some(u16::from_be_bytes([*high, *low])), payload })
+            }
+        }
+        PINGRESP => Ok(Packet::PingResp),
+        _ => Ok(Packet::Other),
+    }
+}
@@ -0,0 +1,48 @@
+use serde::Serialize;
+
+use super::MessageError;
+
+/// Length of the longest response including its newline.
+pub const MAX_RESPONSE_LEN: usize = 80;
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// The command was passed on to the display.
+    Ok,
+    /// The message was rejected, the reason is in `error`.
+    Error,
+    /// The command is shown on the display.
+    Refreshed,
+}
+
+/// Response to a message, sent as one line of JSON, e.g. `{"id":7,"status":"error","error":"too_many_lines"}`.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+pub struct Response {
+    pub id: Option<u32>,
+    pub status: Status,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<MessageError>,
+}
+
+impl Response {
+    pub fn accepted(id: Option<u32>) -> Self {
+        Response { id, status: Status::Ok, error: None }
+    }
+
+    pub fn rejected(id: Option<u32>, error: MessageError) -> Self {
+        Response { id, status: Status::Error, error: Some(error) }
+    }
+
+    pub fn refreshed(id: Option<u32>) -> Self {
+        Response { id, status: Status::Refreshed, error: None }
+    }
+
+    /// Serializes the response with a trailing newline into the buffer.
+    pub fn write_line<'a>(&self, buf: &'a mut [u8; MAX_RESPONSE_LEN]) -> &'a [u8] {
+        // Every response fits, so serializing can't fail.
+        let len = serde_json_core::to_slice(self, &mut buf[..MAX_RESPONSE_LEN - 1]).unwrap_or(0);
+        buf[len] = b'\n';
+        &buf[..=len]
+    }
+}
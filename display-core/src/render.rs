@@ -0,0 +1,165 @@
+use core::fmt::Write;
+use heapless::{String, Vec};
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::Alignment;
+use embedded_graphics::primitives::Rectangle;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::layout::leaf::{Chart, Paragraph};
+use epd_display::layout::node::{Length, Node, Padding};
+use epd_display::text::font_text_style::FontTextStyle;
+use epd_display::text::fonts::FONT_10X20_PROPORTIONAL;
+use epd_display::widgets::chart::bar_chart::BarChart;
+use epd_display::widgets::chart::gauge::Gauge;
+use epd_display::widgets::chart::scale::Scale;
+use epd_display::widgets::chart::sparkline::Sparkline;
+use epd_display::widgets::qr_code::QrCode;
+use crate::data::display_cmd::{ChartContent, ChartKind, DisplayCmd, QrCodeContent, StatusContent, TextLine, TextPanelContent, MAX_BODY_LINES, STRING_CAPACITY};
+use crate::data::screen::Screen;
+
+const TEXT_MARGIN: u32 = 2;
+const BODY_PADDING: Padding = Padding::new(7, TEXT_MARGIN, 2, TEXT_MARGIN);
+
+/// Clears the target and draws the command. Commands without content of their own leave the target
+/// blank, `DisplayCmd::Bitmap` and `DisplayCmd::ShowStatus` are drawn by the display task. Commands
+/// that act on the current image leave the target as it is.
+pub fn draw<D>(target: &mut D, cmd: &DisplayCmd) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = ThreeColor>,
+{
+    if matches!(cmd, DisplayCmd::Sleep | DisplayCmd::Refresh(_) | DisplayCmd::SetOrientation(_)) {
+        return Ok(());
+    }
+
+    target.clear(ThreeColor::White)?;
+
+    match cmd {
+        DisplayCmd::TextPanel(content) => draw_text_panel(target, content),
+        DisplayCmd::Chart(content) => draw_chart(target, content),
+        DisplayCmd::Screen(screen) => draw_screen(target, screen),
+        DisplayCmd::None | DisplayCmd::Bitmap | DisplayCmd::Clear | DisplayCmd::ShowStatus => Ok(()),
+        DisplayCmd::Sleep | DisplayCmd::Refresh(_) | DisplayCmd::SetOrientation(_) => Ok(()),
+    }
+}
+
+/// Draws the status as text panel.
+pub fn draw_status<D>(target: &mut D, status: &StatusContent) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = ThreeColor>,
+{
+    let (days, hours, minutes) = (status.uptime / 86400, status.uptime / 3600 % 24, status.uptime / 60 % 60);
+    let mut lines: [String<STRING_CAPACITY>; 4] = Default::default();
+    let _ = write!(lines[0], "Uptime: {}d {:02}:{:02}", days, hours, minutes);
+    let _ = write!(lines[1], "Command: {}", status.sequence);
+    let _ = write!(lines[2], "Refreshes since cleaning: {}", status.refreshes);
+    let _ = write!(lines[3], "Orientation: {:?}", status.orientation);
+
+    let mut content = TextPanelContent::new(TextLine::new("Status", ThreeColor::Black));
+
+    for line in &lines {
+        let _ = content.add_body_line(TextLine::new(line, ThreeColor::Black));
+    }
+
+    target.clear(ThreeColor::White)?;
+    draw_text_panel(target, &content)
+}
+
+pub fn draw_text_panel<D>(target: &mut D, content: &TextPanelContent) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = ThreeColor>,
+{
+    let title = [paragraph(content.title())];
+    let body_lines = || (0 .. content.body_len()).filter_map(|i| content.body_line(i));
+    let body: Vec<Paragraph, MAX_BODY_LINES> = body_lines().map(paragraph).collect();
+    // Every line is a text node of its own, so that lines can be aligned differently.
+    let lines: Vec<Node, MAX_BODY_LINES> = body.chunks(1)
+        .zip(body_lines())
+        .map(|(paragraph, line)| Node::text(paragraph, line.alignment()).with_spacing(2))
+        .collect();
+    let qr_code = content.qr_code().and_then(qr_code);
+
+    let mut body_nodes: Vec<Node, 2> = Vec::new();
+    let _ = body_nodes.push(Node::column(&lines).with_spacing(2));
+
+    if let Some(qr_code) = &qr_code {
+        let _ = body_nodes.push(Node::qr_code(qr_code));
+    }
+
+    // The QR code goes to the right in landscape and to the bottom in portrait orientation.
+    let size = target.bounding_box().size;
+    let body = if size.width >= size.height { Node::row(&body_nodes) } else { Node::column(&body_nodes) };
+    let [title, separator] = header(&title, content.title().alignment());
+    let screen = [title, separator, body.with_spacing(4).with_padding(BODY_PADDING)];
+
+    Node::column(&screen).draw(target.bounding_box(), target)
+}
+
+pub fn draw_chart<D>(target: &mut D, content: &ChartContent) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = ThreeColor>,
+{
+    let values = content.values();
+    let Some(&last) = values.last() else { return Ok(()) };
+
+    let default_scale = match content.kind() {
+        ChartKind::Sparkline => Scale::fit(values),
+        ChartKind::Bars => Scale::fit(values).including(0.0),
+        ChartKind::Gauge => Scale::new(0.0, 100.0),
+    };
+    let scale = Scale::new(content.min().unwrap_or(default_scale.min()), content.max().unwrap_or(default_scale.max()));
+    // Without a threshold no value is highlighted.
+    let threshold = content.threshold().unwrap_or(f32::INFINITY);
+
+    let chart: Chart = match content.kind() {
+        ChartKind::Sparkline => Sparkline::new(values, Rectangle::zero()).with_scale(scale).with_threshold(threshold).with_stroke_width(2).into(),
+        ChartKind::Bars => BarChart::new(values, Rectangle::zero()).with_scale(scale).with_threshold(threshold).into(),
+        ChartKind::Gauge => Gauge::new(last, Point::zero(), 0).with_scale(scale).with_threshold(threshold).into(),
+    };
+
+    // The latest value is shown below the chart.
+    let mut label: String<24> = String::new();
+    let _ = write!(label, "{:.1}{}", last, content.unit());
+    let label_color = if last > threshold { ThreeColor::Chromatic } else { ThreeColor::Black };
+    let label = [(label.as_str(), FontTextStyle::new(&FONT_10X20_PROPORTIONAL, label_color))];
+    let label_alignment = if content.kind() == ChartKind::Gauge { Alignment::Center } else { Alignment::Right };
+
+    let title = [paragraph(content.title())];
+    let [title, separator] = header(&title, content.title().alignment());
+    let body = [Node::chart(chart).with_length(Length::Flex(1)), Node::text(&label, label_alignment)];
+    let screen = [title, separator, Node::column(&body).with_spacing(4).with_padding(BODY_PADDING)];
+
+    Node::column(&screen).draw(target.bounding_box(), target)
+}
+
+/// Draws the screen, or a panel with the reason if the screen doesn't fit the target.
+pub fn draw_screen<D>(target: &mut D, screen: &Screen) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = ThreeColor>,
+{
+    if let Err(error) = screen.validate(target.bounding_box().size) {
+        let mut reason: String<STRING_CAPACITY> = String::new();
+        let _ = write!(reason, "{}", error);
+
+        let mut content = TextPanelContent::new(TextLine::new("Invalid screen", ThreeColor::Chromatic));
+        let _ = content.add_body_line(TextLine::new(&reason, ThreeColor::Black));
+
+        return draw_text_panel(target, &content);
+    }
+
+    screen.draw(target)
+}
+
+// Title with the separator line below.
+fn header<'a>(title: &'a [Paragraph<'a>], alignment: Alignment) -> [Node<'a>; 2] {
+    [
+        Node::text(title, alignment).with_padding(Padding::new(5, TEXT_MARGIN, 5, TEXT_MARGIN)),
+        Node::line(ThreeColor::Chromatic, 3),
+    ]
+}
+
+fn paragraph(line: &TextLine) -> Paragraph<'_> {
+    (line.text(), FontTextStyle::new(line.font_size().font(true), line.color()))
+}
+
+fn qr_code(content: &QrCodeContent) -> Option<QrCode> {
+    QrCode::new(content.text(), Point::zero()).ok().map(|qr_code| qr_code.with_color(content.color()))
+}
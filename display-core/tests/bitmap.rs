@@ -0,0 +1,285 @@
+use display_core::protocol::MessageError;
+use display_core::protocol::bitmap::heatshrink::{Heatshrink, LOOKAHEAD_BITS, WINDOW_BITS};
+use display_core::protocol::bitmap::packbits::PackBits;
+use display_core::protocol::bitmap::{is_bitmap, parse_header, BitmapDecoder, BitmapHeader, Encoding, PlaneArea, BITMAP_HEADER_LEN, HEATSHRINK_FLAG, PACKBITS_FLAG, REFRESH_FLAG};
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::simulator::SimulatedDisplay;
+
+fn upload(flags: u8, area: [u16; 4], planes: &[u8]) -> Vec<u8> {
+    let mut bytes = b"EPDP".to_vec();
+    bytes.push(flags);
+    area.iter().for_each(|value| bytes.extend_from_slice(&value.to_be_bytes()));
+    bytes.extend_from_slice(planes);
+    bytes
+}
+
+/// Decodes the upload in chunks of the size into the display, `None` if it is rejected.
+fn decode(bytes: &[u8], chunk_size: usize, display: &mut SimulatedDisplay) -> Option<BitmapHeader> {
+    let header = parse_header(bytes, bytes.len()).ok()?;
+    let mut decoder = BitmapDecoder::new(header);
+
+    for chunk in bytes[BITMAP_HEADER_LEN..].chunks(chunk_size) {
+        decoder.decode(chunk, &mut *display);
+    }
+
+    decoder.is_complete().then_some(header)
+}
+
+fn planes(display: &mut SimulatedDisplay) -> Vec<u8> {
+    let (bw, chromatic) = display.planes_mut();
+    [bw.buffer.as_slice(), &chromatic.buffer].concat()
+}
+
+fn decoded<D: FnMut(&[u8], &mut dyn FnMut(u8))>(input: &[u8], chunk_size: usize, mut decode: D) -> Vec<u8> {
+    let mut output = Vec::new();
+    input.chunks(chunk_size).for_each(|chunk| decode(chunk, &mut |byte| output.push(byte)));
+    output
+}
+
+#[test]
+fn parses_header() {
+    let bytes = upload(1, [16, 290, 16, 3], &[0; 12]);
+    let header = parse_header(&bytes, bytes.len()).unwrap();
+
+    assert!(is_bitmap(&bytes));
+    assert_eq!(header, BitmapHeader { area: PlaneArea { x: 16, y: 290, width: 16, height: 3 }, refresh: true, encoding: Encoding::Raw });
+    assert_eq!(header.plane_len(), 6);
+
+    // Only the header has to be received yet.
+    assert_eq!(parse_header(&bytes[..BITMAP_HEADER_LEN], bytes.len()), Ok(header));
+}
+
+#[test]
+fn rejects_invalid_headers() {
+    let cases = [
+        upload(0, [0, 0, 8, 1], &[0; 1]),
+        upload(0, [0, 0, 8, 1], &[0; 3]),
+        upload(0, [4, 0, 8, 1], &[0; 2]),
+        upload(0, [0, 0, 12, 1], &[0; 4]),
+        upload(0, [0, 0, 0, 0], &[]),
+        upload(0, [152, 0, 8, 1], &[0; 2]),
+        upload(0, [0, 296, 8, 1], &[0; 2]),
+        upload(8, [0, 0, 8, 1], &[0; 2]),
+        upload(PACKBITS_FLAG | HEATSHRINK_FLAG, [0, 0, 8, 1], &[0; 2]),
+        upload(PACKBITS_FLAG, [0, 0, 8, 1], &[]),
+        b"EPDQ\0\0\0\0\0\0\x08\0\x01\0\0".to_vec(),
+        b"EPDP".to_vec(),
+    ];
+
+    for bytes in cases {
+        assert_eq!(parse_header(&bytes, bytes.len()), Err(MessageError::InvalidBitmap), "{:?}", bytes);
+    }
+}
+
+#[test]
+fn rejects_data_that_does_not_decode_to_the_planes() {
+    let cases = [
+        upload(PACKBITS_FLAG, [0, 0, 8, 1], &[0x02, 0, 0, 0]),
+        upload(PACKBITS_FLAG, [0, 0, 8, 1], &[0xff, 0, 0x00]),
+        upload(PACKBITS_FLAG, [0, 0, 8, 1], &[0xfe]),
+        upload(HEATSHRINK_FLAG, [0, 0, 8, 1], &[0x80, 0x40]),
+    ];
+
+    for bytes in cases {
+        let mut display = SimulatedDisplay::simulated();
+        let header = parse_header(&bytes, bytes.len()).unwrap();
+        let mut decoder = BitmapDecoder::new(header);
+        decoder.decode(&bytes[BITMAP_HEADER_LEN..], &mut display);
+
+        assert!(!decoder.is_complete(), "{:?}", bytes);
+    }
+
+    // Decoding stops being useful once the data expands beyond the planes.
+    let bytes = upload(PACKBITS_FLAG, [0, 0, 8, 1], &[0x81, 0x00]);
+    let mut decoder = BitmapDecoder::new(parse_header(&bytes, bytes.len()).unwrap());
+    decoder.decode(&bytes[BITMAP_HEADER_LEN..], &mut SimulatedDisplay::simulated());
+    assert!(decoder.is_too_long());
+}
+
+#[test]
+fn planes_are_decoded_into_the_display() {
+    let mut display = SimulatedDisplay::simulated();
+    display.set_orientation(DisplayOrientation::Portrait);
+
+    // A black byte, then a red byte in the second row.
+    assert!(decode(&upload(0, [8, 0, 8, 1], &[0x00, 0x00]), 1, &mut display).is_some());
+    assert!(decode(&upload(0, [144, 1, 8, 1], &[0x00, 0xff]), 1, &mut display).is_some());
+
+    let color = |x, y| display.pixel(Point::new(x, y)).unwrap();
+    assert_eq!((color(7, 0), color(8, 0), color(15, 0), color(16, 0)), (ThreeColor::White, ThreeColor::Black, ThreeColor::Black, ThreeColor::White));
+    assert_eq!((color(143, 1), color(144, 1), color(151, 1)), (ThreeColor::White, ThreeColor::Chromatic, ThreeColor::Chromatic));
+    assert_eq!(color(151, 295), ThreeColor::White);
+}
+
+#[test]
+fn decodes_packbits() {
+    let input = [0xfe, 0xaa, 0x02, 0x80, 0x00, 0x2a, 0xfd, 0xaa, 0x03, 0x80, 0x00, 0x2a, 0x22, 0x80, 0xf7, 0xaa];
+    let expected = [
+        [0xaa, 0xaa, 0xaa, 0x80, 0x00, 0x2a, 0xaa, 0xaa, 0xaa, 0xaa, 0x80, 0x00, 0x2a, 0x22].as_slice(),
+        &[0xaa; 10],
+    ].concat();
+
+    // Runs may be split between the chunks.
+    for chunk_size in [1, 2, 3, 16] {
+        let mut decoder = PackBits::new();
+        assert_eq!(decoded(&input, chunk_size, |chunk, output| decoder.decode(chunk, output)), expected, "chunk size {}", chunk_size);
+        assert!(decoder.is_complete());
+    }
+
+    // A literal run or a repeat header without its byte.
+    for input in [[0x02, 0x01, 0x02].as_slice(), &[0x00, 0x01, 0xfe]] {
+        let mut decoder = PackBits::new();
+        decoder.decode(input, |_| {});
+        assert!(!decoder.is_complete(), "{:?}", input);
+    }
+}
+
+#[test]
+fn decodes_heatshrink() {
+    // Literal `a`, then 5 bytes from a distance of 1, then literal `b`, padded with zeros.
+    let mut bits = Bits::default();
+    bits.push(1, 1);
+    bits.push(b'a'.into(), 8);
+    bits.push(0, 1);
+    bits.push(0, WINDOW_BITS);
+    bits.push(4, LOOKAHEAD_BITS);
+    bits.push(1, 1);
+    bits.push(b'b'.into(), 8);
+    let input = bits.finish();
+
+    for chunk_size in [1, 2, 4] {
+        let mut decoder = Heatshrink::new();
+        assert_eq!(decoded(&input, chunk_size, |chunk, output| decoder.decode(chunk, output)), b"aaaaaab", "chunk size {}", chunk_size);
+        assert!(decoder.is_complete());
+    }
+
+    // Padding has to be zero.
+    let mut padded = input.clone();
+    *padded.last_mut().unwrap() |= 0x01;
+    let mut decoder = Heatshrink::new();
+    decoder.decode(&padded, |_| {});
+    assert!(!decoder.is_complete());
+
+    // A literal that continues after the input.
+    let mut decoder = Heatshrink::new();
+    decoder.decode(&input[..1], |_| {});
+    assert!(!decoder.is_complete());
+}
+
+#[test]
+fn compressed_frames_match_raw_frame() {
+    // Black stripes and a red block on white, with some noise.
+    let plane_len = 19 * 296;
+    let mut planes_data = vec![0xff; plane_len];
+    planes_data.extend(vec![0x00; plane_len]);
+
+    for y in 0..296 {
+        for x in 0..19 {
+            if y % 20 < 3 {
+                planes_data[y * 19 + x] = 0x00;
+            }
+            if (100..140).contains(&y) && (4..12).contains(&x) {
+                planes_data[plane_len + y * 19 + x] = 0xff;
+            }
+            if (200..210).contains(&y) {
+                planes_data[y * 19 + x] = (y * 31 + x * 17) as u8;
+            }
+        }
+    }
+
+    let area = [0, 0, 152, 296];
+    let mut expected = SimulatedDisplay::simulated();
+    assert!(decode(&upload(REFRESH_FLAG, area, &planes_data), 4096, &mut expected).is_some());
+    assert_eq!(planes(&mut expected), planes_data);
+
+    for (flags, compressed) in [(PACKBITS_FLAG, packbits(&planes_data)), (HEATSHRINK_FLAG, heatshrink(&planes_data))] {
+        // A whole frame fits into the 4 KB receive buffer of MQTT.
+        assert!(compressed.len() < 4096 - 13, "{} bytes", compressed.len());
+
+        for chunk_size in [1, 7, 1460] {
+            let mut actual = SimulatedDisplay::simulated();
+            assert!(decode(&upload(flags | REFRESH_FLAG, area, &compressed), chunk_size, &mut actual).is_some());
+            assert!(planes(&mut actual) == planes_data, "flags {}, chunk size {}", flags, chunk_size);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    fn push(&mut self, value: usize, count: u32) {
+        for i in (0..count).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            *self.bytes.last_mut().unwrap() |= (((value >> i) & 1) as u8) << (7 - self.len % 8);
+            self.len += 1;
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+fn packbits(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut literal: Vec<u8> = Vec::new();
+    let mut i = 0;
+
+    while i < data.len() {
+        let run = data[i..].iter().take(128).take_while(|&&byte| byte == data[i]).count();
+
+        if (run >= 3 || literal.len() == 128) && !literal.is_empty() {
+            output.push(literal.len() as u8 - 1);
+            output.append(&mut literal);
+        }
+
+        if run >= 3 {
+            output.extend_from_slice(&[(1 - run as i16) as u8, data[i]]);
+            i += run;
+        } else {
+            literal.push(data[i]);
+            i += 1;
+        }
+    }
+
+    if !literal.is_empty() {
+        output.push(literal.len() as u8 - 1);
+        output.append(&mut literal);
+    }
+
+    output
+}
+
+fn heatshrink(data: &[u8]) -> Vec<u8> {
+    let mut bits = Bits::default();
+    let mut i = 0;
+
+    while i < data.len() {
+        let (distance, len) = (1..=i.min(1 << WINDOW_BITS))
+            .map(|distance| (distance, (0..1 << LOOKAHEAD_BITS).take_while(|&k| i + k < data.len() && data[i + k] == data[i + k - distance]).count()))
+            .max_by_key(|&(distance, len)| (len, usize::MAX - distance))
+            .unwrap_or((0, 0));
+
+        if len >= 2 {
+            bits.push(0, 1);
+            bits.push(distance - 1, WINDOW_BITS);
+            bits.push(len - 1, LOOKAHEAD_BITS);
+            i += len;
+        } else {
+            bits.push(1, 1);
+            bits.push(data[i].into(), 8);
+            i += 1;
+        }
+    }
+
+    bits.finish()
+}
@@ -0,0 +1,236 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use display_core::data::display_cmd::{ChartContent, ChartKind, DisplayCmd, QrCodeContent, StatusContent, TextLine, TextPanelContent};
+use display_core::data::screen::{FontSize, Screen};
+use display_core::render::{draw, draw_status};
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::Alignment;
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::simulator::{rgb, SimulatedDisplay};
+
+// Golden images are rewritten instead of compared when this variable is set.
+const UPDATE_VARIABLE: &str = "UPDATE_GOLDEN";
+
+fn render(orientation: DisplayOrientation, cmd: &DisplayCmd) -> SimulatedDisplay {
+    let mut display = SimulatedDisplay::simulated();
+    display.set_orientation(orientation);
+    draw(&mut display, cmd).unwrap();
+    display
+}
+
+fn to_rgb(display: &SimulatedDisplay) -> (Size, Vec<u8>) {
+    let area = display.bounding_box();
+    let pixels = area.points().flat_map(|point| rgb(display.pixel(point).unwrap())).collect();
+
+    (area.size, pixels)
+}
+
+fn write_png(path: &Path, size: Size, pixels: &[u8]) {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path).unwrap()), size.width, size.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header().unwrap().write_image_data(pixels).unwrap();
+}
+
+fn read_png(path: &Path) -> Option<(Size, Vec<u8>)> {
+    let mut reader = png::Decoder::new(File::open(path).ok()?).read_info().ok()?;
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).ok()?;
+
+    if info.color_type != png::ColorType::Rgb || info.bit_depth != png::BitDepth::Eight {
+        return None;
+    }
+
+    pixels.truncate(info.buffer_size());
+    Some((Size::new(info.width, info.height), pixels))
+}
+
+fn assert_golden(name: &str, display: &SimulatedDisplay) {
+    let golden = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.png", name));
+    let (size, pixels) = to_rgb(display);
+
+    if std::env::var_os(UPDATE_VARIABLE).is_some() {
+        write_png(&golden, size, &pixels);
+        return;
+    }
+
+    let expected = read_png(&golden);
+
+    if expected.as_ref() != Some(&(size, pixels.clone())) {
+        let actual = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.png", name));
+        write_png(&actual, size, &pixels);
+
+        let differences = expected
+            .filter(|(expected_size, _)| *expected_size == size)
+            .map(|(_, expected)| expected.chunks(3).zip(pixels.chunks(3)).filter(|(a, b)| a != b).count());
+
+        panic!(
+            "{} differs from {} ({} pixels), the rendered image is {}. Run with {}=1 to accept it.",
+            name,
+            golden.display(),
+            differences.map_or("size or format".to_string(), |count| count.to_string()),
+            actual.display(),
+            UPDATE_VARIABLE,
+        );
+    }
+}
+
+fn text_panel(title: TextLine, body: &[(&str, ThreeColor)]) -> TextPanelContent {
+    let mut content = TextPanelContent::new(title);
+
+    for &(text, color) in body {
+        content.add_body_line(TextLine::new(text, color)).unwrap();
+    }
+
+    content
+}
+
+fn chart(kind: ChartKind, values: &[f32]) -> ChartContent {
+    let mut content = ChartContent::new(TextLine::new("Temperature", ThreeColor::Black), kind);
+
+    for &value in values {
+        content.add_value(value).unwrap();
+    }
+
+    content.set_unit(" C");
+    content
+}
+
+fn screen(json: &str) -> Screen {
+    serde_json_core::from_str::<Screen>(json).unwrap().0
+}
+
+const BODY: [(&str, ThreeColor); 3] = [
+    ("Meeting room is booked until 14:30, the projector is out of order.", ThreeColor::Black),
+    ("Please use room 4 instead.", ThreeColor::Chromatic),
+    ("Facility management", ThreeColor::Black),
+];
+
+const VALUES: [f32; 12] = [18.5, 19.0, 19.8, 21.2, 22.4, 23.9, 24.6, 23.1, 21.7, 20.3, 19.6, 19.2];
+
+#[test]
+fn none() {
+    assert_golden("none", &render(DisplayOrientation::Landscape, &DisplayCmd::None));
+}
+
+#[test]
+fn text_panel_landscape() {
+    let content = text_panel(TextLine::new("Room 3", ThreeColor::Black), &BODY);
+    assert_golden("text_panel_landscape", &render(DisplayOrientation::Landscape, &DisplayCmd::TextPanel(content)));
+}
+
+#[test]
+fn text_panel_portrait() {
+    let content = text_panel(TextLine::new("Room 3", ThreeColor::Black), &BODY);
+    assert_golden("text_panel_portrait", &render(DisplayOrientation::Portrait, &DisplayCmd::TextPanel(content)));
+}
+
+#[test]
+fn text_panel_long_title() {
+    let title = TextLine::new("A title that is far too long for a single line on this small display", ThreeColor::Black);
+    let content = text_panel(title, &BODY[1..]);
+    assert_golden("text_panel_long_title", &render(DisplayOrientation::Landscape, &DisplayCmd::TextPanel(content)));
+}
+
+#[test]
+fn text_panel_empty_body() {
+    let content = text_panel(TextLine::new("Nothing to report", ThreeColor::Black), &[]);
+    assert_golden("text_panel_empty_body", &render(DisplayOrientation::Landscape, &DisplayCmd::TextPanel(content)));
+}
+
+#[test]
+fn text_panel_chromatic_lines() {
+    let body = [("Door open", ThreeColor::Chromatic), ("Alarm armed", ThreeColor::Chromatic)];
+    let content = text_panel(TextLine::new("Warning", ThreeColor::Chromatic), &body);
+    assert_golden("text_panel_chromatic_lines", &render(DisplayOrientation::Landscape, &DisplayCmd::TextPanel(content)));
+}
+
+#[test]
+fn text_panel_styled_lines() {
+    let mut content = TextPanelContent::new(TextLine::new("Alarms", ThreeColor::Black).with_alignment(Alignment::Center));
+    let body = [
+        TextLine::new("Freezer 2: -4 C", ThreeColor::Chromatic).with_alignment(Alignment::Center),
+        TextLine::new("Checked at 14:30", ThreeColor::Black).with_font_size(FontSize::Small).with_alignment(Alignment::Right),
+        TextLine::new("All other sensors ok", ThreeColor::Black).with_font_size(FontSize::Medium),
+    ];
+
+    for line in body {
+        content.add_body_line(line).unwrap();
+    }
+
+    assert_golden("text_panel_styled_lines", &render(DisplayOrientation::Landscape, &DisplayCmd::TextPanel(content)));
+}
+
+#[test]
+fn text_panel_qr_code_landscape() {
+    let mut content = text_panel(TextLine::new("Guest WiFi", ThreeColor::Black), &BODY[2..]);
+    content.set_qr_code(QrCodeContent::new("WIFI:T:WPA;S:guest;P:welcome;;", ThreeColor::Black));
+    assert_golden("text_panel_qr_code_landscape", &render(DisplayOrientation::Landscape, &DisplayCmd::TextPanel(content)));
+}
+
+#[test]
+fn text_panel_qr_code_portrait() {
+    let mut content = text_panel(TextLine::new("Guest WiFi", ThreeColor::Black), &BODY[2..]);
+    content.set_qr_code(QrCodeContent::new("WIFI:T:WPA;S:guest;P:welcome;;", ThreeColor::Chromatic));
+    assert_golden("text_panel_qr_code_portrait", &render(DisplayOrientation::Portrait, &DisplayCmd::TextPanel(content)));
+}
+
+#[test]
+fn chart_sparkline() {
+    let mut content = chart(ChartKind::Sparkline, &VALUES);
+    content.set_threshold(23.0);
+    assert_golden("chart_sparkline", &render(DisplayOrientation::Landscape, &DisplayCmd::Chart(content)));
+}
+
+#[test]
+fn chart_bars() {
+    let mut content = chart(ChartKind::Bars, &VALUES);
+    content.set_threshold(23.0);
+    assert_golden("chart_bars", &render(DisplayOrientation::Landscape, &DisplayCmd::Chart(content)));
+}
+
+#[test]
+fn chart_gauge() {
+    let mut content = chart(ChartKind::Gauge, &[64.0]);
+    content.set_threshold(80.0);
+    assert_golden("chart_gauge", &render(DisplayOrientation::Portrait, &DisplayCmd::Chart(content)));
+}
+
+#[test]
+fn chart_without_values() {
+    let content = chart(ChartKind::Sparkline, &[]);
+    assert_golden("chart_without_values", &render(DisplayOrientation::Landscape, &DisplayCmd::Chart(content)));
+}
+
+#[test]
+fn screen_layout() {
+    let screen = screen(r#"{"widgets": [
+        {"type": "column", "children": [1, 2, 3]},
+        {"type": "text", "text": "Shelf 12", "font": "10x20", "padding": 4},
+        {"type": "line", "color": "red", "stroke": 3},
+        {"type": "row", "children": [4, 5], "padding": 4, "spacing": 6},
+        {"type": "sparkline", "values": [1, 4, 2, 6, 3, 7, 5], "threshold": 5, "flex": 2},
+        {"type": "gauge", "values": [42], "flex": 1}
+    ]}"#);
+    assert_golden("screen_layout", &render(DisplayOrientation::Landscape, &DisplayCmd::Screen(screen)));
+}
+
+#[test]
+fn screen_invalid() {
+    let screen = screen(r#"{"widgets": [{"type": "column", "children": [0]}]}"#);
+    assert_golden("screen_invalid", &render(DisplayOrientation::Landscape, &DisplayCmd::Screen(screen)));
+}
+
+#[test]
+fn status() {
+    let status = StatusContent { uptime: 3 * 86400 + 4 * 3600 + 5 * 60 + 6, sequence: 42, refreshes: 7, orientation: DisplayOrientation::Landscape };
+    let mut display = SimulatedDisplay::simulated();
+    display.set_orientation(status.orientation);
+    draw_status(&mut display, &status).unwrap();
+
+    assert_golden("status", &display);
+}
@@ -0,0 +1,322 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+use display_core::data::display_cmd::DisplayCmd;
+use display_core::protocol::MessageError;
+use display_core::protocol::bitmap::{PlaneBuffers, REFRESH_FLAG};
+use display_core::protocol::connection::Handler;
+use display_core::protocol::http::{serve_http, DisplayStatus, HttpHandler, INDEX_PAGE};
+use embassy_futures::block_on;
+use embedded_io_async::{ErrorType, Read, Write};
+use epd_display::epd::epd_2in66b::PlaneBuffer;
+
+/// Connection that returns at most one chunk per read and records everything written.
+struct Connection {
+    reads: VecDeque<Vec<u8>>,
+    written: Vec<u8>,
+}
+
+impl ErrorType for Connection {
+    type Error = Infallible;
+}
+
+impl Read for Connection {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        let Some(mut chunk) = self.reads.pop_front() else { return Ok(0) };
+        let n = chunk.len().min(buf.len());
+        buf[..n].copy_from_slice(&chunk[..n]);
+
+        if n < chunk.len() {
+            self.reads.push_front(chunk.split_off(n));
+        }
+
+        Ok(n)
+    }
+}
+
+impl Write for Connection {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+#[derive(Default)]
+struct Recorder {
+    shown: Vec<DisplayCmd>,
+    refreshed: Vec<u32>,
+    rejected: Vec<MessageError>,
+    planes: Planes,
+}
+
+/// Buffers that the bitmap uploads are decoded into, with the number of uploads.
+#[derive(Default)]
+struct Planes {
+    bw: PlaneBuffer,
+    chromatic: PlaneBuffer,
+    uploads: usize,
+}
+
+impl PlaneBuffers for Planes {
+    fn planes_mut(&mut self) -> (&mut PlaneBuffer, &mut PlaneBuffer) {
+        (&mut self.bw, &mut self.chromatic)
+    }
+}
+
+impl Handler for Recorder {
+    fn show(&mut self, cmd: DisplayCmd) -> u32 {
+        self.shown.push(cmd);
+        self.shown.len() as u32
+    }
+
+    async fn refreshed(&mut self, sequence: u32) {
+        self.refreshed.push(sequence);
+    }
+
+    type Planes<'a> = &'a mut Planes;
+
+    async fn planes(&mut self) -> &mut Planes {
+        self.planes.uploads += 1;
+        &mut self.planes
+    }
+
+    fn rejected(&mut self, error: MessageError) {
+        self.rejected.push(error);
+    }
+}
+
+impl HttpHandler for Recorder {
+    fn status(&mut self) -> DisplayStatus {
+        DisplayStatus { sequence: self.shown.len() as u32, refreshed: 1, sleeping: false, uptime: 42 }
+    }
+}
+
+fn request(recorder: &mut Recorder, reads: &[&str], buf_size: usize) -> String {
+    let reads: Vec<_> = reads.iter().map(|read| read.as_bytes()).collect();
+    request_bytes(recorder, &reads, buf_size)
+}
+
+fn request_bytes(recorder: &mut Recorder, reads: &[&[u8]], buf_size: usize) -> String {
+    let mut connection = Connection { reads: reads.iter().map(|read| read.to_vec()).collect(), written: Vec::new() };
+    let mut buf = vec![0; buf_size];
+
+    block_on(serve_http(&mut connection, &mut buf, recorder)).unwrap();
+
+    String::from_utf8(connection.written).unwrap()
+}
+
+fn post(path: &str, body: &str) -> String {
+    format!("POST {} HTTP/1.1\r\nHost: display\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body)
+}
+
+fn status_line(response: &str) -> &str {
+    response.lines().next().unwrap()
+}
+
+fn body(response: &str) -> &str {
+    response.split_once("\r\n\r\n").unwrap().1
+}
+
+#[test]
+fn posts_display_message() {
+    let mut recorder = Recorder::default();
+    let response = request(&mut recorder, &[&post("/display", r#"{"id": 3, "title": "Hello", "body": []}"#)], 1024);
+
+    assert_eq!(response, concat!(
+        "HTTP/1.1 200 OK\r\n",
+        "Content-Type: application/json\r\n",
+        "Content-Length: 23\r\n",
+        "Connection: close\r\n",
+        "\r\n",
+        "{\"id\":3,\"status\":\"ok\"}\n",
+    ));
+    assert!(matches!(recorder.shown[..], [DisplayCmd::TextPanel(_)]));
+}
+
+#[test]
+fn accepts_repeated_content_length() {
+    let body_text = r#"{"title": "Hello", "body": []}"#;
+    let request_text = format!("POST /display HTTP/1.1\r\nContent-Length: {0}\r\ncontent-length: {0}\r\n\r\n{1}", body_text.len(), body_text);
+    let mut recorder = Recorder::default();
+    let response = request(&mut recorder, &[&request_text], 1024);
+
+    assert_eq!(status_line(&response), "HTTP/1.1 200 OK");
+    assert!(matches!(recorder.shown[..], [DisplayCmd::TextPanel(_)]));
+}
+
+#[test]
+fn reads_body_in_pieces() {
+    let request_text = post("/display", r#"{"id": 4, "notify_refresh": true, "title": "Hello", "body": ["split", "body"]}"#);
+
+    for chunk_size in [1, 5, 64] {
+        let mut recorder = Recorder::default();
+        let reads: Vec<_> = request_text.as_bytes().chunks(chunk_size).map(|chunk| str::from_utf8(chunk).unwrap()).collect();
+        let response = request(&mut recorder, &reads, 1024);
+
+        assert_eq!(status_line(&response), "HTTP/1.1 200 OK", "chunk size {}", chunk_size);
+        assert_eq!(body(&response), "{\"id\":4,\"status\":\"refreshed\"}\n", "chunk size {}", chunk_size);
+        assert_eq!(recorder.refreshed, [1]);
+    }
+}
+
+#[test]
+fn rejects_invalid_message() {
+    let mut recorder = Recorder::default();
+    let lines = ["\"line\""; 11].join(", ");
+    let response = request(&mut recorder, &[&post("/display", &format!(r#"{{"id": 5, "title": "Hello", "body": [{}]}}"#, lines))], 1024);
+
+    assert_eq!(status_line(&response), "HTTP/1.1 400 Bad Request");
+    assert_eq!(body(&response), "{\"id\":5,\"status\":\"error\",\"error\":\"too_many_lines\"}\n");
+    assert_eq!(recorder.rejected, [MessageError::TooManyLines]);
+    assert!(recorder.shown.is_empty());
+}
+
+#[test]
+fn rejects_body_larger_than_buffer() {
+    let mut recorder = Recorder::default();
+    let response = request(&mut recorder, &[&post("/display", &format!(r#"{{"title": "{}", "body": []}}"#, "x".repeat(200)))], 128);
+
+    assert_eq!(status_line(&response), "HTTP/1.1 413 Payload Too Large");
+    assert!(recorder.shown.is_empty());
+}
+
+fn post_bitmap(upload: &[u8]) -> Vec<u8> {
+    let mut message = format!("POST /bitmap HTTP/1.1\r\nContent-Length: {}\r\n\r\n", upload.len()).into_bytes();
+    message.extend_from_slice(upload);
+    message
+}
+
+#[test]
+fn uploads_bitmaps() {
+    let mut recorder = Recorder::default();
+    let mut upload = b"EPDP\x00\x00\x08\x00\x02\x00\x10\x00\x03".to_vec();
+    upload.extend_from_slice(&[0xff; 12]);
+
+    let response = request_bytes(&mut recorder, &[&post_bitmap(&upload)], 1024);
+
+    assert_eq!(status_line(&response), "HTTP/1.1 200 OK");
+    assert_eq!(body(&response), "{\"id\":null,\"status\":\"ok\"}\n");
+    assert_eq!(recorder.planes.uploads, 1);
+    assert_eq!(recorder.planes.bw.buffer[2 * 19..3 * 19 + 3], [&[0x00, 0xff, 0xff], [0x00; 16].as_slice(), &[0x00, 0xff, 0xff]].concat());
+    assert!(recorder.shown.is_empty());
+
+    // The last upload shows the planes.
+    upload[4] = REFRESH_FLAG;
+    request_bytes(&mut recorder, &[&post_bitmap(&upload)], 1024);
+
+    assert_eq!(recorder.planes.uploads, 2);
+    assert!(matches!(recorder.shown[..], [DisplayCmd::Bitmap]));
+
+    let response = request(&mut recorder, &[&post("/bitmap", "EPDP")], 1024);
+    assert_eq!(status_line(&response), "HTTP/1.1 400 Bad Request");
+    assert_eq!(recorder.rejected, [MessageError::InvalidBitmap]);
+    assert_eq!(recorder.planes.uploads, 2);
+}
+
+#[test]
+fn decodes_bitmaps_larger_than_the_buffer_while_receiving() {
+    // A whole raw frame, with the planes in pieces of the body.
+    let mut upload = b"EPDP\x01\x00\x00\x00\x00\x00\x98\x01\x28".to_vec();
+    upload.extend((0..2 * 19 * 296).map(|i| (i % 251) as u8));
+    let message = post_bitmap(&upload);
+    let reads: Vec<_> = message.chunks(100).collect();
+    let mut recorder = Recorder::default();
+
+    let response = request_bytes(&mut recorder, &reads, 256);
+
+    assert_eq!(status_line(&response), "HTTP/1.1 200 OK");
+    assert_eq!([recorder.planes.bw.buffer.as_slice(), &recorder.planes.chromatic.buffer].concat(), upload[13..]);
+    assert!(matches!(recorder.shown[..], [DisplayCmd::Bitmap]));
+}
+
+#[test]
+fn rejects_bitmaps_that_do_not_decode_to_the_planes() {
+    // Raw planes of the wrong length are rejected before decoding.
+    let mut recorder = Recorder::default();
+    let response = request_bytes(&mut recorder, &[&post_bitmap(b"EPDP\x01\x00\x00\x00\x00\x00\x08\x00\x01\x00")], 1024);
+
+    assert_eq!(status_line(&response), "HTTP/1.1 400 Bad Request");
+    assert_eq!(recorder.planes.uploads, 0);
+
+    // PackBits that repeats a byte beyond the planes, the rest of the body isn't read.
+    let mut upload = b"EPDP\x03\x00\x00\x00\x00\x00\x08\x00\x01\x81\x00".to_vec();
+    upload.extend_from_slice(&[0x00; 2000]);
+    let response = request_bytes(&mut recorder, &[&post_bitmap(&upload)], 256);
+
+    assert_eq!(status_line(&response), "HTTP/1.1 400 Bad Request");
+    assert_eq!(body(&response), "{\"id\":null,\"status\":\"error\",\"error\":\"invalid_bitmap\"}\n");
+    assert_eq!(recorder.rejected, [MessageError::InvalidBitmap, MessageError::InvalidBitmap]);
+    assert!(recorder.shown.is_empty());
+
+    // The peer closes the connection before the end of the planes.
+    let message = post_bitmap(&[b"EPDP\x01\x00\x00\x00\x00\x00\x08\x00\x02".as_slice(), &[0x00; 4]].concat());
+    let response = request_bytes(&mut recorder, &[&message[..message.len() - 1]], 256);
+
+    assert_eq!(response, "");
+    assert!(recorder.shown.is_empty());
+}
+
+#[test]
+fn answers_status() {
+    let mut recorder = Recorder::default();
+    recorder.show(DisplayCmd::None);
+    let response = request(&mut recorder, &["GET /status HTTP/1.1\r\nHost: display\r\n\r\n"], 1024);
+
+    assert_eq!(status_line(&response), "HTTP/1.1 200 OK");
+    assert_eq!(body(&response), r#"{"sequence":1,"refreshed":1,"sleeping":false,"uptime":42}"#);
+}
+
+#[test]
+fn serves_index_page() {
+    let response = request(&mut Recorder::default(), &["GET / HTTP/1.1\r\nHost: display\r\n\r\n"], 1024);
+
+    assert_eq!(status_line(&response), "HTTP/1.1 200 OK");
+    assert!(response.contains("Content-Type: text/html; charset=utf-8\r\n"));
+    assert!(response.contains(&format!("Content-Length: {}\r\n", INDEX_PAGE.len())));
+    assert_eq!(body(&response).as_bytes(), INDEX_PAGE);
+
+    let response = request(&mut Recorder::default(), &["POST / HTTP/1.1\r\nContent-Length: 0\r\n\r\n"], 1024);
+    assert_eq!(status_line(&response), "HTTP/1.1 405 Method Not Allowed");
+    assert!(response.contains("Allow: GET\r\n"));
+}
+
+#[test]
+fn clears_and_sleeps() {
+    let mut recorder = Recorder::default();
+
+    let response = request(&mut recorder, &["POST /sleep HTTP/1.1\r\nContent-Length: 0\r\n\r\n"], 1024);
+    assert_eq!(status_line(&response), "HTTP/1.1 200 OK");
+
+    let response = request(&mut recorder, &["POST /clear HTTP/1.1\r\n\r\n"], 1024);
+    assert_eq!(status_line(&response), "HTTP/1.1 200 OK");
+    assert!(matches!(recorder.shown[..], [DisplayCmd::Sleep, DisplayCmd::Clear]));
+}
+
+#[test]
+fn answers_errors() {
+    let cases = [
+        ("GET /missing HTTP/1.1\r\n\r\n", "HTTP/1.1 404 Not Found"),
+        ("GET /display HTTP/1.1\r\n\r\n", "HTTP/1.1 405 Method Not Allowed"),
+        ("POST /display HTTP/1.1\r\n\r\n", "HTTP/1.1 411 Length Required"),
+        ("POST /display HTTP/1.1\r\nContent-Length: many\r\n\r\n", "HTTP/1.1 400 Bad Request"),
+        ("POST /display HTTP/1.1\r\nContent-Length: 2\r\nContent-Length: 12\r\n\r\n{}", "HTTP/1.1 400 Bad Request"),
+        ("POST /display HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n", "HTTP/1.1 501 Not Implemented"),
+        ("GET /status HTTP/2\r\n\r\n", "HTTP/1.1 505 HTTP Version Not Supported"),
+        ("garbage\r\n\r\n", "HTTP/1.1 400 Bad Request"),
+    ];
+
+    for (request_text, expected) in cases {
+        let mut recorder = Recorder::default();
+        let response = request(&mut recorder, &[request_text], 1024);
+
+        assert_eq!(status_line(&response), expected, "{:?}", request_text);
+        assert!(response.contains("Content-Length: 0\r\n"), "{:?}", request_text);
+    }
+
+    let response = request(&mut Recorder::default(), &["GET /display HTTP/1.1\r\n\r\n"], 1024);
+    assert!(response.contains("Allow: POST\r\n"));
+
+    let long_header = format!("GET /status HTTP/1.1\r\nCookie: {}\r\n\r\n", "x".repeat(64));
+    let response = request(&mut Recorder::default(), &[&long_header], 32);
+    assert_eq!(status_line(&response), "HTTP/1.1 431 Request Header Fields Too Large");
+}
@@ -0,0 +1,343 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::future::poll_fn;
+use std::task::Poll;
+
+use display_core::data::display_cmd::DisplayCmd;
+use display_core::protocol::MessageError;
+use display_core::protocol::bitmap::PlaneBuffers;
+use display_core::protocol::connection::Handler;
+use display_core::protocol::mqtt::{serve_mqtt, MqttConfig, MqttError, MqttHandler, Refresh};
+use embassy_futures::block_on;
+use embedded_io_async::{ErrorType, Read, Write};
+use epd_display::epd::epd_2in66b::PlaneBuffer;
+
+const CONFIG: MqttConfig = MqttConfig {
+    client_id: "door",
+    topic: "display/door",
+    username: Some("user"),
+    password: Some("secret"),
+    keep_alive: 60,
+};
+
+const CONNACK: &[u8] = &[0x20, 2, 0, 0];
+const SUBACK: &[u8] = &[0x90, 4, 0, 1, 1, 1];
+const PINGRESP: &[u8] = &[0xd0, 0];
+
+/// Connection to a broker that returns at most one chunk per read and records everything written.
+/// A `None` chunk keeps the read pending once, so the session handles the next refresh instead.
+struct Connection {
+    reads: VecDeque<Option<Vec<u8>>>,
+    written: Vec<u8>,
+}
+
+impl Connection {
+    fn new(reads: &[Option<&[u8]>]) -> Self {
+        Connection { reads: reads.iter().map(|read| read.map(<[u8]>::to_vec)).collect(), written: Vec::new() }
+    }
+}
+
+impl ErrorType for Connection {
+    type Error = Infallible;
+}
+
+impl Read for Connection {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        let Some(mut chunk) = self.reads.pop_front() else { return Ok(0) };
+
+        let Some(bytes) = chunk.as_mut() else {
+            let mut pending = true;
+            return poll_fn(|cx| {
+                cx.waker().wake_by_ref();
+                if std::mem::take(&mut pending) { Poll::Pending } else { Poll::Ready(Ok(0)) }
+            })
+            .await;
+        };
+
+        let n = bytes.len().min(buf.len());
+        buf[..n].copy_from_slice(&bytes[..n]);
+
+        if n < bytes.len() {
+            self.reads.push_front(Some(bytes.split_off(n)));
+        }
+
+        Ok(n)
+    }
+}
+
+impl Write for Connection {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+/// Records the commands, and hands out a queued refresh, or a timeout for `None`, per call.
+#[derive(Default)]
+struct Recorder {
+    shown: Vec<DisplayCmd>,
+    rejected: Vec<MessageError>,
+    planes: Planes,
+    refreshes: VecDeque<Option<Refresh>>,
+    /// Sequence number before the first command.
+    sequence: u32,
+}
+
+/// Buffers that the bitmap uploads are decoded into, with the number of uploads.
+#[derive(Default)]
+struct Planes {
+    bw: PlaneBuffer,
+    chromatic: PlaneBuffer,
+    uploads: usize,
+}
+
+impl PlaneBuffers for Planes {
+    fn planes_mut(&mut self) -> (&mut PlaneBuffer, &mut PlaneBuffer) {
+        (&mut self.bw, &mut self.chromatic)
+    }
+}
+
+impl Handler for Recorder {
+    fn show(&mut self, cmd: DisplayCmd) -> u32 {
+        self.shown.push(cmd);
+        self.sequence.wrapping_add(self.shown.len() as u32)
+    }
+
+    async fn refreshed(&mut self, _sequence: u32) {}
+
+    type Planes<'a> = &'a mut Planes;
+
+    async fn planes(&mut self) -> &mut Planes {
+        self.planes.uploads += 1;
+        &mut self.planes
+    }
+
+    fn rejected(&mut self, error: MessageError) {
+        self.rejected.push(error);
+    }
+}
+
+impl MqttHandler for Recorder {
+    async fn next_refresh(&mut self, _timeout: u16) -> Option<Refresh> {
+        match self.refreshes.pop_front() {
+            Some(refresh) => refresh,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+fn string(text: &str) -> Vec<u8> {
+    let mut bytes = (text.len() as u16).to_be_bytes().to_vec();
+    bytes.extend_from_slice(text.as_bytes());
+    bytes
+}
+
+fn packet(first: u8, body: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![first];
+    let mut remaining = body.len();
+
+    loop {
+        let byte = (remaining % 128) as u8;
+        remaining /= 128;
+        bytes.push(if remaining > 0 { byte | 0x80 } else { byte });
+
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+fn publish(topic: &str, packet_id: Option<u16>, payload: &str) -> Vec<u8> {
+    let mut body = string(topic);
+    let first = match packet_id {
+        Some(packet_id) => {
+            body.extend_from_slice(&packet_id.to_be_bytes());
+            0x32
+        }
+        None => 0x30,
+    };
+    body.extend_from_slice(payload.as_bytes());
+    packet(first, &body)
+}
+
+/// Splits the written bytes into packets.
+fn packets(mut written: &[u8]) -> Vec<Vec<u8>> {
+    let mut packets = Vec::new();
+
+    while !written.is_empty() {
+        let (mut remaining, mut len) = (0, 1);
+
+        loop {
+            remaining |= usize::from(written[len] & 0x7f) << (7 * (len - 1));
+            len += 1;
+
+            if written[len - 1] & 0x80 == 0 {
+                break;
+            }
+        }
+
+        packets.push(written[..len + remaining].to_vec());
+        written = &written[len + remaining..];
+    }
+
+    packets
+}
+
+fn session(reads: &[Option<&[u8]>], recorder: &mut Recorder, buf_size: usize) -> (Result<(), MqttError<Infallible>>, Vec<Vec<u8>>) {
+    let mut connection = Connection::new(reads);
+    let mut buf = vec![0; buf_size];
+    let result = block_on(serve_mqtt(&mut connection, &mut buf, &CONFIG, recorder));
+
+    (result, packets(&connection.written))
+}
+
+#[test]
+fn connects_and_subscribes() {
+    let (result, written) = session(&[Some(CONNACK), Some(SUBACK)], &mut Recorder::default(), 256);
+    assert_eq!(result, Ok(()));
+
+    let mut connect = string("MQTT");
+    // Level 4, username, password, retained will, clean session, 60 s keep alive.
+    connect.extend_from_slice(&[4, 0xe6, 0, 60]);
+    for field in ["door", "display/door/status", "offline", "user", "secret"] {
+        connect.extend_from_slice(&string(field));
+    }
+
+    let mut subscribe = vec![0, 1];
+    subscribe.extend_from_slice(&string("display/door/display"));
+    subscribe.push(1);
+    subscribe.extend_from_slice(&string("display/door/bitmap"));
+    subscribe.push(1);
+
+    let mut online = publish("display/door/status", None, "online");
+    online[0] |= 0x01;
+
+    assert_eq!(written, [packet(0x10, &connect), packet(0x82, &subscribe), online]);
+}
+
+#[test]
+fn shows_published_messages() {
+    let message = publish("display/door/display", Some(7), r#"{"id": 3, "title": "Free", "body": ["until 14:30"]}"#);
+    let other = publish("display/hall/display", None, r#"{"title": "Hall", "body": []}"#);
+    let mut recorder = Recorder::default();
+
+    // The packets arrive one byte at a time.
+    let bytes = [CONNACK, SUBACK, &message, &other].concat();
+    let reads: Vec<_> = bytes.chunks(1).map(Some).collect();
+    let (result, written) = session(&reads, &mut recorder, 256);
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(recorder.shown.len(), 1);
+    assert_eq!(written[3..], [packet(0x40, &[0, 7]), publish("display/door/response", None, r#"{"id":3,"status":"ok"}"#)]);
+}
+
+#[test]
+fn rejects_invalid_and_too_long_messages() {
+    let invalid = publish("display/door/display", None, r#"{"id": 4, "title": 5}"#);
+    let long = publish("display/door/display", None, &format!(r#"{{"title": "{}", "body": []}}"#, "x".repeat(100)));
+    let valid = publish("display/door/display", None, r#"{"title": "Free", "body": []}"#);
+    let mut recorder = Recorder::default();
+
+    let (result, written) = session(&[Some(CONNACK), Some(&invalid), Some(&long), Some(&valid)], &mut recorder, 64);
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(recorder.rejected, [MessageError::Invalid, MessageError::TooLong]);
+    assert_eq!(recorder.shown.len(), 1);
+    assert_eq!(written[3..], [
+        publish("display/door/response", None, r#"{"id":4,"status":"error","error":"invalid"}"#),
+        publish("display/door/response", None, r#"{"id":null,"status":"error","error":"too_long"}"#),
+        publish("display/door/response", None, r#"{"id":null,"status":"ok"}"#),
+    ]);
+}
+
+#[test]
+fn uploads_bitmaps() {
+    let mut upload = string("display/door/bitmap");
+    upload.extend_from_slice(b"EPDP\x01\x00\x00\x00\x00\x00\x98\x00\x01");
+    upload.extend_from_slice(&[0xff; 38]);
+    let upload = packet(0x30, &upload);
+    let mut recorder = Recorder::default();
+
+    let (result, written) = session(&[Some(CONNACK), Some(&upload)], &mut recorder, 256);
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(recorder.planes.uploads, 1);
+    assert_eq!(recorder.planes.bw.buffer[..20], [[0xff; 19].as_slice(), &[0x00]].concat());
+    assert_eq!(recorder.planes.chromatic.buffer[..20], [[0xff; 19].as_slice(), &[0x00]].concat());
+    assert!(matches!(recorder.shown[..], [DisplayCmd::Bitmap]));
+    assert_eq!(written[3..], [publish("display/door/response", None, r#"{"id":null,"status":"ok"}"#)]);
+}
+
+#[test]
+fn publishes_refreshes() {
+    let message = publish("display/door/display", None, r#"{"id": 9, "notify_refresh": true, "title": "Busy", "body": []}"#);
+    let mut recorder = Recorder::default();
+    recorder.refreshes.push_back(Some(Refresh { sequence: 0, uptime: 12 }));
+    recorder.refreshes.push_back(Some(Refresh { sequence: 1, uptime: 34 }));
+
+    let (result, written) = session(&[Some(CONNACK), None, Some(&message), None], &mut recorder, 256);
+
+    let mut refreshed = publish("display/door/refreshed", None, r#"{"sequence":0,"uptime":12}"#);
+    refreshed[0] |= 0x01;
+    let mut refreshed_message = publish("display/door/refreshed", None, r#"{"sequence":1,"uptime":34}"#);
+    refreshed_message[0] |= 0x01;
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(written[3..], [
+        refreshed,
+        publish("display/door/response", None, r#"{"id":9,"status":"ok"}"#),
+        refreshed_message,
+        publish("display/door/response", None, r#"{"id":9,"status":"refreshed"}"#),
+    ]);
+}
+
+#[test]
+fn notifies_refreshes_across_the_wrap_around() {
+    let message = publish("display/door/display", None, r#"{"id": 9, "notify_refresh": true, "title": "Busy", "body": []}"#);
+    let mut recorder = Recorder { sequence: u32::MAX, ..Recorder::default() };
+    recorder.refreshes.push_back(Some(Refresh { sequence: u32::MAX, uptime: 12 }));
+    recorder.refreshes.push_back(Some(Refresh { sequence: 0, uptime: 34 }));
+
+    let (result, written) = session(&[Some(CONNACK), Some(&message), None, None], &mut recorder, 256);
+
+    let refreshed = |payload| {
+        let mut refreshed = publish("display/door/refreshed", None, payload);
+        refreshed[0] |= 0x01;
+        refreshed
+    };
+
+    // The command wrapped around to 0, the refresh of the one before doesn't answer it.
+    assert_eq!(result, Ok(()));
+    assert_eq!(written[3..], [
+        publish("display/door/response", None, r#"{"id":9,"status":"ok"}"#),
+        refreshed(r#"{"sequence":4294967295,"uptime":12}"#),
+        refreshed(r#"{"sequence":0,"uptime":34}"#),
+        publish("display/door/response", None, r#"{"id":9,"status":"refreshed"}"#),
+    ]);
+}
+
+#[test]
+fn pings_until_broker_stops_answering() {
+    let mut recorder = Recorder::default();
+    recorder.refreshes.extend([None, None, None]);
+
+    let (result, written) = session(&[Some(CONNACK), None, Some(PINGRESP), None, None], &mut recorder, 256);
+
+    assert_eq!(result, Err(MqttError::Timeout));
+    assert_eq!(written[3..], [packet(0xc0, &[]), packet(0xc0, &[])]);
+}
+
+#[test]
+fn reports_refused_connection() {
+    let (result, _) = session(&[Some(&[0x20, 2, 0, 5])], &mut Recorder::default(), 256);
+    assert_eq!(result, Err(MqttError::Refused(5)));
+
+    let (result, _) = session(&[Some(CONNACK), Some(&[0x90, 4, 0, 1, 1, 0x80])], &mut Recorder::default(), 256);
+    assert_eq!(result, Err(MqttError::SubscriptionRefused));
+
+    let (result, _) = session(&[Some(CONNACK), Some(&[0x30, 0xff, 0xff, 0xff, 0xff])], &mut Recorder::default(), 256);
+    assert_eq!(result, Err(MqttError::Protocol));
+}
@@ -0,0 +1,333 @@
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+use display_core::data::display_cmd::{ChartKind, DisplayCmd, QrCodeContent, RefreshMode, TextLine, TextPanelContent};
+use display_core::data::screen::FontSize;
+use display_core::protocol::connection::{serve, Handler};
+use display_core::protocol::{parse_message, parse_request, MessageError};
+use display_core::render::draw;
+use embassy_futures::block_on;
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::Alignment;
+use embedded_io_async::{ErrorType, Read, Write};
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::simulator::SimulatedDisplay;
+
+/// Connection that returns at most one chunk per read and records everything written.
+struct Connection {
+    reads: VecDeque<Vec<u8>>,
+    written: Vec<u8>,
+}
+
+impl Connection {
+    fn new(reads: &[&str]) -> Self {
+        Connection { reads: reads.iter().map(|read| read.as_bytes().to_vec()).collect(), written: Vec::new() }
+    }
+}
+
+impl ErrorType for Connection {
+    type Error = Infallible;
+}
+
+impl Read for Connection {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        let Some(mut chunk) = self.reads.pop_front() else { return Ok(0) };
+        let n = chunk.len().min(buf.len());
+        buf[..n].copy_from_slice(&chunk[..n]);
+
+        if n < chunk.len() {
+            self.reads.push_front(chunk.split_off(n));
+        }
+
+        Ok(n)
+    }
+}
+
+impl Write for Connection {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+fn text_panel(cmd: &DisplayCmd) -> &TextPanelContent {
+    match cmd {
+        DisplayCmd::TextPanel(content) => content,
+        _ => panic!("not a text panel"),
+    }
+}
+
+fn pixels(cmd: &DisplayCmd) -> Vec<ThreeColor> {
+    let mut display = SimulatedDisplay::simulated();
+    draw(&mut display, cmd).unwrap();
+    display.bounding_box().points().map(|point| display.pixel(point).unwrap()).collect()
+}
+
+#[test]
+fn parses_text_message() {
+    let cmd = parse_message(br#"{"title": "Room \"3\"", "body": ["Booked", "until 14:30"], "qr": {"text": "https://example.com", "color": "red"}}"#).unwrap();
+    let content = text_panel(&cmd);
+
+    assert_eq!(content.title().text(), "Room \"3\"");
+    assert_eq!(content.body_len(), 2);
+    assert_eq!(content.body_line(1).unwrap().text(), "until 14:30");
+
+    let qr_code = content.qr_code().unwrap();
+    assert_eq!(qr_code.text(), "https://example.com");
+    assert_eq!(qr_code.color(), ThreeColor::Chromatic);
+}
+
+#[test]
+fn draws_escaped_characters() {
+    let cmd = parse_message(br#"{"title": "K\u00e4se", "body": ["5 \u20ac"]}"#).unwrap();
+    let content = text_panel(&cmd);
+
+    assert_eq!(content.title().text(), "Käse");
+    assert_eq!(content.body_line(0).unwrap().text(), "5 €");
+
+    let mut expected = TextPanelContent::new(TextLine::new("Käse", ThreeColor::Black));
+    expected.add_body_line(TextLine::new("5 €", ThreeColor::Black)).unwrap();
+    assert!(pixels(&cmd) == pixels(&DisplayCmd::TextPanel(expected)));
+}
+
+#[test]
+fn rejects_white_qr_codes() {
+    let qr_code = |color: &str| format!(r#"{{"title": "Guest WiFi", "body": [], "qr": {{"text": "WIFI:S:guest;;", "color": "{}"}}}}"#, color);
+
+    assert!(parse_message(qr_code("black").as_bytes()).is_ok());
+    assert_eq!(parse_message(qr_code("white").as_bytes()).err(), Some(MessageError::Invalid));
+}
+
+#[test]
+fn parses_styled_lines() {
+    let cmd = parse_message(br#"{"title": {"text": "Alarms", "align": "center"}, "body": [{"text": "Freezer 2: -4 C", "color": "red", "font": "8x13"}, {"text": "All other sensors ok"}]}"#).unwrap();
+    let content = text_panel(&cmd);
+
+    assert_eq!(content.title().text(), "Alarms");
+    assert_eq!(content.title().alignment(), Alignment::Center);
+    assert_eq!(content.title().font_size(), FontSize::Large);
+
+    let alarm = content.body_line(0).unwrap();
+    assert_eq!(alarm.color(), ThreeColor::Chromatic);
+    assert_eq!(alarm.font_size(), FontSize::Medium);
+    assert_eq!(alarm.alignment(), Alignment::Left);
+    assert_eq!(content.body_line(1).unwrap().color(), ThreeColor::Black);
+
+    let cmd = parse_message(br#"{"title": "Alarms", "body": [{"text": "Door open", "color": "red", "align": "right"}]}"#).unwrap();
+    assert_eq!(text_panel(&cmd).body_line(0).unwrap().alignment(), Alignment::Right);
+
+    assert_eq!(parse_message(br#"{"title": "Alarms", "body": [{"text": "Door open", "font": "huge"}]}"#).err(), Some(MessageError::Invalid));
+}
+
+#[test]
+fn parses_chart_message() {
+    let cmd = parse_message(br#"{"title": "Load", "chart": {"type": "bars", "values": [1, 2.5, 3], "threshold": 2, "unit": "%"}}"#).unwrap();
+
+    let DisplayCmd::Chart(content) = cmd else { panic!("not a chart") };
+    assert!(content.kind() == ChartKind::Bars);
+    assert_eq!(content.values(), [1.0, 2.5, 3.0]);
+    assert_eq!(content.threshold(), Some(2.0));
+    assert_eq!(content.min(), None);
+    assert_eq!(content.unit(), "%");
+}
+
+#[test]
+fn parses_screen_message() {
+    let cmd = parse_message(br#"{"screen": {"widgets": [{"type": "text", "text": "Hello"}]}}"#).unwrap();
+
+    let DisplayCmd::Screen(screen) = cmd else { panic!("not a screen") };
+    assert_eq!(screen.widgets.len(), 1);
+}
+
+#[test]
+fn parses_command_messages() {
+    let cmd = |json: &str| parse_message(json.as_bytes());
+
+    assert!(matches!(cmd(r#"{"command": "clear"}"#), Ok(DisplayCmd::Clear)));
+    assert!(matches!(cmd(r#"{"command": "sleep"}"#), Ok(DisplayCmd::Sleep)));
+    assert!(matches!(cmd(r#"{"command": "status"}"#), Ok(DisplayCmd::ShowStatus)));
+    assert!(matches!(cmd(r#"{"command": "refresh"}"#), Ok(DisplayCmd::Refresh(RefreshMode::Full))));
+    assert!(matches!(cmd(r#"{"command": "refresh", "mode": "partial"}"#), Ok(DisplayCmd::Refresh(RefreshMode::Plain))));
+    assert!(matches!(cmd(r#"{"command": "refresh", "mode": "plain"}"#), Ok(DisplayCmd::Refresh(RefreshMode::Plain))));
+    assert_eq!(cmd(r#"{"command": "refresh", "mode": "fast"}"#).err(), Some(MessageError::Invalid));
+    assert!(matches!(
+        cmd(r#"{"id": 2, "command": "orientation", "orientation": "portrait_flipped"}"#),
+        Ok(DisplayCmd::SetOrientation(DisplayOrientation::PortraitFlipped))
+    ));
+
+    assert_eq!(cmd(r#"{"command": "orientation"}"#).err(), Some(MessageError::Invalid));
+    assert_eq!(cmd(r#"{"command": "reboot"}"#).err(), Some(MessageError::Invalid));
+    assert_eq!(cmd(r#"{"command": "refresh", "mode": "fast"}"#).err(), Some(MessageError::Invalid));
+}
+
+#[test]
+fn keeps_image_for_commands_without_content() {
+    let mut display = SimulatedDisplay::simulated();
+    draw(&mut display, &DisplayCmd::TextPanel(TextPanelContent::new(TextLine::new("Hello", ThreeColor::Black)))).unwrap();
+    let image: Vec<_> = display.bounding_box().points().map(|point| display.pixel(point).unwrap()).collect();
+    assert!(image.contains(&ThreeColor::Black));
+
+    for cmd in [DisplayCmd::Sleep, DisplayCmd::Refresh(RefreshMode::Plain), DisplayCmd::SetOrientation(DisplayOrientation::Portrait)] {
+        draw(&mut display, &cmd).unwrap();
+        assert!(display.bounding_box().points().map(|point| display.pixel(point).unwrap()).eq(image.iter().copied()));
+    }
+
+    draw(&mut display, &DisplayCmd::Clear).unwrap();
+    assert!(display.bounding_box().points().all(|point| display.pixel(point) == Some(ThreeColor::White)));
+}
+
+#[test]
+fn rejects_invalid_messages() {
+    assert_eq!(parse_message(b"").err(), Some(MessageError::Invalid));
+    assert_eq!(parse_message(b"hello").err(), Some(MessageError::Invalid));
+    assert_eq!(parse_message(br#"{"body": ["no title"]}"#).err(), Some(MessageError::Invalid));
+    assert_eq!(parse_message(br#"{"title": "Load", "chart": {"type": "pie", "values": [1]}}"#).err(), Some(MessageError::Invalid));
+}
+
+#[test]
+fn reports_capacity_errors() {
+    let line = |text: &str| format!("\"{}\"", text);
+    let message = |title: &str, body: &[String]| format!("{{\"title\": {}, \"body\": [{}]}}", title, body.join(", "));
+    let long = "x".repeat(81);
+
+    let too_many_lines = message(&line("Title"), &vec![line("line"); 11]);
+    assert_eq!(parse_message(too_many_lines.as_bytes()).err(), Some(MessageError::TooManyLines));
+
+    let long_title = message(&line(&long), &[]);
+    assert_eq!(parse_message(long_title.as_bytes()).err(), Some(MessageError::StringTooLong));
+
+    let long_styled_line = message(&line("Title"), &[format!("{{\"text\": {}, \"color\": \"red\"}}", line(&long))]);
+    assert_eq!(parse_message(long_styled_line.as_bytes()).err(), Some(MessageError::StringTooLong));
+
+    let long_escaped_line = message(&line("Title"), &[line(&"\\n".repeat(200))]);
+    assert_eq!(parse_message(long_escaped_line.as_bytes()).err(), Some(MessageError::StringTooLong));
+
+    let long_qr_code = format!("{{\"title\": \"Title\", \"body\": [], \"qr\": {{\"text\": {}}}}}", line(&"x".repeat(129)));
+    assert_eq!(parse_message(long_qr_code.as_bytes()).err(), Some(MessageError::StringTooLong));
+}
+
+#[test]
+fn parses_request_fields() {
+    let request = parse_request(br#"{"id": 7, "notify_refresh": true, "title": "Hello", "body": []}"#);
+    assert_eq!(request.id, Some(7));
+    assert!(request.notify_refresh);
+    assert!(request.cmd.is_ok());
+
+    let request = parse_request(br#"{"title": "Hello", "body": []}"#);
+    assert_eq!(request.id, None);
+    assert!(!request.notify_refresh);
+
+    let request = parse_request(br#"{"id": 8, "body": []}"#);
+    assert_eq!(request.id, Some(8));
+    assert_eq!(request.cmd.err(), Some(MessageError::Invalid));
+}
+
+/// Handler that records the titles of the commands and the errors of rejected messages.
+#[derive(Default)]
+struct Recorder {
+    titles: Vec<Result<String, MessageError>>,
+    refreshed: Vec<u32>,
+    last: Option<DisplayCmd>,
+}
+
+impl Handler for Recorder {
+    fn show(&mut self, cmd: DisplayCmd) -> u32 {
+        self.titles.push(Ok(text_panel(&cmd).title().text().to_string()));
+        self.last = Some(cmd);
+        self.titles.len() as u32
+    }
+
+    async fn refreshed(&mut self, sequence: u32) {
+        self.refreshed.push(sequence);
+    }
+
+    type Planes<'a> = &'a mut SimulatedDisplay;
+
+    async fn planes(&mut self) -> &mut SimulatedDisplay {
+        unreachable!("the TCP protocol has no bitmap uploads");
+    }
+
+    fn rejected(&mut self, error: MessageError) {
+        self.titles.push(Err(error));
+    }
+}
+
+fn serve_reads(reads: &[&str], buf: &mut [u8]) -> (Recorder, String) {
+    let mut connection = Connection::new(reads);
+    let mut recorder = Recorder::default();
+
+    block_on(serve(&mut connection, buf, &mut recorder)).unwrap();
+
+    (recorder, String::from_utf8(connection.written).unwrap())
+}
+
+#[test]
+fn serves_until_closed() {
+    let (recorder, written) = serve_reads(&[
+        "{\"id\": 1, \"title\": \"First\", \"body\": []}\n",
+        "garbage\r\n",
+        "\n{\"title\": \"Second\", \"body\": []}",
+    ], &mut [0; 1024]);
+
+    assert_eq!(recorder.titles, [Ok("First".to_string()), Err(MessageError::Invalid), Ok("Second".to_string())]);
+    assert_eq!(written, concat!(
+        "{\"id\":1,\"status\":\"ok\"}\n",
+        "{\"id\":null,\"status\":\"error\",\"error\":\"invalid\"}\n",
+        "{\"id\":null,\"status\":\"ok\"}\n",
+    ));
+}
+
+#[test]
+fn notifies_refresh() {
+    let (recorder, written) = serve_reads(&[
+        "{\"id\": 1, \"title\": \"First\", \"body\": []}\n",
+        "{\"id\": 2, \"notify_refresh\": true, \"title\": \"Second\", \"body\": []}\n",
+        "{\"id\": 3, \"notify_refresh\": true, \"title\": \"Third\", \"body\": [\"a\", \"b\", \"c\", \"d\", \"e\", \"f\", \"g\", \"h\", \"i\", \"j\", \"k\"]}\n",
+    ], &mut [0; 1024]);
+
+    assert_eq!(recorder.refreshed, [2]);
+    assert_eq!(written, concat!(
+        "{\"id\":1,\"status\":\"ok\"}\n",
+        "{\"id\":2,\"status\":\"ok\"}\n",
+        "{\"id\":2,\"status\":\"refreshed\"}\n",
+        "{\"id\":3,\"status\":\"error\",\"error\":\"too_many_lines\"}\n",
+    ));
+}
+
+#[test]
+fn reassembles_split_and_merged_messages() {
+    let stream = "{\"title\": \"First\", \"body\": [\"a\"]}\n{\"title\": \"Second\", \"body\": [\"b\"]}\n";
+    let expected = [Ok("First".to_string()), Ok("Second".to_string())];
+
+    for chunk_size in [1, 2, 7, 40, stream.len()] {
+        let reads: Vec<_> = stream.as_bytes().chunks(chunk_size).map(|chunk| str::from_utf8(chunk).unwrap()).collect();
+        let (recorder, written) = serve_reads(&reads, &mut [0; 64]);
+
+        assert_eq!(recorder.titles, expected, "chunk size {}", chunk_size);
+        assert_eq!(written.lines().count(), 2, "chunk size {}", chunk_size);
+    }
+}
+
+#[test]
+fn drops_messages_longer_than_buffer() {
+    let long = format!("{{\"title\": \"{}\", \"body\": []}}\n", "x".repeat(100));
+    let (recorder, written) = serve_reads(&[&long[..50], &long[50..], "{\"title\": \"Short\", \"body\": []}\n", &long[..80]], &mut [0; 64]);
+
+    assert_eq!(recorder.titles, [Err(MessageError::TooLong), Ok("Short".to_string()), Err(MessageError::TooLong)]);
+    assert!(written.starts_with("{\"id\":null,\"status\":\"error\",\"error\":\"too_long\"}\n"));
+}
+
+#[test]
+fn draws_received_message() {
+    let (recorder, _) = serve_reads(&[r#"{"title": "Guest WiFi", "body": ["Scan the code"], "qr": {"text": "WIFI:S:guest;;"}}"#], &mut [0; 1024]);
+
+    let mut expected = TextPanelContent::new(TextLine::new("Guest WiFi", ThreeColor::Black));
+    expected.add_body_line(TextLine::new("Scan the code", ThreeColor::Black)).unwrap();
+    expected.set_qr_code(QrCodeContent::new("WIFI:S:guest;;", ThreeColor::Black));
+
+    let received = pixels(&recorder.last.unwrap());
+    assert!(received == pixels(&DisplayCmd::TextPanel(expected)));
+    assert!(received.contains(&ThreeColor::Black));
+}
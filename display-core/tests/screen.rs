@@ -0,0 +1,148 @@
+use display_core::data::display_cmd::DisplayCmd;
+use display_core::data::display_cmd::STRING_CAPACITY;
+use display_core::data::screen::{Screen, ScreenError, MAX_DEPTH, MAX_SCREEN_VALUES, MAX_SCREEN_WIDGETS, SCREEN_TEXT_CAPACITY};
+use display_core::protocol::{parse_message, MessageError};
+use display_core::render::draw;
+use embedded_graphics::prelude::*;
+use epd_display::simulator::SimulatedDisplay;
+
+const SIZE: Size = Size::new(296, 152);
+
+fn screen(json: &str) -> Screen {
+    serde_json_core::from_str::<Screen>(json).unwrap().0
+}
+
+fn validate(json: &str) -> Result<(), ScreenError> {
+    screen(json).validate(SIZE)
+}
+
+#[test]
+fn accepts_valid_screens() {
+    assert_eq!(validate(r#"{"widgets": [
+        {"type": "column", "children": [1, 2], "padding": 4, "spacing": 2},
+        {"type": "text", "text": "Shelf 12", "length": 20},
+        {"type": "sparkline", "values": [1, 4, 2], "flex": 2, "stroke": 2}
+    ]}"#), Ok(()));
+    assert_eq!(validate(r#"{"widgets": [{"type": "text", "text": "x", "bounds": {"x": 10, "y": 0, "width": 286, "height": 152}}]}"#), Ok(()));
+}
+
+#[test]
+fn rejects_invalid_trees() {
+    assert_eq!(validate(r#"{"widgets": []}"#), Err(ScreenError::Empty));
+    assert_eq!(validate(r#"{"widgets": [{"type": "column", "children": [0]}]}"#), Err(ScreenError::InvalidChild(0)));
+    assert_eq!(validate(r#"{"widgets": [{"type": "column", "children": [1]}, {"type": "row", "children": [1]}]}"#), Err(ScreenError::InvalidChild(1)));
+    assert_eq!(validate(r#"{"widgets": [{"type": "text", "children": [1]}, {"type": "spacer"}]}"#), Err(ScreenError::InvalidChild(0)));
+    assert_eq!(validate(r#"{"widgets": [{"type": "gauge"}]}"#), Err(ScreenError::MissingValues(0)));
+}
+
+#[test]
+fn rejects_bounds_outside_the_display() {
+    let bounds = |x: i64, y: i64, width: u32, height: u32| {
+        validate(&format!(r#"{{"widgets": [{{"type": "text", "text": "x", "bounds": {{"x": {}, "y": {}, "width": {}, "height": {}}}}}]}}"#, x, y, width, height))
+    };
+
+    assert_eq!(bounds(10, 0, 287, 10), Err(ScreenError::OutOfBounds(0)));
+    assert_eq!(bounds(0, 10, 10, 143), Err(ScreenError::OutOfBounds(0)));
+    assert_eq!(bounds(-1, 0, 10, 10), Err(ScreenError::OutOfBounds(0)));
+    assert_eq!(bounds(296, 0, 0, 10), Err(ScreenError::OutOfBounds(0)));
+    assert_eq!(bounds(10, 0, u32::MAX, 10), Err(ScreenError::OutOfBounds(0)));
+    assert_eq!(bounds(0, 10, 10, u32::MAX), Err(ScreenError::OutOfBounds(0)));
+    assert_eq!(bounds(i32::MAX as i64, i32::MAX as i64, u32::MAX, u32::MAX), Err(ScreenError::OutOfBounds(0)));
+}
+
+#[test]
+fn rejects_sizes_larger_than_the_display() {
+    for field in ["length", "padding", "spacing", "stroke"] {
+        let message = |value: u32| format!(r#"{{"widgets": [{{"type": "row", "children": [1, 2], "{}": {}}}, {{"type": "spacer", "{}": {}}}, {{"type": "line"}}]}}"#, field, value, field, value);
+
+        assert_ne!(validate(&message(296)), Err(ScreenError::TooLarge(0)), "{}", field);
+        assert_eq!(validate(&message(297)), Err(ScreenError::TooLarge(0)), "{}", field);
+        assert_eq!(validate(&message(u32::MAX)), Err(ScreenError::TooLarge(0)), "{}", field);
+    }
+}
+
+#[test]
+fn accepts_large_flex_weights() {
+    assert_eq!(validate(r#"{"widgets": [{"type": "row", "children": [1, 2]}, {"type": "spacer", "flex": 300}, {"type": "line", "flex": 4294967295}]}"#), Ok(()));
+}
+
+#[test]
+fn keeps_texts_and_values_of_all_widgets() {
+    let screen = screen(r#"{"widgets": [
+        {"type": "column", "children": [1, 2, 3]},
+        {"type": "text", "text": "Shelf 12"},
+        {"type": "sparkline", "values": [1, 4, 2]},
+        {"type": "qr", "text": "https://example.com"}
+    ]}"#);
+    let widgets = &screen.widgets;
+
+    assert_eq!(screen.text(&widgets[0]), "");
+    assert_eq!(screen.text(&widgets[1]), "Shelf 12");
+    assert_eq!(screen.values(&widgets[2]), [1.0, 4.0, 2.0]);
+    assert_eq!(screen.text(&widgets[3]), "https://example.com");
+    assert!(screen.values(&widgets[3]).is_empty());
+}
+
+#[test]
+fn rejects_screens_larger_than_the_capacity() {
+    let widgets = |count: usize| format!(r#"{{"widgets": [{}]}}"#, vec![r#"{"type": "spacer"}"#; count].join(", "));
+    assert!(serde_json_core::from_str::<Screen>(&widgets(MAX_SCREEN_WIDGETS)).is_ok());
+    assert!(serde_json_core::from_str::<Screen>(&widgets(MAX_SCREEN_WIDGETS + 1)).is_err());
+
+    let values = |count: usize| format!(r#"{{"type": "bars", "values": [{}]}}"#, vec!["1"; count].join(", "));
+    let charts = format!(r#"{{"widgets": [{}, {}]}}"#, values(MAX_SCREEN_VALUES / 2), values(MAX_SCREEN_VALUES / 2));
+    assert!(serde_json_core::from_str::<Screen>(&charts).is_ok());
+    let charts = format!(r#"{{"widgets": [{}, {}]}}"#, values(MAX_SCREEN_VALUES / 2), values(MAX_SCREEN_VALUES / 2 + 1));
+    assert!(serde_json_core::from_str::<Screen>(&charts).is_err());
+
+    let text = "x".repeat(STRING_CAPACITY);
+    let texts = |count: usize| format!(r#"{{"widgets": [{}]}}"#, vec![format!(r#"{{"type": "text", "text": "{}"}}"#, text); count].join(", "));
+    assert!(serde_json_core::from_str::<Screen>(&texts(SCREEN_TEXT_CAPACITY / STRING_CAPACITY)).is_ok());
+    assert!(serde_json_core::from_str::<Screen>(&texts(SCREEN_TEXT_CAPACITY / STRING_CAPACITY + 1)).is_err());
+}
+
+#[test]
+fn rejects_invalid_screen_messages() {
+    let message = |widgets: &str| parse_message(format!(r#"{{"screen": {{"widgets": {}}}}}"#, widgets).as_bytes()).err();
+
+    assert_eq!(message("[]"), Some(MessageError::InvalidScreen));
+    assert_eq!(message(r#"[{"type": "column", "children": [0]}]"#), Some(MessageError::InvalidScreen));
+    assert_eq!(message(r#"[{"type": "gauge"}]"#), Some(MessageError::InvalidScreen));
+    assert_eq!(message(r#"[{"type": "qr", "text": "a"}, {"type": "qr", "text": "b"}, {"type": "qr", "text": "c"}]"#), Some(MessageError::InvalidScreen));
+    assert_eq!(message(r#"[{"type": "text", "text": "x", "bounds": {"x": 0, "y": 0, "width": 297, "height": 10}}]"#), Some(MessageError::InvalidScreen));
+    assert_eq!(message(r#"[{"type": "text", "text": "x", "length": 400}]"#), Some(MessageError::InvalidScreen));
+
+    let nested = (0..MAX_DEPTH).map(|i| format!(r#"{{"type": "row", "children": [{}]}}"#, i + 1)).collect::<Vec<_>>().join(", ");
+    assert_eq!(message(&format!(r#"[{}, {{"type": "spacer"}}]"#, nested)), Some(MessageError::InvalidScreen));
+}
+
+#[test]
+fn accepts_screens_that_fit_one_orientation() {
+    let bounds = |width: u32, height: u32| {
+        parse_message(format!(r#"{{"screen": {{"widgets": [{{"type": "text", "text": "x", "bounds": {{"x": 0, "y": 0, "width": {}, "height": {}}}}}]}}}}"#, width, height).as_bytes())
+    };
+
+    assert!(matches!(bounds(296, 152), Ok(DisplayCmd::Screen(_))));
+    assert!(matches!(bounds(152, 296), Ok(DisplayCmd::Screen(_))));
+    assert_eq!(bounds(296, 153).err(), Some(MessageError::InvalidScreen));
+}
+
+#[test]
+fn draws_screens_with_extreme_values() {
+    // Only the screen with the flex weights is accepted when parsing, the others are drawn directly
+    // as they have to be rejected when drawn as well.
+    let screens = [
+        r#"{"widgets":[{"type":"text","text":"x","bounds":{"x":10,"y":0,"width":4294967295,"height":10}}]}"#,
+        r#"{"widgets":[{"type":"row","children":[1,2]},{"type":"spacer","flex":4294967295},{"type":"spacer","flex":4294967295}]}"#,
+        r#"{"widgets":[{"type":"text","text":"x","padding":4294967295}]}"#,
+        r#"{"widgets":[{"type":"column","children":[1,2],"spacing":4294967295},{"type":"text","text":"x"},{"type":"line","stroke":4294967295}]}"#,
+    ];
+
+    for json in screens {
+        let mut display = SimulatedDisplay::simulated();
+        draw(&mut display, &DisplayCmd::Screen(screen(json))).unwrap();
+    }
+
+    let cmd = parse_message(format!(r#"{{"screen":{}}}"#, screens[1]).as_bytes()).unwrap();
+    draw(&mut SimulatedDisplay::simulated(), &cmd).unwrap();
+}
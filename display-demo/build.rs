@@ -3,6 +3,8 @@ use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 
+use epd_assets::{AssetBuilder, Charset};
+
 fn main() {
     let out = &PathBuf::from(env::var_os("OUT_DIR").unwrap());
 
@@ -19,6 +21,12 @@ fn main() {
         .write_all(memory_x.as_bytes())
         .unwrap();
 
+    AssetBuilder::new()
+        .bdf_font("FONT_SYMBOLS", "assets/symbols.bdf", Charset::All)
+        .image("LOGO", "assets/logo.png")
+        .build(out.join("assets.rs"))
+        .unwrap();
+
     println!("cargo:rustc-link-search={}", out.display());
     println!("cargo:rerun-if-changed=memory-rp2040.x");
     println!("cargo:rerun-if-changed=memory-rp2350.x");
@@ -13,7 +13,8 @@ use embedded_graphics::{
     prelude::*,
     mono_font::{ascii::FONT_10X20, MonoTextStyle},
     primitives::{Circle, Line, Rectangle, PrimitiveStyle},
-    text::{Alignment, Text},
+    image::Image,
+    text::{Alignment, Baseline, Text},
 };
 
 use static_cell::StaticCell;
@@ -23,6 +24,11 @@ use dev_tools::stack_paint::{paint_stack, measure_stack_usage};
 use epd_display::{EpdType, EpdPeripherals};
 use epd_display::epd::epd_2in66b::Epd2in66b;
 use epd_display::epd::three_color::ThreeColor;
+use epd_display::text::font_text_style::FontTextStyle;
+
+mod assets {
+    include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+}
 
 static EXECUTOR0: StaticCell<Executor> = StaticCell::new();
 static DISPLAY: StaticCell<EpdType<SPI1>> = StaticCell::new();
@@ -109,6 +115,16 @@ where
         Alignment::Left,
     ).draw(display);
 
+    let _ = Image::new(&assets::LOGO, Point::new(w as i32 - 50, 2))
+        .draw(display);
+
+    let _ = Text::with_baseline(
+        "\u{2191}\u{2193}\u{2713}\u{2717}",
+        Point::new(w as i32 - 42, 38),
+        FontTextStyle::new(&assets::FONT_SYMBOLS, ThreeColor::White),
+        Baseline::Top,
+    ).draw(display);
+
     let ly = 65;
     let _ = Line::new(Point::new(0, ly), Point::new(w as i32, ly))
         .into_styled(PrimitiveStyle::with_stroke(ThreeColor::Chromatic, 3))
@@ -0,0 +1,120 @@
+use crate::charset::Charset;
+use crate::raster_font::{RasterFont, RasterGlyph};
+
+struct BdfChar {
+    encoding: i64,
+    advance: i32,
+    bbx: [i32; 4],
+    bitmap: Vec<Vec<u8>>,
+}
+
+/// Parses a font in the Glyph Bitmap Distribution Format.
+pub fn parse(data: &[u8], charset: Charset) -> Result<RasterFont, String> {
+    let text = String::from_utf8_lossy(data);
+    let mut lines = text.lines().enumerate();
+    let mut bounding_box = None;
+    let mut ascent = None;
+    let mut descent = None;
+    let mut chars = Vec::new();
+
+    while let Some((n, line)) = lines.next() {
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("FONTBOUNDINGBOX") => bounding_box = Some(numbers::<4>(words, n)?),
+            Some("FONT_ASCENT") => ascent = Some(numbers::<1>(words, n)?[0]),
+            Some("FONT_DESCENT") => descent = Some(numbers::<1>(words, n)?[0]),
+            Some("STARTCHAR") => chars.push(parse_char(&mut lines)?),
+            _ => {}
+        }
+    }
+
+    let [_, bbx_height, _, bbx_y_offset] = bounding_box.ok_or("missing FONTBOUNDINGBOX")?;
+    let ascent = ascent.unwrap_or(bbx_height + bbx_y_offset);
+    let descent = descent.unwrap_or(-bbx_y_offset);
+
+    if ascent < 0 || descent < 0 {
+        return Err(format!("invalid ascent {} or descent {}", ascent, descent));
+    }
+
+    let glyphs = chars.into_iter()
+        .filter_map(|c| {
+            let character = u32::try_from(c.encoding).ok().and_then(char::from_u32)?;
+            charset.contains(character).then(|| to_glyph(character, ascent, c))
+        })
+        .collect();
+
+    Ok(RasterFont { line_height: (ascent + descent) as u32, baseline: ascent as u32, glyphs })
+}
+
+fn parse_char<'a>(lines: &mut impl Iterator<Item = (usize, &'a str)>) -> Result<BdfChar, String> {
+    let mut encoding = -1;
+    let mut advance = 0;
+    let mut bbx = [0; 4];
+
+    while let Some((n, line)) = lines.next() {
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("ENCODING") => encoding = numbers::<1>(words, n)?[0] as i64,
+            Some("DWIDTH") => advance = numbers::<1>(words, n)?[0],
+            Some("BBX") => bbx = numbers::<4>(words, n)?,
+            Some("BITMAP") => {
+                let rows = usize::try_from(bbx[1]).map_err(|_| format!("line {}: invalid BBX height", n + 1))?;
+                let mut bitmap = Vec::with_capacity(rows);
+
+                for (n, row) in lines.take(rows) {
+                    bitmap.push(hex_row(row.trim()).ok_or_else(|| format!("line {}: invalid bitmap row", n + 1))?);
+                }
+
+                return Ok(BdfChar { encoding, advance, bbx, bitmap });
+            }
+            Some("ENDCHAR") => return Err(format!("line {}: missing BITMAP", n + 1)),
+            _ => {}
+        }
+    }
+
+    Err("unexpected end of file".to_string())
+}
+
+fn to_glyph(character: char, ascent: i32, c: BdfChar) -> RasterGlyph {
+    let [width, height, x_offset, y_offset] = c.bbx;
+    let (width, height) = (width.max(0) as usize, c.bitmap.len().min(height.max(0) as usize));
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| c.bitmap[y].get(x / 8).is_some_and(|byte| byte & (0x80 >> (x % 8)) != 0))
+        .collect();
+
+    RasterGlyph {
+        character,
+        width,
+        height,
+        x_offset,
+        y_offset: ascent - (y_offset + height as i32),
+        advance: c.advance.max(0) as u32,
+        pixels,
+    }.trim()
+}
+
+fn numbers<'a, const N: usize>(mut words: impl Iterator<Item = &'a str>, n: usize) -> Result<[i32; N], String> {
+    let mut numbers = [0; N];
+
+    for number in numbers.iter_mut() {
+        *number = words.next()
+            .and_then(|word| word.parse().ok())
+            .ok_or_else(|| format!("line {}: expected {} numbers", n + 1, N))?;
+    }
+
+    Ok(numbers)
+}
+
+fn hex_row(row: &str) -> Option<Vec<u8>> {
+    if !row.is_ascii() || !row.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..row.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&row[i..i + 2], 16).ok())
+        .collect()
+}
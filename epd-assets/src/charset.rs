@@ -0,0 +1,42 @@
+/// Characters converted from a font.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Charset {
+    /// Printable ASCII characters.
+    Ascii,
+    /// Printable ISO-8859-1 characters.
+    Latin1,
+    /// Printable ISO-8859-15 characters, including the euro sign.
+    Latin9,
+    /// All characters of the font, only supported for BDF fonts.
+    All,
+}
+
+const LATIN9_REPLACED: [char; 8] = ['\u{a4}', '\u{a6}', '\u{a8}', '\u{b4}', '\u{b8}', '\u{bc}', '\u{bd}', '\u{be}'];
+const LATIN9_ADDED: [char; 8] = ['\u{20ac}', '\u{160}', '\u{161}', '\u{17d}', '\u{17e}', '\u{152}', '\u{153}', '\u{178}'];
+
+impl Charset {
+    pub fn contains(self, c: char) -> bool {
+        let ascii = (' '..='~').contains(&c);
+        let latin1 = ascii || ('\u{a0}'..='\u{ff}').contains(&c);
+
+        match self {
+            Charset::Ascii => ascii,
+            Charset::Latin1 => latin1,
+            Charset::Latin9 => (latin1 && !LATIN9_REPLACED.contains(&c)) || LATIN9_ADDED.contains(&c),
+            Charset::All => true,
+        }
+    }
+
+    /// Returns the characters of the charset, `None` for `Charset::All`.
+    pub fn chars(self) -> Option<Vec<char>> {
+        if self == Charset::All {
+            return None;
+        }
+
+        let candidates = (' '..='~').chain('\u{a0}'..='\u{ff}').chain(LATIN9_ADDED);
+        let mut chars: Vec<char> = candidates.filter(|&c| self.contains(c)).collect();
+        chars.sort();
+
+        Some(chars)
+    }
+}
@@ -0,0 +1,41 @@
+use std::fmt::Write;
+
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_uppercase() || c == '_')
+        && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+pub fn byte_array(bytes: &[u8]) -> String {
+    let mut code = String::from("&[");
+
+    for (i, byte) in bytes.iter().enumerate() {
+        if i % 16 == 0 {
+            code.push_str("\n        ");
+        } else {
+            code.push(' ');
+        }
+
+        let _ = write!(code, "0x{:02x},", byte);
+    }
+
+    code.push_str("\n    ]");
+    code
+}
+
+/// Packs rows of pixels MSB first, each row padded to whole bytes.
+pub fn pack_rows(width: usize, height: usize, pixel: impl Fn(usize, usize) -> bool) -> Vec<u8> {
+    let stride = width.div_ceil(8);
+    let mut bytes = vec![0; stride * height];
+
+    for y in 0..height {
+        for x in 0..width {
+            if pixel(x, y) {
+                bytes[y * stride + x / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+
+    bytes
+}
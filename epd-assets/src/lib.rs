@@ -0,0 +1,132 @@
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub use crate::charset::Charset;
+
+mod bdf;
+mod charset;
+mod codegen;
+mod png_image;
+mod raster_font;
+mod ttf;
+
+/// Converts fonts and images into a Rust source file for `include!` in the firmware.
+///
+/// Fonts become `epd_display::text::bitmap_font::BitmapFont` statics and images become
+/// `epd_display::image::three_color_image::ThreeColorImage` statics. Relative paths are resolved
+/// from the package directory of the build script.
+///
+/// ```ignore
+/// AssetBuilder::new()
+///     .bdf_font("FONT_SYMBOLS", "assets/symbols.bdf", Charset::All)
+///     .ttf_font("FONT_SANS_24", "assets/sans.ttf", 24.0, Charset::Latin9)
+///     .image("LOGO", "assets/logo.png")
+///     .build(out.join("assets.rs"))
+///     .unwrap();
+/// ```
+pub struct AssetBuilder {
+    assets: Vec<Asset>,
+}
+
+enum Asset {
+    BdfFont { name: String, path: PathBuf, charset: Charset },
+    TtfFont { name: String, path: PathBuf, pixel_size: f32, charset: Charset },
+    Image { name: String, path: PathBuf },
+}
+
+impl AssetBuilder {
+    pub fn new() -> Self {
+        AssetBuilder { assets: Vec::new() }
+    }
+
+    pub fn bdf_font(mut self, name: &str, path: impl AsRef<Path>, charset: Charset) -> Self {
+        let (name, path) = (name.to_string(), path.as_ref().to_path_buf());
+        self.assets.push(Asset::BdfFont { name, path, charset });
+        self
+    }
+
+    pub fn ttf_font(mut self, name: &str, path: impl AsRef<Path>, pixel_size: f32, charset: Charset) -> Self {
+        let (name, path) = (name.to_string(), path.as_ref().to_path_buf());
+        self.assets.push(Asset::TtfFont { name, path, pixel_size, charset });
+        self
+    }
+
+    pub fn image(mut self, name: &str, path: impl AsRef<Path>) -> Self {
+        let (name, path) = (name.to_string(), path.as_ref().to_path_buf());
+        self.assets.push(Asset::Image { name, path });
+        self
+    }
+
+    /// Converts all assets and writes the generated code to `out`.
+    pub fn build(self, out: impl AsRef<Path>) -> Result<(), AssetError> {
+        let mut code = String::from("// Generated by epd-assets, do not edit.\n");
+
+        for asset in &self.assets {
+            let (name, path) = asset.name_and_path();
+
+            if !codegen::is_valid_name(name) {
+                return Err(AssetError::InvalidName(name.to_string()));
+            }
+
+            println!("cargo:rerun-if-changed={}", path.display());
+            let data = fs::read(path).map_err(|e| AssetError::Io(path.to_path_buf(), e))?;
+
+            let item = match asset {
+                Asset::BdfFont { charset, .. } => bdf::parse(&data, *charset)
+                    .and_then(|font| font.to_code(name))
+                    .map_err(|e| AssetError::Font(path.to_path_buf(), e))?,
+                Asset::TtfFont { pixel_size, charset, .. } => ttf::rasterize(&data, *pixel_size, *charset)
+                    .and_then(|font| font.to_code(name))
+                    .map_err(|e| AssetError::Font(path.to_path_buf(), e))?,
+                Asset::Image { .. } => png_image::decode(&data)
+                    .map(|image| image.to_code(name))
+                    .map_err(|e| AssetError::Image(path.to_path_buf(), e))?,
+            };
+
+            code.push('\n');
+            code.push_str(&item);
+        }
+
+        let out = out.as_ref();
+        fs::write(out, code).map_err(|e| AssetError::Io(out.to_path_buf(), e))
+    }
+}
+
+impl Default for AssetBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Asset {
+    fn name_and_path(&self) -> (&str, &Path) {
+        match self {
+            Asset::BdfFont { name, path, .. } => (name, path),
+            Asset::TtfFont { name, path, .. } => (name, path),
+            Asset::Image { name, path } => (name, path),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AssetError {
+    Io(PathBuf, io::Error),
+    Font(PathBuf, String),
+    Image(PathBuf, String),
+    InvalidName(String),
+}
+
+impl Display for AssetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            AssetError::Font(path, e) => write!(f, "{}: invalid font: {}", path.display(), e),
+            AssetError::Image(path, e) => write!(f, "{}: invalid image: {}", path.display(), e),
+            AssetError::InvalidName(name) => write!(f, "'{}' is not a valid static name", name),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
@@ -0,0 +1,102 @@
+use std::fmt::Write;
+
+use png::{ColorType, Decoder, Transformations};
+
+use crate::codegen::{byte_array, pack_rows};
+
+#[derive(Copy, Clone, PartialEq)]
+enum Pixel {
+    Transparent,
+    Black,
+    White,
+    Chromatic,
+}
+
+pub struct ThreeColorImage {
+    width: usize,
+    height: usize,
+    pixels: Vec<Pixel>,
+}
+
+/// Decodes a PNG image and maps its pixels to the three display colors.
+///
+/// Saturated red pixels become chromatic, all other pixels are black or white by luminance.
+/// Pixels with less than half opacity are transparent.
+pub fn decode(data: &[u8]) -> Result<ThreeColorImage, String> {
+    let mut decoder = Decoder::new(data);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+    let (width, height) = (info.width as usize, info.height as usize);
+
+    let channels = match info.color_type {
+        ColorType::Grayscale => 1,
+        ColorType::GrayscaleAlpha => 2,
+        ColorType::Rgb => 3,
+        ColorType::Rgba => 4,
+        ColorType::Indexed => return Err("unexpanded indexed colors".to_string()),
+    };
+
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let offset = y * info.line_size + x * channels;
+            let values = &buffer[offset..offset + channels];
+
+            match values {
+                [gray] => to_pixel(*gray, *gray, *gray, 255),
+                [gray, alpha] => to_pixel(*gray, *gray, *gray, *alpha),
+                [r, g, b] => to_pixel(*r, *g, *b, 255),
+                [r, g, b, alpha] => to_pixel(*r, *g, *b, *alpha),
+                _ => Pixel::Transparent,
+            }
+        })
+        .collect();
+
+    Ok(ThreeColorImage { width, height, pixels })
+}
+
+fn to_pixel(r: u8, g: u8, b: u8, alpha: u8) -> Pixel {
+    let (r, g, b) = (r as u32, g as u32, b as u32);
+    let luminance = (299 * r + 587 * g + 114 * b) / 1000;
+
+    if alpha < 128 {
+        Pixel::Transparent
+    } else if r >= 128 && r >= 2 * g.max(b) {
+        Pixel::Chromatic
+    } else if luminance < 128 {
+        Pixel::Black
+    } else {
+        Pixel::White
+    }
+}
+
+impl ThreeColorImage {
+    fn pixel(&self, x: usize, y: usize) -> Pixel {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn to_code(&self, name: &str) -> String {
+        let bw_plane = pack_rows(self.width, self.height, |x, y| self.pixel(x, y) == Pixel::White);
+        let chromatic_plane = pack_rows(self.width, self.height, |x, y| self.pixel(x, y) == Pixel::Chromatic);
+        let mask_plane = if self.pixels.contains(&Pixel::Transparent) {
+            let mask = pack_rows(self.width, self.height, |x, y| self.pixel(x, y) != Pixel::Transparent);
+            format!("Some({})", byte_array(&mask))
+        } else {
+            "None".to_string()
+        };
+
+        let mut code = String::new();
+        let _ = writeln!(code, "pub static {}: epd_display::image::three_color_image::ThreeColorImage = epd_display::image::three_color_image::ThreeColorImage::new(", name);
+        let _ = writeln!(code, "    {},", self.width);
+        let _ = writeln!(code, "    {},", self.height);
+        let _ = writeln!(code, "    {},", byte_array(&bw_plane));
+        let _ = writeln!(code, "    {},", byte_array(&chromatic_plane));
+        let _ = writeln!(code, "    {},", mask_plane);
+        let _ = writeln!(code, ");");
+
+        code
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt::Write;
+
+use crate::codegen::{byte_array, pack_rows};
+
+/// Glyph with 1 bit per pixel, offsets are relative to the pen position at the top of the line.
+pub struct RasterGlyph {
+    pub character: char,
+    pub width: usize,
+    pub height: usize,
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub advance: u32,
+    pub pixels: Vec<bool>,
+}
+
+pub struct RasterFont {
+    pub line_height: u32,
+    pub baseline: u32,
+    pub glyphs: Vec<RasterGlyph>,
+}
+
+impl RasterGlyph {
+    fn pixel(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Removes empty rows and columns around the glyph.
+    pub fn trim(mut self) -> Self {
+        let is_ink_row = |y: usize| (0..self.width).any(|x| self.pixel(x, y));
+        let is_ink_column = |x: usize| (0..self.height).any(|y| self.pixel(x, y));
+
+        let Some(top) = (0..self.height).find(|&y| is_ink_row(y)) else {
+            self.width = 0;
+            self.height = 0;
+            self.pixels.clear();
+            return self;
+        };
+
+        let bottom = (0..self.height).rev().find(|&y| is_ink_row(y)).unwrap_or(top);
+        let left = (0..self.width).find(|&x| is_ink_column(x)).unwrap_or(0);
+        let right = (0..self.width).rev().find(|&x| is_ink_column(x)).unwrap_or(left);
+
+        let pixels = (top..=bottom)
+            .flat_map(|y| (left..=right).map(move |x| (x, y)))
+            .map(|(x, y)| self.pixel(x, y))
+            .collect();
+
+        RasterGlyph {
+            character: self.character,
+            width: right - left + 1,
+            height: bottom - top + 1,
+            x_offset: self.x_offset + left as i32,
+            y_offset: self.y_offset + top as i32,
+            advance: self.advance,
+            pixels,
+        }
+    }
+}
+
+impl RasterFont {
+    pub fn to_code(&self, name: &str) -> Result<String, String> {
+        let mut glyphs: Vec<&RasterGlyph> = self.glyphs.iter().collect();
+        glyphs.sort_by_key(|glyph| glyph.character);
+        glyphs.dedup_by_key(|glyph| glyph.character);
+
+        let width: usize = glyphs.iter().map(|glyph| glyph.width).sum();
+        let height = glyphs.iter().map(|glyph| glyph.height).max().unwrap_or(0);
+        let mut columns = Vec::with_capacity(width);
+
+        for glyph in &glyphs {
+            columns.extend((0..glyph.width).map(|x| (*glyph, x)));
+        }
+
+        let atlas = pack_rows(width, height, |x, y| {
+            let (glyph, glyph_x) = columns[x];
+            y < glyph.height && glyph.pixel(glyph_x, y)
+        });
+
+        let mut code = String::new();
+        let _ = writeln!(code, "pub static {}: epd_display::text::bitmap_font::BitmapFont = epd_display::text::bitmap_font::BitmapFont::new(", name);
+        let _ = writeln!(code, "    {},", byte_array(&atlas));
+        let _ = writeln!(code, "    {},", width);
+        let _ = writeln!(code, "    &[");
+
+        let mut x = 0;
+
+        for glyph in glyphs {
+            let field = |value: i64, field: &str| format!("{} of '{}' out of range: {}", field, glyph.character.escape_unicode(), value);
+            let atlas_x = u16::try_from(x).map_err(|_| field(x as i64, "atlas position"))?;
+            let glyph_width = u8::try_from(glyph.width).map_err(|_| field(glyph.width as i64, "width"))?;
+            let glyph_height = u8::try_from(glyph.height).map_err(|_| field(glyph.height as i64, "height"))?;
+            let x_offset = i8::try_from(glyph.x_offset).map_err(|_| field(glyph.x_offset as i64, "x offset"))?;
+            let y_offset = i8::try_from(glyph.y_offset).map_err(|_| field(glyph.y_offset as i64, "y offset"))?;
+            let advance = u8::try_from(glyph.advance).map_err(|_| field(glyph.advance as i64, "advance"))?;
+
+            let _ = writeln!(
+                code,
+                "        epd_display::text::bitmap_font::BitmapGlyph {{ character: '{}', x: {}, width: {}, height: {}, x_offset: {}, y_offset: {}, advance: {} }},",
+                glyph.character.escape_unicode(), atlas_x, glyph_width, glyph_height, x_offset, y_offset, advance,
+            );
+
+            x += glyph.width;
+        }
+
+        let _ = writeln!(code, "    ],");
+        let _ = writeln!(code, "    {},", self.line_height);
+        let _ = writeln!(code, "    {},", self.baseline);
+        let _ = writeln!(code, ");");
+
+        Ok(code)
+    }
+}
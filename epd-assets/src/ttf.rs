@@ -0,0 +1,35 @@
+use fontdue::{Font, FontSettings};
+
+use crate::charset::Charset;
+use crate::raster_font::{RasterFont, RasterGlyph};
+
+const COVERAGE_THRESHOLD: u8 = 128;
+
+/// Rasterizes a TrueType or OpenType font at the given pixel size.
+pub fn rasterize(data: &[u8], pixel_size: f32, charset: Charset) -> Result<RasterFont, String> {
+    let chars = charset.chars().ok_or("TrueType fonts need an explicit charset")?;
+    let settings = FontSettings { scale: pixel_size, ..FontSettings::default() };
+    let font = Font::from_bytes(data, settings)?;
+    let metrics = font.horizontal_line_metrics(pixel_size).ok_or("font has no horizontal metrics")?;
+    let ascent = metrics.ascent.ceil() as i32;
+    let descent = (-metrics.descent).ceil() as i32;
+
+    let glyphs = chars.into_iter()
+        .filter(|&c| font.has_glyph(c))
+        .map(|c| {
+            let (metrics, coverage) = font.rasterize(c, pixel_size);
+
+            RasterGlyph {
+                character: c,
+                width: metrics.width,
+                height: metrics.height,
+                x_offset: metrics.xmin,
+                y_offset: ascent - (metrics.ymin + metrics.height as i32),
+                advance: metrics.advance_width.round().max(0.0) as u32,
+                pixels: coverage.iter().map(|&value| value >= COVERAGE_THRESHOLD).collect(),
+            }.trim()
+        })
+        .collect();
+
+    Ok(RasterFont { line_height: (ascent + descent).max(0) as u32, baseline: ascent.max(0) as u32, glyphs })
+}
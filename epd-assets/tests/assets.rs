@@ -0,0 +1,246 @@
+use std::fs;
+use std::path::PathBuf;
+
+use epd_assets::{AssetBuilder, AssetError, Charset};
+
+const BDF: &str = "\
+STARTFONT 2.1
+FONTBOUNDINGBOX 9 8 0 -2
+FONT_ASCENT 6
+FONT_DESCENT 2
+CHARS 2
+STARTCHAR A
+ENCODING 65
+DWIDTH 6 0
+BBX 5 4 0 1
+BITMAP
+00
+70
+88
+F8
+ENDCHAR
+STARTCHAR arrowup
+ENCODING 8593
+DWIDTH 10 0
+BBX 9 2 0 0
+BITMAP
+0800
+1C00
+ENDCHAR
+ENDFONT
+";
+
+/// Writes `data` to a file in the test directory.
+fn fixture(name: &str, data: &[u8]) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, data).unwrap();
+    path
+}
+
+/// Runs the builder and returns the generated code.
+fn build(name: &str, builder: AssetBuilder) -> Result<String, AssetError> {
+    let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(format!("{}.rs", name));
+    builder.build(&out)?;
+    Ok(fs::read_to_string(out).unwrap())
+}
+
+/// Returns the generated glyph of `c`, if any.
+fn glyph(code: &str, c: char) -> Option<&str> {
+    let character = format!("character: '{}',", c.escape_unicode());
+    code.lines().map(str::trim).find(|line| line.contains(&character))
+}
+
+fn push_u16(data: &mut Vec<u8>, values: &[u16]) {
+    for value in values {
+        data.extend(value.to_be_bytes());
+    }
+}
+
+fn push_i16(data: &mut Vec<u8>, values: &[i16]) {
+    for value in values {
+        data.extend(value.to_be_bytes());
+    }
+}
+
+/// Builds a TrueType font with 1000 units per em, an ascent of 800 and a descent of 200.
+///
+/// It contains an empty space with an advance of 300 and an 'A' drawn as a 500 x 500 square
+/// 100 units right of the origin with an advance of 700.
+fn square_font() -> Vec<u8> {
+    let mut head = Vec::new();
+    head.extend(0x0001_0000u32.to_be_bytes());
+    head.extend(0x0001_0000u32.to_be_bytes());
+    head.extend(0u32.to_be_bytes());
+    head.extend(0x5f0f_3cf5u32.to_be_bytes());
+    push_u16(&mut head, &[0, 1000]);
+    head.extend([0; 16]);
+    push_i16(&mut head, &[100, 0, 600, 500]);
+    push_u16(&mut head, &[0, 8]);
+    push_i16(&mut head, &[2, 0, 0]);
+
+    let mut hhea = Vec::new();
+    hhea.extend(0x0001_0000u32.to_be_bytes());
+    push_i16(&mut hhea, &[800, -200, 0]);
+    push_u16(&mut hhea, &[700]);
+    push_i16(&mut hhea, &[0, 0, 600, 1, 0, 0, 0, 0, 0, 0, 0]);
+    push_u16(&mut hhea, &[3]);
+
+    let mut maxp = Vec::new();
+    maxp.extend(0x0000_5000u32.to_be_bytes());
+    push_u16(&mut maxp, &[3]);
+
+    let mut hmtx = Vec::new();
+    push_u16(&mut hmtx, &[500, 0, 300, 0, 700, 100]);
+
+    // .notdef and space have no outline, the square is a single clockwise contour.
+    let mut glyf = Vec::new();
+    push_i16(&mut glyf, &[1, 100, 0, 600, 500]);
+    push_u16(&mut glyf, &[3, 0]);
+    glyf.extend([0x01; 4]);
+    push_i16(&mut glyf, &[100, 0, 500, 0]);
+    push_i16(&mut glyf, &[0, 500, 0, -500]);
+
+    let mut loca = Vec::new();
+    push_u16(&mut loca, &[0, 0, 0, glyf.len() as u16 / 2]);
+
+    // Format 4 with the segments ' ', 'A' and the final 0xffff.
+    let mut cmap = Vec::new();
+    push_u16(&mut cmap, &[0, 1, 3, 1]);
+    cmap.extend(12u32.to_be_bytes());
+    push_u16(&mut cmap, &[4, 40, 0, 6, 4, 1, 2]);
+    push_u16(&mut cmap, &[0x20, 0x41, 0xffff, 0]);
+    push_u16(&mut cmap, &[0x20, 0x41, 0xffff]);
+    push_i16(&mut cmap, &[1 - 0x20, 2 - 0x41, 1]);
+    push_u16(&mut cmap, &[0, 0, 0]);
+
+    let tables: [(&[u8; 4], Vec<u8>); 7] = [
+        (b"cmap", cmap), (b"glyf", glyf), (b"head", head), (b"hhea", hhea),
+        (b"hmtx", hmtx), (b"loca", loca), (b"maxp", maxp),
+    ];
+
+    let mut font = Vec::new();
+    font.extend(0x0001_0000u32.to_be_bytes());
+    push_u16(&mut font, &[tables.len() as u16, 64, 2, 48]);
+
+    let mut offset = 12 + 16 * tables.len();
+
+    for (tag, data) in &tables {
+        font.extend(*tag);
+        font.extend(0u32.to_be_bytes());
+        font.extend((offset as u32).to_be_bytes());
+        font.extend((data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+
+    for (_, data) in &tables {
+        font.extend(data);
+        font.resize(font.len().next_multiple_of(4), 0);
+    }
+
+    font
+}
+
+#[test]
+fn charsets_contain_their_printable_characters() {
+    assert!(Charset::Ascii.contains('A'));
+    assert!(!Charset::Ascii.contains('\n'));
+    assert!(!Charset::Ascii.contains('é'));
+
+    assert!(Charset::Latin1.contains('é'));
+    assert!(Charset::Latin1.contains('¤'));
+    assert!(!Charset::Latin1.contains('€'));
+
+    assert!(Charset::Latin9.contains('é'));
+    assert!(Charset::Latin9.contains('€'));
+    assert!(Charset::Latin9.contains('Œ'));
+    assert!(!Charset::Latin9.contains('¤'));
+    assert!(!Charset::Latin9.contains('\u{9f}'));
+
+    assert!(Charset::All.contains('↑'));
+}
+
+#[test]
+fn charsets_list_their_characters() {
+    let ascii = Charset::Ascii.chars().unwrap();
+    assert_eq!(ascii.len(), 95);
+    assert_eq!((ascii[0], ascii[94]), (' ', '~'));
+
+    assert_eq!(Charset::Latin1.chars().unwrap().len(), 191);
+
+    let latin9 = Charset::Latin9.chars().unwrap();
+    assert_eq!(latin9.len(), 191);
+    assert!(latin9.contains(&'€') && !latin9.contains(&'¤'));
+    assert!(latin9.is_sorted());
+
+    assert_eq!(Charset::All.chars(), None);
+}
+
+#[test]
+fn converts_bdf_fonts() {
+    let path = fixture("font.bdf", BDF.as_bytes());
+    let code = build("bdf", AssetBuilder::new().bdf_font("FONT", &path, Charset::All)).unwrap();
+
+    // The empty top row of 'A' is trimmed, glyphs are sorted by character in the atlas.
+    assert_eq!(
+        glyph(&code, 'A'),
+        Some("epd_display::text::bitmap_font::BitmapGlyph { character: '\\u{41}', x: 0, width: 5, height: 3, x_offset: 0, y_offset: 2, advance: 6 },"),
+    );
+    assert_eq!(
+        glyph(&code, '↑'),
+        Some("epd_display::text::bitmap_font::BitmapGlyph { character: '\\u{2191}', x: 5, width: 3, height: 2, x_offset: 3, y_offset: 4, advance: 10 },"),
+    );
+    assert!(code.contains("0x72, 0x8f, 0xf8,"));
+    assert!(code.ends_with("    ],\n    8,\n    6,\n);\n"));
+}
+
+#[test]
+fn filters_bdf_fonts_by_charset() {
+    let path = fixture("ascii.bdf", BDF.as_bytes());
+    let code = build("bdf_ascii", AssetBuilder::new().bdf_font("FONT", &path, Charset::Ascii)).unwrap();
+
+    assert!(glyph(&code, 'A').is_some());
+    assert_eq!(glyph(&code, '↑'), None);
+}
+
+#[test]
+fn rejects_bdf_fonts_without_bounding_box() {
+    let path = fixture("invalid.bdf", BDF.replace("FONTBOUNDINGBOX 9 8 0 -2\n", "").as_bytes());
+    let result = build("bdf_invalid", AssetBuilder::new().bdf_font("FONT", &path, Charset::All));
+
+    assert!(matches!(result, Err(AssetError::Font(_, e)) if e == "missing FONTBOUNDINGBOX"));
+}
+
+#[test]
+fn rasterizes_ttf_fonts() {
+    let path = fixture("square.ttf", &square_font());
+    let code = build("ttf", AssetBuilder::new().ttf_font("FONT", &path, 10.0, Charset::Ascii)).unwrap();
+
+    // At 10 pixels per em the square covers 5 x 5 whole pixels on the baseline.
+    assert_eq!(
+        glyph(&code, ' '),
+        Some("epd_display::text::bitmap_font::BitmapGlyph { character: '\\u{20}', x: 0, width: 0, height: 0, x_offset: 0, y_offset: 8, advance: 3 },"),
+    );
+    assert_eq!(
+        glyph(&code, 'A'),
+        Some("epd_display::text::bitmap_font::BitmapGlyph { character: '\\u{41}', x: 0, width: 5, height: 5, x_offset: 1, y_offset: 3, advance: 7 },"),
+    );
+    assert_eq!(glyph(&code, 'B'), None);
+    assert!(code.contains("0xf8, 0xf8, 0xf8, 0xf8, 0xf8,"));
+    assert!(code.ends_with("    ],\n    10,\n    8,\n);\n"));
+}
+
+#[test]
+fn rejects_ttf_fonts_without_charset() {
+    let path = fixture("all.ttf", &square_font());
+    let result = build("ttf_all", AssetBuilder::new().ttf_font("FONT", &path, 10.0, Charset::All));
+
+    assert!(matches!(result, Err(AssetError::Font(..))));
+}
+
+#[test]
+fn rejects_invalid_names() {
+    let path = fixture("name.bdf", BDF.as_bytes());
+    let result = build("name", AssetBuilder::new().bdf_font("font", &path, Charset::All));
+
+    assert!(matches!(result, Err(AssetError::InvalidName(name)) if name == "font"));
+}
@@ -0,0 +1,42 @@
+//! Draws a screen into the simulated display and saves it as PPM image:
+//!
+//! cargo run -p epd-display --example simulator --target x86_64-unknown-linux-gnu --features std -- screen.ppm
+
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::layout::leaf::Chart;
+use epd_display::layout::node::{Length, Node, Padding};
+use epd_display::simulator::{save_ppm, SimulatedDisplay};
+use epd_display::text::font_text_style::FontTextStyle;
+use epd_display::text::fonts::{FONT_10X20_PROPORTIONAL, FONT_8X13_PROPORTIONAL};
+use epd_display::widgets::chart::sparkline::Sparkline;
+
+fn main() -> std::io::Result<()> {
+    let path = std::env::args().nth(1).unwrap_or_else(|| "simulator.ppm".into());
+
+    let mut display = SimulatedDisplay::simulated();
+    display.set_orientation(DisplayOrientation::Landscape);
+    display.clear();
+
+    let values = [18.5, 19.0, 19.8, 21.2, 22.4, 23.9, 23.1, 21.7, 20.3, 19.6];
+    let title = [("Temperature", FontTextStyle::new(&FONT_10X20_PROPORTIONAL, ThreeColor::Black))];
+    let label = [("19.6 °C", FontTextStyle::new(&FONT_8X13_PROPORTIONAL, ThreeColor::Black))];
+    let chart: Chart = Sparkline::new(&values, Rectangle::zero()).with_threshold(23.0).with_stroke_width(2).into();
+
+    let screen = [
+        Node::text(&title, Alignment::Left).with_padding(Padding::all(5)),
+        Node::line(ThreeColor::Chromatic, 3),
+        Node::chart(chart).with_length(Length::Flex(1)).with_padding(Padding::all(4)),
+        Node::text(&label, Alignment::Right).with_padding(Padding::all(2)),
+    ];
+
+    let _ = Node::column(&screen).draw(display.bounding_box(), &mut display);
+
+    save_ppm(&display, &path)?;
+    println!("Saved {}", path);
+
+    Ok(())
+}
@@ -1,3 +1,7 @@
+/// Bitmap with one bit per pixel, the most significant bit is the leftmost pixel.
+///
+/// Every row starts at a new byte, so rows of widths that aren't a multiple of 8 end with
+/// unused bits. `BUFFER_SIZE` has to be `WIDTH.div_ceil(8) * HEIGHT`.
 pub struct BitmapBuffer<const WIDTH: usize, const HEIGHT: usize, const BUFFER_SIZE: usize> {
     pub buffer: [u8; BUFFER_SIZE],
 }
@@ -5,15 +9,25 @@ pub struct BitmapBuffer<const WIDTH: usize, const HEIGHT: usize, const BUFFER_SI
 impl<const WIDTH: usize, const HEIGHT: usize, const BUFFER_SIZE: usize>
     BitmapBuffer<WIDTH, HEIGHT, BUFFER_SIZE>
 {
-    pub fn new() -> Self {
+    /// Number of bytes per row.
+    pub const STRIDE: usize = WIDTH.div_ceil(8);
+
+    pub const fn new() -> Self {
+        Self::filled(0x00)
+    }
+
+    /// Bitmap with every byte set to the value.
+    pub const fn filled(value: u8) -> Self {
+        const { assert!(BUFFER_SIZE == Self::STRIDE * HEIGHT, "BUFFER_SIZE doesn't match the size of the bitmap") };
+
         BitmapBuffer {
-            buffer: [0x00; BUFFER_SIZE],
+            buffer: [value; BUFFER_SIZE],
         }
     }
 
     pub fn set_pixel(&mut self, x: usize, y: usize) {
         if x < WIDTH && y < HEIGHT {
-            let byte_index = byte_index(WIDTH, x, y);
+            let byte_index = byte_index(Self::STRIDE, x, y);
             let bit_index = 7 - x % 8;
             self.buffer[byte_index] |= 1 << bit_index;
         }
@@ -21,12 +35,40 @@ impl<const WIDTH: usize, const HEIGHT: usize, const BUFFER_SIZE: usize>
 
     pub fn clear_pixel(&mut self, x: usize, y: usize) {
         if x < WIDTH && y < HEIGHT {
-            let byte_index = byte_index(WIDTH, x, y);
+            let byte_index = byte_index(Self::STRIDE, x, y);
             let bit_index = 7 - x % 8;
             self.buffer[byte_index] &= !(1 << bit_index);
         }
     }
 
+    pub fn get_pixel(&self, x: usize, y: usize) -> bool {
+        if x < WIDTH && y < HEIGHT {
+            let byte_index = byte_index(Self::STRIDE, x, y);
+            let bit_index = 7 - x % 8;
+            self.buffer[byte_index] & (1 << bit_index) != 0
+        } else {
+            false
+        }
+    }
+
+    /// Copies rows of whole bytes to the area at `x`, a multiple of 8, and `y`. Each row of the data
+    /// is `width.div_ceil(8)` bytes long, the parts outside the bitmap are dropped.
+    pub fn copy_area(&mut self, x: usize, y: usize, width: usize, data: &[u8]) {
+        let row_len = width.div_ceil(8);
+
+        if row_len == 0 || x >= WIDTH {
+            return;
+        }
+
+        let len = row_len.min(Self::STRIDE - x / 8);
+
+        for (row, y) in data.chunks(row_len).zip(y..HEIGHT) {
+            let index = byte_index(Self::STRIDE, x, y);
+            let len = len.min(row.len());
+            self.buffer[index..index + len].copy_from_slice(&row[..len]);
+        }
+    }
+
     pub fn fill(&mut self, value: u8) {
         for i in 0..BUFFER_SIZE {
             self.buffer[i] = value;
@@ -34,6 +76,14 @@ impl<const WIDTH: usize, const HEIGHT: usize, const BUFFER_SIZE: usize>
     }
 }
 
-const fn byte_index(width: usize, x: usize, y: usize) -> usize {
-    (y * width + x) / 8
+impl<const WIDTH: usize, const HEIGHT: usize, const BUFFER_SIZE: usize> Default
+    for BitmapBuffer<WIDTH, HEIGHT, BUFFER_SIZE>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+const fn byte_index(stride: usize, x: usize, y: usize) -> usize {
+    y * stride + x / 8
 }
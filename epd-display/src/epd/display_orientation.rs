@@ -1,3 +1,4 @@
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum DisplayOrientation {
     Portrait,
     Landscape,
@@ -1,4 +1,5 @@
-use embassy_time::{Duration, Timer};
+use embassy_time::{Duration, Instant, Timer};
+use embedded_graphics::image::GetPixel;
 use embedded_graphics::pixelcolor::raw::RawU2;
 use embedded_graphics::Pixel;
 use embedded_graphics::primitives::Rectangle;
@@ -7,11 +8,17 @@ use embedded_graphics::prelude::*;
 use embedded_hal::digital::{InputPin, OutputPin};
 use embedded_hal_async::spi::SpiDevice;
 use super::display_orientation::DisplayOrientation;
+use super::maintenance::{Maintenance, MaintenancePolicy};
 use super::three_color::ThreeColor;
 use super::epd_spi::EpdSpi;
 
-const WIDTH: usize = 152;
-const HEIGHT: usize = 296;
+/// Size of the panel in its native portrait orientation, which is also the layout of the buffers.
+pub const WIDTH: usize = 152;
+pub const HEIGHT: usize = 296;
+const BUFFER_LEN: usize = WIDTH.div_ceil(8) * HEIGHT;
+
+/// Buffer of one color plane of the panel.
+pub type PlaneBuffer = bitmap_buffer_type!(WIDTH, HEIGHT);
 
 pub struct Epd2in66b<SPI, DC, RST, BUSY>
 where
@@ -22,8 +29,9 @@ where
 {
     epd: EpdSpi<SPI, DC, RST, BUSY>,
     orientation: DisplayOrientation,
-    bw_buffer: bitmap_buffer_type!(WIDTH, HEIGHT),
-    chromatic_buffer: bitmap_buffer_type!(WIDTH, HEIGHT),
+    maintenance: Maintenance,
+    bw_buffer: PlaneBuffer,
+    chromatic_buffer: PlaneBuffer,
 }
 
 // public API
@@ -41,7 +49,9 @@ where
         let chromatic_buffer = bitmap_buffer!(WIDTH, HEIGHT);
         let orientation = DisplayOrientation::Landscape;
 
-        let mut epd = Epd2in66b { epd, orientation, bw_buffer, chromatic_buffer };
+        let maintenance = Maintenance::new(MaintenancePolicy::NEVER);
+
+        let mut epd = Epd2in66b { epd, orientation, maintenance, bw_buffer, chromatic_buffer };
 
         epd.clear();
 
@@ -66,11 +76,43 @@ where
         }
     }
 
+    pub fn orientation(&self) -> DisplayOrientation {
+        self.orientation
+    }
+
+    pub fn set_orientation(&mut self, orientation: DisplayOrientation) {
+        self.orientation = orientation;
+    }
+
+    /// Converts a point in the coordinates of the current orientation to the coordinates of the panel.
+    pub fn convert_point(&self, point: Point) -> Point {
+        match self.orientation {
+            DisplayOrientation::Portrait => Point::new(point.x, point.y),
+            DisplayOrientation::LandscapeFlipped => Point::new(WIDTH as i32 - 1 - point.y, point.x),
+            DisplayOrientation::PortraitFlipped => Point::new(WIDTH as i32 - 1 - point.x, HEIGHT as i32 - 1 - point.y),
+            DisplayOrientation::Landscape => Point::new(point.y, HEIGHT as i32 - 1 - point.x),
+        }
+    }
+
+    pub fn maintenance(&self) -> &Maintenance {
+        &self.maintenance
+    }
+
+    pub fn set_maintenance_policy(&mut self, policy: MaintenancePolicy) {
+        self.maintenance.set_policy(policy);
+    }
+
     pub fn clear(&mut self) {
         self.bw_buffer.fill(0xff);
         self.chromatic_buffer.fill(0x0);
     }
 
+    /// Black and white and chromatic buffer for writing packed planes directly, independent of the
+    /// orientation. A set bit is white in the black and white plane and red in the chromatic one.
+    pub fn planes_mut(&mut self) -> (&mut PlaneBuffer, &mut PlaneBuffer) {
+        (&mut self.bw_buffer, &mut self.chromatic_buffer)
+    }
+
     pub async fn init(&mut self) {
         self.epd.hw_reset().await;
         self.sw_reset().await;
@@ -80,12 +122,32 @@ where
         self.set_window(0, WIDTH - 1, 0, HEIGHT - 1).await;
     }
 
+    /// Shows the buffers on the display, after a cleaning cycle if the maintenance policy asks for one.
     pub async fn refresh(&mut self) {
+        if self.maintenance.is_due(Instant::now()) {
+            self.clean().await;
+        }
+
         self.set_cursor(0, 0).await;
         self.epd.cmd_data(ThreeColorEpdCommand::WriteBlackWhiteRAM as u8, &self.bw_buffer.buffer).await;
         self.set_cursor(0, 0).await;
         self.epd.cmd_data(ThreeColorEpdCommand::WriteChromaticRAM as u8, &self.chromatic_buffer.buffer).await;
         self.activate().await;
+        self.maintenance.record_refresh();
+    }
+
+    /// Drives all pixels to black and then to white against ghosting. The buffers are kept, so
+    /// the image is restored with the next refresh.
+    pub async fn clean(&mut self) {
+        for bw in [0x00, 0xff] {
+            self.set_cursor(0, 0).await;
+            self.epd.cmd_repeat(ThreeColorEpdCommand::WriteBlackWhiteRAM as u8, bw, BUFFER_LEN).await;
+            self.set_cursor(0, 0).await;
+            self.epd.cmd_repeat(ThreeColorEpdCommand::WriteChromaticRAM as u8, 0x00, BUFFER_LEN).await;
+            self.activate().await;
+        }
+
+        self.maintenance.record_cleaning(Instant::now());
     }
 
     pub async fn sleep(&mut self) {
@@ -105,7 +167,7 @@ where
     BUSY: InputPin,
 {
     fn bounding_box(&self) -> Rectangle {
-       Rectangle::new(Point::zero(), Size::new(WIDTH as u32, HEIGHT as u32))
+        Rectangle::new(Point::zero(), Size::new(self.width() as u32, self.height() as u32))
     }
 }
 
@@ -152,24 +214,43 @@ where
     }
 }
 
-// private API
-
-impl<SPI, DC, RST, BUSY> Epd2in66b<SPI, DC, RST, BUSY>
+impl<SPI, DC, RST, BUSY> GetPixel for Epd2in66b<SPI, DC, RST, BUSY>
 where
     SPI: SpiDevice,
     DC: OutputPin,
     RST: OutputPin,
     BUSY: InputPin,
 {
-    fn convert_point(&self, point: Point) -> Point {
-        match self.orientation {
-            DisplayOrientation::Portrait => Point::new(point.x, point.y),
-            DisplayOrientation::LandscapeFlipped => Point::new(WIDTH as i32 - 1 - point.y, point.x),
-            DisplayOrientation::PortraitFlipped => Point::new(WIDTH as i32 - 1 - point.x, HEIGHT as i32 - 1 - point.y),
-            DisplayOrientation::Landscape => Point::new(point.y, HEIGHT as i32 - 1 - point.x),
+    type Color = ThreeColor;
+
+    fn pixel(&self, point: Point) -> Option<Self::Color> {
+        let coord = self.convert_point(point);
+
+        if coord.x < 0 || coord.x >= WIDTH as i32 || coord.y < 0 || coord.y >= HEIGHT as i32 {
+            return None;
+        }
+
+        let (x, y) = (coord.x as usize, coord.y as usize);
+
+        if self.chromatic_buffer.get_pixel(x, y) {
+            Some(ThreeColor::Chromatic)
+        } else if self.bw_buffer.get_pixel(x, y) {
+            Some(ThreeColor::White)
+        } else {
+            Some(ThreeColor::Black)
         }
     }
+}
 
+// private API
+
+impl<SPI, DC, RST, BUSY> Epd2in66b<SPI, DC, RST, BUSY>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+    BUSY: InputPin,
+{
     async fn sw_reset(&mut self) {
         self.cmd(ThreeColorEpdCommand::Reset).await;
 
@@ -49,6 +49,21 @@ where
         let _ = self.spi.write(&[cmd]).await;
     }
 
+    /// Sends a command followed by `count` copies of `byte`.
+    pub async fn cmd_repeat(&mut self, cmd: u8, byte: u8, count: usize) {
+        let chunk = [byte; 64];
+        let mut remaining = count;
+
+        self.cmd(cmd).await;
+        let _ = self.dc.set_high();
+
+        while remaining > 0 {
+            let n = remaining.min(chunk.len());
+            let _ = self.spi.write(&chunk[..n]).await;
+            remaining -= n;
+        }
+    }
+
     pub async fn cmd_data(&mut self, cmd: u8, data: &[u8]) {
         self.cmd(cmd).await;
 
@@ -0,0 +1,82 @@
+use embassy_time::{Duration, Instant};
+
+/// When the display is cleaned against ghosting, by driving all pixels black and then white.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MaintenancePolicy {
+    /// Clean after this many refreshes since the last cleaning.
+    pub max_refreshes: Option<u32>,
+    /// Clean when the last cleaning, or startup, is longer ago.
+    pub max_interval: Option<Duration>,
+}
+
+impl MaintenancePolicy {
+    pub const NEVER: MaintenancePolicy = MaintenancePolicy { max_refreshes: None, max_interval: None };
+    pub const DAILY: MaintenancePolicy = MaintenancePolicy { max_refreshes: None, max_interval: Some(Duration::from_secs(24 * 60 * 60)) };
+
+    pub const fn after_refreshes(refreshes: u32) -> Self {
+        MaintenancePolicy { max_refreshes: Some(refreshes), max_interval: None }
+    }
+
+    pub const fn or_after(mut self, interval: Duration) -> Self {
+        self.max_interval = Some(interval);
+        self
+    }
+}
+
+impl Default for MaintenancePolicy {
+    fn default() -> Self {
+        MaintenancePolicy::NEVER
+    }
+}
+
+/// Counts refreshes and tracks when the next cleaning is due.
+pub struct Maintenance {
+    policy: MaintenancePolicy,
+    refreshes: u32,
+    last_cleaning: Instant,
+    cleaned: bool,
+}
+
+impl Maintenance {
+    pub const fn new(policy: MaintenancePolicy) -> Self {
+        Maintenance { policy, refreshes: 0, last_cleaning: Instant::from_ticks(0), cleaned: false }
+    }
+
+    pub fn policy(&self) -> MaintenancePolicy {
+        self.policy
+    }
+
+    pub fn set_policy(&mut self, policy: MaintenancePolicy) {
+        self.policy = policy;
+    }
+
+    /// Refreshes since the last cleaning.
+    pub fn refreshes(&self) -> u32 {
+        self.refreshes
+    }
+
+    pub fn is_due(&self, now: Instant) -> bool {
+        let by_count = self.policy.max_refreshes.is_some_and(|max| self.refreshes >= max);
+        let by_time = self.next_due().is_some_and(|due| now >= due);
+
+        by_count || by_time
+    }
+
+    /// Time at which a cleaning becomes due even without further refreshes.
+    pub fn next_due(&self) -> Option<Instant> {
+        self.policy.max_interval.map(|interval| self.last_cleaning + interval)
+    }
+
+    /// Counts a refresh, except the one that shows the image again after a cleaning.
+    pub fn record_refresh(&mut self) {
+        if !core::mem::take(&mut self.cleaned) {
+            self.refreshes = self.refreshes.saturating_add(1);
+        }
+    }
+
+    pub fn record_cleaning(&mut self, now: Instant) {
+        self.refreshes = 0;
+        self.last_cleaning = now;
+        self.cleaned = true;
+    }
+}
@@ -1,18 +1,19 @@
 macro_rules! bitmap_buffer_type {
     ($width:expr, $height:expr) => {
-        crate::epd::bitmap_buffer::BitmapBuffer<$width, $height, { ($width + 7) / 8 * $height }>
+        crate::epd::bitmap_buffer::BitmapBuffer<$width, $height, { $width.div_ceil(8) * $height }>
     };
 }
 
 macro_rules! bitmap_buffer {
     ($width:expr, $height:expr) => {
-        crate::epd::bitmap_buffer::BitmapBuffer::<$width, $height, { ($width + 7) / 8 * $height }>::new()
+        crate::epd::bitmap_buffer::BitmapBuffer::<$width, $height, { $width.div_ceil(8) * $height }>::new()
     };
 }
 
+pub mod bitmap_buffer;
 pub mod display_orientation;
 pub mod epd_2in66b;
+pub mod maintenance;
 pub mod three_color;
 
-mod bitmap_buffer;
 mod epd_spi;
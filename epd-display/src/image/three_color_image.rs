@@ -0,0 +1,72 @@
+use embedded_graphics::image::ImageDrawable;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+
+use crate::epd::three_color::ThreeColor;
+
+/// Three color image stored as 1 bit per pixel planes in the layout of the display buffers.
+///
+/// Rows are packed MSB first and padded to whole bytes. A set bit in the black/white plane is
+/// white, a set bit in the chromatic plane is chromatic. Pixels with a cleared bit in the
+/// optional mask plane are transparent.
+pub struct ThreeColorImage<'a> {
+    size: Size,
+    bw_plane: &'a [u8],
+    chromatic_plane: &'a [u8],
+    mask_plane: Option<&'a [u8]>,
+}
+
+impl<'a> ThreeColorImage<'a> {
+    pub const fn new(width: u32, height: u32, bw_plane: &'a [u8], chromatic_plane: &'a [u8], mask_plane: Option<&'a [u8]>) -> Self {
+        ThreeColorImage { size: Size::new(width, height), bw_plane, chromatic_plane, mask_plane }
+    }
+
+    pub fn color(&self, point: Point) -> Option<ThreeColor> {
+        if point.x < 0 || point.y < 0 || point.x as u32 >= self.size.width || point.y as u32 >= self.size.height {
+            return None;
+        }
+
+        let stride = self.size.width.div_ceil(8) as usize;
+        let (x, y) = (point.x as usize, point.y as usize);
+        let index = y * stride + x / 8;
+        let bit = 0x80 >> (x % 8);
+        let is_set = |plane: &[u8]| plane.get(index).is_some_and(|byte| byte & bit != 0);
+
+        if self.mask_plane.is_some_and(|mask| !is_set(mask)) {
+            None
+        } else if is_set(self.chromatic_plane) {
+            Some(ThreeColor::Chromatic)
+        } else if is_set(self.bw_plane) {
+            Some(ThreeColor::White)
+        } else {
+            Some(ThreeColor::Black)
+        }
+    }
+}
+
+impl OriginDimensions for ThreeColorImage<'_> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl ImageDrawable for ThreeColorImage<'_> {
+    type Color = ThreeColor;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.draw_sub_image(target, &self.bounding_box())
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let pixels = area.points()
+            .filter_map(|point| Some(Pixel(point - area.top_left, self.color(point)?)));
+
+        target.draw_iter(pixels)
+    }
+}
@@ -0,0 +1,115 @@
+use embedded_graphics::image::Image;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+
+use crate::epd::three_color::ThreeColor;
+use crate::image::three_color_image::ThreeColorImage;
+use crate::text::font_text_style::FontTextStyle;
+use crate::text::text_layout::{text_width, LineBreaker, TextLayout};
+use crate::widgets::chart::bar_chart::BarChart;
+use crate::widgets::chart::gauge::Gauge;
+use crate::widgets::chart::sparkline::Sparkline;
+use crate::widgets::qr_code::QrCode;
+use super::node::Axis;
+
+pub type Paragraph<'a> = (&'a str, FontTextStyle<'a, ThreeColor>);
+
+/// Content of a layout node without children.
+#[derive(Copy, Clone)]
+pub enum Leaf<'a> {
+    Empty,
+    /// Word-wrapped paragraphs starting at the top of the bounds.
+    Text(&'a [Paragraph<'a>], Alignment),
+    /// Line across the longer side of the bounds, centered on the shorter side.
+    Line(ThreeColor, u32),
+    /// Image centered in the bounds.
+    Image(&'a ThreeColorImage<'a>),
+    /// QR code scaled to fit and centered in the bounds.
+    QrCode(&'a QrCode),
+    /// Chart resized to the bounds.
+    Chart(Chart<'a>),
+}
+
+#[derive(Copy, Clone)]
+pub enum Chart<'a> {
+    Sparkline(Sparkline<'a>),
+    Bars(BarChart<'a>),
+    Gauge(Gauge),
+}
+
+impl<'a> From<Sparkline<'a>> for Chart<'a> {
+    fn from(sparkline: Sparkline<'a>) -> Self {
+        Chart::Sparkline(sparkline)
+    }
+}
+
+impl<'a> From<BarChart<'a>> for Chart<'a> {
+    fn from(bar_chart: BarChart<'a>) -> Self {
+        Chart::Bars(bar_chart)
+    }
+}
+
+impl From<Gauge> for Chart<'_> {
+    fn from(gauge: Gauge) -> Self {
+        Chart::Gauge(gauge)
+    }
+}
+
+impl Leaf<'_> {
+    /// Natural length along the axis when the leaf is `cross` pixels wide on the other axis.
+    pub(crate) fn content_length(&self, axis: Axis, cross: u32, spacing: u32) -> u32 {
+        match *self {
+            Leaf::Empty => 0,
+            Leaf::Text(paragraphs, alignment) => match axis {
+                Axis::Horizontal => paragraphs.iter()
+                    .flat_map(|(text, style)| LineBreaker::new(text, u32::MAX, |s| text_width(style, s)).map(|line| text_width(style, line)))
+                    .max()
+                    .unwrap_or(0),
+                Axis::Vertical => {
+                    let bounds = Rectangle::new(Point::zero(), Size::new(cross, i32::MAX as u32));
+                    TextLayout::new(bounds, alignment).with_line_spacing(spacing).measure(paragraphs.iter().copied())
+                }
+            },
+            Leaf::Line(_, stroke_width) => stroke_width,
+            Leaf::Image(image) => axis.main(image.size()),
+            Leaf::QrCode(qr_code) => qr_code.clone().scale_to_fit(Size::new_equal(cross)).map_or(0, |qr_code| qr_code.bounding_box().size.width),
+            Leaf::Chart(Chart::Gauge(_)) => cross,
+            Leaf::Chart(_) => 0,
+        }
+    }
+
+    pub(crate) fn draw<D>(&self, bounds: Rectangle, spacing: u32, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        match *self {
+            Leaf::Empty => Ok(()),
+            Leaf::Text(paragraphs, alignment) => TextLayout::new(bounds, alignment)
+                .with_line_spacing(spacing)
+                .draw(paragraphs.iter().copied(), target)
+                .map(|_| ()),
+            Leaf::Line(color, stroke_width) => {
+                let Size { width, height } = bounds.size;
+                let size = if width >= height { Size::new(width, stroke_width) } else { Size::new(stroke_width, height) };
+
+                target.fill_solid(&Rectangle::with_center(bounds.center(), size), color)
+            }
+            Leaf::Image(image) => {
+                let top_left = Rectangle::with_center(bounds.center(), image.size()).top_left;
+                Image::new(image, top_left).draw(target)
+            }
+            Leaf::QrCode(qr_code) => {
+                // Codes that don't fit are left out rather than cut off.
+                let Some(qr_code) = qr_code.clone().scale_to_fit(bounds.size) else { return Ok(()) };
+                let area = qr_code.bounding_box();
+                let top_left = Rectangle::with_center(bounds.center(), area.size).top_left;
+
+                qr_code.translate(top_left - area.top_left).draw(target)
+            }
+            Leaf::Chart(Chart::Sparkline(sparkline)) => sparkline.with_bounds(bounds).draw(target),
+            Leaf::Chart(Chart::Bars(bar_chart)) => bar_chart.with_bounds(bounds).draw(target),
+            Leaf::Chart(Chart::Gauge(gauge)) => gauge.with_bounds(bounds).draw(target),
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod leaf;
+pub mod node;
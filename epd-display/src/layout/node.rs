@@ -0,0 +1,315 @@
+use embedded_graphics::draw_target::DrawTargetExt;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+
+use crate::epd::three_color::ThreeColor;
+use crate::image::three_color_image::ThreeColorImage;
+use crate::widgets::qr_code::QrCode;
+use super::leaf::{Chart, Leaf, Paragraph};
+
+/// Length of a node along the main axis of its parent.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Length {
+    Fixed(u32),
+    /// Share of the space left by the other children, relative to the weights of all flex children.
+    Flex(u32),
+    /// Natural length of the content, e.g. the height of wrapped text.
+    Content,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Padding {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+impl Padding {
+    pub const fn new(top: u32, right: u32, bottom: u32, left: u32) -> Self {
+        Padding { top, right, bottom, left }
+    }
+
+    pub const fn all(padding: u32) -> Self {
+        Padding::new(padding, padding, padding, padding)
+    }
+
+    pub const fn symmetric(vertical: u32, horizontal: u32) -> Self {
+        Padding::new(vertical, horizontal, vertical, horizontal)
+    }
+
+    fn along(&self, axis: Axis) -> u32 {
+        match axis {
+            Axis::Horizontal => self.left.saturating_add(self.right),
+            Axis::Vertical => self.top.saturating_add(self.bottom),
+        }
+    }
+
+    fn shrink(&self, bounds: Rectangle) -> Rectangle {
+        let width = bounds.size.width.saturating_sub(self.along(Axis::Horizontal));
+        let height = bounds.size.height.saturating_sub(self.along(Axis::Vertical));
+        let top_left = Point::new(bounds.top_left.x.saturating_add_unsigned(self.left), bounds.top_left.y.saturating_add_unsigned(self.top));
+
+        Rectangle::new(top_left, Size::new(width, height))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub(crate) enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+impl Axis {
+    pub(crate) fn main(self, size: Size) -> u32 {
+        match self {
+            Axis::Horizontal => size.width,
+            Axis::Vertical => size.height,
+        }
+    }
+
+    fn cross(self, size: Size) -> u32 {
+        match self {
+            Axis::Horizontal => size.height,
+            Axis::Vertical => size.width,
+        }
+    }
+
+    fn other(self) -> Axis {
+        match self {
+            Axis::Horizontal => Axis::Vertical,
+            Axis::Vertical => Axis::Horizontal,
+        }
+    }
+
+    fn child_bounds(self, bounds: Rectangle, offset: u32, length: u32) -> Rectangle {
+        match self {
+            Axis::Horizontal => {
+                let top_left = Point::new(bounds.top_left.x.saturating_add_unsigned(offset), bounds.top_left.y);
+                Rectangle::new(top_left, Size::new(length, bounds.size.height))
+            }
+            Axis::Vertical => {
+                let top_left = Point::new(bounds.top_left.x, bounds.top_left.y.saturating_add_unsigned(offset));
+                Rectangle::new(top_left, Size::new(bounds.size.width, length))
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Content<'a> {
+    Container(Axis, &'a [Node<'a>]),
+    Leaf(Leaf<'a>),
+}
+
+/// Node of a layout tree.
+///
+/// Rows and columns divide their bounds among their children along the main axis, children
+/// always fill the cross axis. Children with a fixed or content length are sized first, the
+/// remaining space is divided among the flex children. Leaves are clipped to their bounds.
+///
+/// ```ignore
+/// let title = [("Shelf 12", FontTextStyle::new(&FONT_10X20, ThreeColor::Black))];
+/// let screen = [
+///     Node::text(&title, Alignment::Left).with_padding(Padding::all(2)),
+///     Node::line(ThreeColor::Chromatic, 3),
+///     Node::chart(Sparkline::new(&values, Rectangle::zero())).with_length(Length::Flex(1)),
+/// ];
+/// Node::column(&screen).draw(display.bounding_box(), &mut display)?;
+/// ```
+#[derive(Copy, Clone)]
+pub struct Node<'a> {
+    content: Content<'a>,
+    length: Length,
+    padding: Padding,
+    spacing: u32,
+}
+
+impl<'a> Node<'a> {
+    pub const fn row(children: &'a [Node<'a>]) -> Self {
+        Node::new(Content::Container(Axis::Horizontal, children), Length::Flex(1))
+    }
+
+    pub const fn column(children: &'a [Node<'a>]) -> Self {
+        Node::new(Content::Container(Axis::Vertical, children), Length::Flex(1))
+    }
+
+    pub const fn leaf(leaf: Leaf<'a>) -> Self {
+        Node::new(Content::Leaf(leaf), Length::Content)
+    }
+
+    /// Empty node taking up a share of the remaining space.
+    pub const fn spacer() -> Self {
+        Node::new(Content::Leaf(Leaf::Empty), Length::Flex(1))
+    }
+
+    pub const fn text(paragraphs: &'a [Paragraph<'a>], alignment: Alignment) -> Self {
+        Node::leaf(Leaf::Text(paragraphs, alignment))
+    }
+
+    pub const fn line(color: ThreeColor, stroke_width: u32) -> Self {
+        Node::leaf(Leaf::Line(color, stroke_width))
+    }
+
+    pub const fn image(image: &'a ThreeColorImage<'a>) -> Self {
+        Node::leaf(Leaf::Image(image))
+    }
+
+    pub const fn qr_code(qr_code: &'a QrCode) -> Self {
+        Node::leaf(Leaf::QrCode(qr_code))
+    }
+
+    /// Chart leaf, the bounds of the chart are replaced by the bounds of the node.
+    pub fn chart(chart: impl Into<Chart<'a>>) -> Self {
+        Node::leaf(Leaf::Chart(chart.into()))
+    }
+
+    const fn new(content: Content<'a>, length: Length) -> Self {
+        Node { content, length, padding: Padding::all(0), spacing: 0 }
+    }
+
+    pub const fn with_length(mut self, length: Length) -> Self {
+        self.length = length;
+        self
+    }
+
+    pub const fn with_padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Space between the children of a container or between the lines of a text.
+    pub const fn with_spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Lays out the tree inside the bounds and draws all leaves.
+    pub fn draw<D>(&self, bounds: Rectangle, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        let inner = self.padding.shrink(bounds);
+
+        match self.content {
+            // Padding larger than the bounds leaves nothing to draw.
+            Content::Leaf(_) if inner.is_zero_sized() => Ok(()),
+            Content::Leaf(leaf) => leaf.draw(inner, self.spacing, &mut target.clipped(&inner)),
+            Content::Container(axis, children) => {
+                let main = axis.main(inner.size);
+                let cross = axis.cross(inner.size);
+
+                self.for_each_child(children, axis, main, cross, |child, offset, length| {
+                    child.draw(axis.child_bounds(inner, offset, length), target)
+                })
+            }
+        }
+    }
+
+    /// Returns false if paddings, spacings and fixed lengths anywhere in the tree need more space
+    /// than the bounds provide. Content that doesn't fit is clipped and doesn't count.
+    pub fn fits(&self, bounds: Rectangle) -> bool {
+        let Size { width, height } = bounds.size;
+
+        if self.padding.along(Axis::Horizontal) > width || self.padding.along(Axis::Vertical) > height {
+            return false;
+        }
+
+        let Content::Container(axis, children) = self.content else { return true };
+        let inner = self.padding.shrink(bounds);
+        let main = axis.main(inner.size);
+        let fixed = children.iter()
+            .map(|child| match child.length {
+                Length::Fixed(length) => length,
+                _ => 0,
+            })
+            .fold(self.spacing.saturating_mul(children.len().saturating_sub(1) as u32), u32::saturating_add);
+
+        let mut fits = fixed <= main;
+        let _ = self.for_each_child::<()>(children, axis, main, axis.cross(inner.size), |child, offset, length| {
+            fits = fits && child.fits(axis.child_bounds(inner, offset, length));
+            Ok(())
+        });
+
+        fits
+    }
+
+    /// Length of the node along the axis when the node is `cross` pixels wide on the other axis.
+    fn content_length(&self, axis: Axis, cross: u32) -> u32 {
+        let inner_cross = cross.saturating_sub(self.padding.along(axis.other()));
+        let padding = self.padding.along(axis);
+
+        let length = match self.content {
+            Content::Leaf(leaf) => leaf.content_length(axis, inner_cross, self.spacing),
+            Content::Container(container_axis, children) if container_axis == axis => {
+                let spacing = self.spacing.saturating_mul(children.len().saturating_sub(1) as u32);
+                children.iter().map(|child| child.natural_length(axis, inner_cross)).fold(spacing, u32::saturating_add)
+            }
+            Content::Container(container_axis, children) => {
+                // The length is not known yet, so children are measured without a limit.
+                let mut length = 0;
+                let _ = self.for_each_child::<()>(children, container_axis, inner_cross, u32::MAX, |child, _, child_length| {
+                    length = length.max(child.content_length(axis, child_length));
+                    Ok(())
+                });
+                length
+            }
+        };
+
+        length.saturating_add(padding)
+    }
+
+    // Length along the main axis of the parent, ignoring the flex share.
+    fn natural_length(&self, axis: Axis, cross: u32) -> u32 {
+        match self.length {
+            Length::Fixed(length) => length,
+            Length::Flex(_) | Length::Content => self.content_length(axis, cross),
+        }
+    }
+
+    fn for_each_child<E>(
+        &self,
+        children: &[Node<'_>],
+        axis: Axis,
+        main: u32,
+        cross: u32,
+        mut f: impl FnMut(&Node<'_>, u32, u32) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let base_length = |child: &Node<'_>| match child.length {
+            Length::Fixed(length) => length,
+            Length::Content => child.content_length(axis, cross),
+            Length::Flex(_) => 0,
+        };
+        let weight = |child: &Node<'_>| match child.length {
+            Length::Flex(weight) => weight as u64,
+            _ => 0,
+        };
+
+        let spacing = self.spacing.saturating_mul(children.len().saturating_sub(1) as u32);
+        let total_base = children.iter().map(base_length).fold(spacing, u32::saturating_add);
+        // Weights are summed in 64 bits, the shares multiply them with the space in 128 bits.
+        let total_weight: u64 = children.iter().map(weight).sum();
+        let flex_space = main.saturating_sub(total_base) as u128;
+
+        let mut offset = 0;
+        let mut weight_before = 0;
+
+        for child in children {
+            let flex = if total_weight > 0 {
+                let share = |weight: u64| (flex_space * weight as u128 / total_weight as u128) as u32;
+                let length = share(weight_before + weight(child)) - share(weight_before);
+                weight_before += weight(child);
+                length
+            } else {
+                0
+            };
+
+            let length = base_length(child).saturating_add(flex).min(main.saturating_sub(offset));
+            f(child, offset, length)?;
+            offset = offset.saturating_add(length).saturating_add(self.spacing).min(main);
+        }
+
+        Ok(())
+    }
+}
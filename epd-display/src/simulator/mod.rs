@@ -0,0 +1,96 @@
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use embedded_hal::digital::{ErrorType as PinErrorType, InputPin, OutputPin};
+use embedded_hal_async::spi::{ErrorType as SpiErrorType, Operation, SpiDevice};
+
+use crate::epd::epd_2in66b::Epd2in66b;
+use crate::epd::three_color::ThreeColor;
+
+pub mod ssd16xx;
+pub mod time;
+
+/// Display driver without hardware, drawings go to the buffers and can be saved as an image.
+pub type SimulatedDisplay = Epd2in66b<NoSpi, NoPin, NoPin, NoPin>;
+
+impl SimulatedDisplay {
+    pub fn simulated() -> Self {
+        Epd2in66b::new(NoSpi, NoPin, NoPin, NoPin)
+    }
+}
+
+/// SPI device that discards all data.
+pub struct NoSpi;
+
+impl SpiErrorType for NoSpi {
+    type Error = Infallible;
+}
+
+impl SpiDevice for NoSpi {
+    async fn transaction(&mut self, _operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Output pin without effect, and input pin that is never busy.
+pub struct NoPin;
+
+impl PinErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+impl InputPin for NoPin {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(false)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        Ok(true)
+    }
+}
+
+/// Color of the pixels in saved images.
+pub fn rgb(color: ThreeColor) -> [u8; 3] {
+    match color {
+        ThreeColor::Black => [0x00, 0x00, 0x00],
+        ThreeColor::White => [0xff, 0xff, 0xff],
+        ThreeColor::Chromatic => [0xd0, 0x10, 0x10],
+    }
+}
+
+/// Writes the display content as binary PPM image in the orientation of the display.
+pub fn write_ppm<D, W>(display: &D, mut writer: W) -> io::Result<()>
+where
+    D: GetPixel<Color = ThreeColor> + Dimensions,
+    W: Write,
+{
+    let area = display.bounding_box();
+    write!(writer, "P6\n{} {}\n255\n", area.size.width, area.size.height)?;
+
+    for point in area.points() {
+        writer.write_all(&rgb(display.pixel(point).unwrap_or(ThreeColor::White)))?;
+    }
+
+    writer.flush()
+}
+
+pub fn save_ppm<D>(display: &D, path: impl AsRef<Path>) -> io::Result<()>
+where
+    D: GetPixel<Color = ThreeColor> + Dimensions,
+{
+    write_ppm(display, BufWriter::new(File::create(path)?))
+}
@@ -0,0 +1,428 @@
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::rc::Rc;
+
+use embassy_time::{Duration, Instant};
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use embedded_hal::digital::{ErrorType as PinErrorType, InputPin, OutputPin};
+use embedded_hal_async::spi::{ErrorType as SpiErrorType, Operation, SpiDevice};
+
+use crate::epd::epd_2in66b::Epd2in66b;
+use crate::epd::three_color::ThreeColor;
+
+const DEEP_SLEEP_MODE: u8 = 0x10;
+const DATA_ENTRY_MODE: u8 = 0x11;
+const SW_RESET: u8 = 0x12;
+const MASTER_ACTIVATION: u8 = 0x20;
+const WRITE_BLACK_WHITE_RAM: u8 = 0x24;
+const WRITE_CHROMATIC_RAM: u8 = 0x26;
+const SET_X_ADDRESS_RANGE: u8 = 0x44;
+const SET_Y_ADDRESS_RANGE: u8 = 0x45;
+const SET_X_ADDRESS_COUNTER: u8 = 0x4e;
+const SET_Y_ADDRESS_COUNTER: u8 = 0x4f;
+
+/// Display driver connected to an emulated controller.
+pub type EmulatedDisplay = Epd2in66b<Ssd16xxSpi, DcPin, RstPin, BusyPin>;
+
+/// How long the controller keeps BUSY high after a software reset and after a master activation.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BusyTiming {
+    pub reset: Duration,
+    pub refresh: Duration,
+}
+
+impl BusyTiming {
+    /// Approximate timing of the 2.66" three color panel.
+    pub const EPD_2IN66B: BusyTiming = BusyTiming { reset: Duration::from_millis(5), refresh: Duration::from_secs(15) };
+}
+
+impl Default for BusyTiming {
+    fn default() -> Self {
+        BusyTiming::EPD_2IN66B
+    }
+}
+
+/// Emulation of an SSD16xx e-paper controller with black/white and chromatic RAM.
+///
+/// The command stream is decoded into the RAM, a master activation copies the RAM to the
+/// emulated screen. BUSY follows the `BusyTiming` on the time of embassy-time, so tests can run
+/// the driver on virtual time with `simulator::time`. Commands sent while the controller is busy
+/// or in deep sleep are counted as protocol violations.
+#[derive(Clone)]
+pub struct Ssd16xx {
+    controller: Rc<RefCell<Controller>>,
+}
+
+impl Ssd16xx {
+    pub fn new(width: usize, height: usize) -> Self {
+        Ssd16xx { controller: Rc::new(RefCell::new(Controller::new(width, height))) }
+    }
+
+    /// Driver using the SPI device and pins of this controller.
+    pub fn display(&self) -> EmulatedDisplay {
+        Epd2in66b::new(self.spi(), self.dc(), self.rst(), self.busy())
+    }
+
+    pub fn spi(&self) -> Ssd16xxSpi {
+        Ssd16xxSpi { controller: self.controller.clone() }
+    }
+
+    pub fn dc(&self) -> DcPin {
+        DcPin { controller: self.controller.clone() }
+    }
+
+    pub fn rst(&self) -> RstPin {
+        RstPin { controller: self.controller.clone() }
+    }
+
+    pub fn busy(&self) -> BusyPin {
+        BusyPin { controller: self.controller.clone() }
+    }
+
+    /// Image shown by the last master activation, in the coordinates of the RAM.
+    pub fn screen(&self) -> Screen {
+        self.controller.borrow().screen.clone()
+    }
+
+    /// Image that the next master activation would show.
+    pub fn ram(&self) -> Screen {
+        self.controller.borrow().ram.clone()
+    }
+
+    pub fn data_entry_mode(&self) -> u8 {
+        self.controller.borrow().data_entry_mode
+    }
+
+    pub fn activations(&self) -> u32 {
+        self.controller.borrow().activations
+    }
+
+    pub fn is_sleeping(&self) -> bool {
+        self.controller.borrow().sleeping
+    }
+
+    pub fn violations(&self) -> u32 {
+        self.controller.borrow().violations
+    }
+
+    pub fn set_timing(&self, timing: BusyTiming) {
+        self.controller.borrow_mut().timing = timing;
+    }
+
+    /// Keeps BUSY high after the next reset or refresh, like a panel with a broken booster circuit.
+    /// Only a hardware reset brings the controller back.
+    pub fn set_stuck_busy(&self, stuck: bool) {
+        self.controller.borrow_mut().stuck_busy = stuck;
+    }
+}
+
+/// Black/white and chromatic plane of the controller, one bit per pixel and rows of whole bytes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Screen {
+    width: usize,
+    height: usize,
+    bw: Vec<u8>,
+    chromatic: Vec<u8>,
+}
+
+impl Screen {
+    fn new(width: usize, height: usize) -> Self {
+        let len = width.div_ceil(8) * height;
+        Screen { width, height, bw: vec![0xff; len], chromatic: vec![0x00; len] }
+    }
+
+    fn write(&mut self, chromatic: bool, x: usize, y: usize, byte: u8) {
+        let row = self.width.div_ceil(8);
+
+        if x < row && y < self.height {
+            let plane = if chromatic { &mut self.chromatic } else { &mut self.bw };
+            plane[y * row + x] = byte;
+        }
+    }
+}
+
+impl OriginDimensions for Screen {
+    fn size(&self) -> Size {
+        Size::new(self.width as u32, self.height as u32)
+    }
+}
+
+impl GetPixel for Screen {
+    type Color = ThreeColor;
+
+    fn pixel(&self, point: Point) -> Option<ThreeColor> {
+        if point.x < 0 || point.x as usize >= self.width || point.y < 0 || point.y as usize >= self.height {
+            return None;
+        }
+
+        let (x, y) = (point.x as usize, point.y as usize);
+        let index = y * self.width.div_ceil(8) + x / 8;
+        let mask = 0x80 >> (x % 8);
+
+        if self.chromatic[index] & mask != 0 {
+            Some(ThreeColor::Chromatic)
+        } else if self.bw[index] & mask != 0 {
+            Some(ThreeColor::White)
+        } else {
+            Some(ThreeColor::Black)
+        }
+    }
+}
+
+struct Controller {
+    ram: Screen,
+    screen: Screen,
+    data_mode: bool,
+    command: Option<u8>,
+    parameters: Vec<u8>,
+    data_entry_mode: u8,
+    x_range: (usize, usize),
+    y_range: (usize, usize),
+    x_counter: usize,
+    y_counter: usize,
+    timing: BusyTiming,
+    busy_until: Instant,
+    stuck_busy: bool,
+    sleeping: bool,
+    activations: u32,
+    violations: u32,
+}
+
+impl Controller {
+    fn new(width: usize, height: usize) -> Self {
+        let mut controller = Controller {
+            ram: Screen::new(width, height),
+            screen: Screen::new(width, height),
+            data_mode: false,
+            command: None,
+            parameters: Vec::new(),
+            data_entry_mode: 0,
+            x_range: (0, 0),
+            y_range: (0, 0),
+            x_counter: 0,
+            y_counter: 0,
+            timing: BusyTiming::default(),
+            busy_until: Instant::MIN,
+            stuck_busy: false,
+            sleeping: false,
+            activations: 0,
+            violations: 0,
+        };
+
+        controller.reset_registers();
+        controller
+    }
+
+    fn reset_registers(&mut self) {
+        self.data_entry_mode = 0b011;
+        self.x_range = (0, self.ram.width.div_ceil(8) - 1);
+        self.y_range = (0, self.ram.height - 1);
+        self.x_counter = 0;
+        self.y_counter = 0;
+        self.command = None;
+        self.parameters.clear();
+    }
+
+    fn hw_reset(&mut self) {
+        self.reset_registers();
+        self.sleeping = false;
+        self.busy_until = Instant::MIN;
+    }
+
+    fn is_busy(&self) -> bool {
+        Instant::now() < self.busy_until
+    }
+
+    fn busy_for(&mut self, duration: Duration) {
+        self.busy_until = if self.stuck_busy { Instant::MAX } else { Instant::now() + duration };
+    }
+
+    fn receive(&mut self, byte: u8) {
+        if self.sleeping || self.is_busy() {
+            self.violations += 1;
+            return;
+        }
+
+        if self.data_mode {
+            self.data(byte);
+        } else {
+            self.command(byte);
+        }
+    }
+
+    fn command(&mut self, command: u8) {
+        self.command = Some(command);
+        self.parameters.clear();
+
+        match command {
+            SW_RESET => {
+                self.reset_registers();
+                self.busy_for(self.timing.reset);
+            }
+            MASTER_ACTIVATION => {
+                self.screen = self.ram.clone();
+                self.activations += 1;
+                self.busy_for(self.timing.refresh);
+            }
+            _ => {}
+        }
+    }
+
+    fn data(&mut self, byte: u8) {
+        let Some(command) = self.command else {
+            self.violations += 1;
+            return;
+        };
+
+        match command {
+            WRITE_BLACK_WHITE_RAM | WRITE_CHROMATIC_RAM => {
+                self.ram.write(command == WRITE_CHROMATIC_RAM, self.x_counter, self.y_counter, byte);
+                self.advance();
+            }
+            _ => {
+                self.parameters.push(byte);
+                self.apply(command);
+            }
+        }
+    }
+
+    // Applies the parameters of a command once all of them have been received.
+    fn apply(&mut self, command: u8) {
+        let p = &self.parameters;
+        let word = |low: u8, high: u8| low as usize | (high as usize & 0x01) << 8;
+
+        match (command, p.len()) {
+            (DATA_ENTRY_MODE, 1) => self.data_entry_mode = p[0] & 0b111,
+            (SET_X_ADDRESS_RANGE, 2) => self.x_range = (p[0] as usize & 0x3f, p[1] as usize & 0x3f),
+            (SET_Y_ADDRESS_RANGE, 4) => self.y_range = (word(p[0], p[1]), word(p[2], p[3])),
+            (SET_X_ADDRESS_COUNTER, 1) => self.x_counter = p[0] as usize & 0x3f,
+            (SET_Y_ADDRESS_COUNTER, 2) => self.y_counter = word(p[0], p[1]),
+            (DEEP_SLEEP_MODE, 1) => self.sleeping = p[0] & 0b11 != 0,
+            _ => {}
+        }
+    }
+
+    // Moves the address counters to the next byte inside the window, as set by the data entry mode.
+    fn advance(&mut self) {
+        let x_increment = self.data_entry_mode & 0b001 != 0;
+        let y_increment = self.data_entry_mode & 0b010 != 0;
+        let y_minor = self.data_entry_mode & 0b100 != 0;
+
+        if y_minor {
+            if step(&mut self.y_counter, self.y_range, y_increment) {
+                step(&mut self.x_counter, self.x_range, x_increment);
+            }
+        } else if step(&mut self.x_counter, self.x_range, x_increment) {
+            step(&mut self.y_counter, self.y_range, y_increment);
+        }
+    }
+}
+
+// Steps the counter from the start towards the end of the range, returns true when it wraps around.
+fn step(counter: &mut usize, (start, end): (usize, usize), increment: bool) -> bool {
+    if *counter == end {
+        *counter = start;
+        return true;
+    }
+
+    if increment {
+        *counter += 1;
+    } else {
+        *counter = counter.wrapping_sub(1);
+    }
+
+    false
+}
+
+pub struct Ssd16xxSpi {
+    controller: Rc<RefCell<Controller>>,
+}
+
+impl SpiErrorType for Ssd16xxSpi {
+    type Error = Infallible;
+}
+
+impl SpiDevice for Ssd16xxSpi {
+    async fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        let mut controller = self.controller.borrow_mut();
+
+        for operation in operations {
+            match operation {
+                Operation::Write(data) => data.iter().for_each(|&byte| controller.receive(byte)),
+                Operation::Transfer(read, write) => {
+                    write.iter().for_each(|&byte| controller.receive(byte));
+                    read.fill(0);
+                }
+                Operation::TransferInPlace(data) => {
+                    data.iter().for_each(|&byte| controller.receive(byte));
+                    data.fill(0);
+                }
+                Operation::Read(data) => data.fill(0),
+                Operation::DelayNs(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Data/command pin, low for commands and high for data.
+pub struct DcPin {
+    controller: Rc<RefCell<Controller>>,
+}
+
+impl PinErrorType for DcPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for DcPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.controller.borrow_mut().data_mode = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.controller.borrow_mut().data_mode = true;
+        Ok(())
+    }
+}
+
+/// Reset pin, active low.
+pub struct RstPin {
+    controller: Rc<RefCell<Controller>>,
+}
+
+impl PinErrorType for RstPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for RstPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.controller.borrow_mut().hw_reset();
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Busy pin, high while the controller is busy.
+pub struct BusyPin {
+    controller: Rc<RefCell<Controller>>,
+}
+
+impl PinErrorType for BusyPin {
+    type Error = Infallible;
+}
+
+impl InputPin for BusyPin {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(self.controller.borrow().is_busy())
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        self.is_high().map(|busy| !busy)
+    }
+}
@@ -0,0 +1,49 @@
+use core::future::Future;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Mutex, PoisonError};
+
+use embassy_time::{Duration, Instant, MockDriver};
+
+/// Resolution of the virtual time.
+pub const STEP: Duration = Duration::from_millis(1);
+
+// The mock time driver is global, so only one future can run on virtual time at once.
+static CLOCK: Mutex<()> = Mutex::new(());
+
+/// Runs the future to completion on virtual time, which advances by `STEP` whenever the future
+/// is pending. Timers expire without waiting for real time.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let Some(output) = run(None, future) else { unreachable!() };
+    output
+}
+
+/// Runs the future on virtual time for at most `limit`, returns `None` if it hasn't completed
+/// by then. The future is dropped in that case, like a cancelled task.
+pub fn run_for<F: Future>(limit: Duration, future: F) -> Option<F::Output> {
+    run(Some(limit), future)
+}
+
+fn run<F: Future>(limit: Option<Duration>, future: F) -> Option<F::Output> {
+    let _clock = CLOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    let start = Instant::now();
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return Some(output);
+        }
+
+        if limit.is_some_and(|limit| elapsed_since(start) >= limit) {
+            return None;
+        }
+
+        MockDriver::get().advance(STEP);
+    }
+}
+
+/// Virtual time since `start`. Unlike `Instant::elapsed` this doesn't need a defmt logger on the host.
+pub fn elapsed_since(start: Instant) -> Duration {
+    Instant::now().checked_duration_since(start).unwrap_or(Duration::from_ticks(0))
+}
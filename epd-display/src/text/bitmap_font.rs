@@ -0,0 +1,61 @@
+use embedded_graphics::image::{GetPixel, ImageRaw};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+
+use super::font::{Font, Glyph};
+
+/// Proportional font with glyphs packed into a single row image.
+///
+/// Fonts of this type are generated at build time by the `epd-assets` crate.
+pub struct BitmapFont<'a> {
+    image: ImageRaw<'a, BinaryColor>,
+    glyphs: &'a [BitmapGlyph],
+    line_height: u32,
+    baseline: u32,
+}
+
+/// Glyph metrics of a `BitmapFont`, the glyphs must be sorted by character.
+pub struct BitmapGlyph {
+    pub character: char,
+    pub x: u16,
+    pub width: u8,
+    pub height: u8,
+    pub x_offset: i8,
+    pub y_offset: i8,
+    pub advance: u8,
+}
+
+impl<'a> BitmapFont<'a> {
+    pub const fn new(data: &'a [u8], width: u32, glyphs: &'a [BitmapGlyph], line_height: u32, baseline: u32) -> Self {
+        BitmapFont { image: ImageRaw::new(data, width), glyphs, line_height, baseline }
+    }
+}
+
+impl Font for BitmapFont<'_> {
+    fn line_height(&self) -> u32 {
+        self.line_height
+    }
+
+    fn baseline(&self) -> u32 {
+        self.baseline
+    }
+
+    fn glyph(&self, c: char) -> Option<Glyph> {
+        let index = self.glyphs.binary_search_by_key(&c, |glyph| glyph.character).ok()?;
+        let glyph = &self.glyphs[index];
+
+        Some(Glyph {
+            source: Some(Point::new(glyph.x as i32, 0)),
+            size: Size::new(glyph.width as u32, glyph.height as u32),
+            offset: Point::new(glyph.x_offset as i32, glyph.y_offset as i32),
+            advance: glyph.advance as u32,
+        })
+    }
+
+    fn glyph_pixel(&self, glyph: &Glyph, point: Point) -> bool {
+        match glyph.source {
+            Some(source) => self.image.pixel(source + point) == Some(BinaryColor::On),
+            None => false,
+        }
+    }
+}
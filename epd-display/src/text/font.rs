@@ -0,0 +1,40 @@
+use embedded_graphics::prelude::*;
+
+/// Source of glyphs for `FontTextStyle`.
+pub trait Font: Sync {
+    /// Height of a line of text in pixels.
+    fn line_height(&self) -> u32;
+
+    /// Offset from the top of a line to the baseline.
+    fn baseline(&self) -> u32;
+
+    /// Looks up the glyph for `c`, `None` if the font doesn't contain it.
+    fn glyph(&self, c: char) -> Option<Glyph>;
+
+    /// Returns whether the glyph pixel at `point` (relative to the glyph origin) is set.
+    fn glyph_pixel(&self, glyph: &Glyph, point: Point) -> bool;
+
+    /// Glyph drawn in place of characters the font doesn't contain.
+    fn fallback_glyph(&self) -> Glyph {
+        let baseline = self.baseline();
+        let height = (baseline * 3 / 4).max(1);
+        let width = (baseline / 2).max(1);
+
+        Glyph {
+            source: None,
+            size: Size::new(width, height),
+            offset: Point::new(1, baseline.saturating_sub(height) as i32),
+            advance: width + 2,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub struct Glyph {
+    /// Position of the glyph in the font data, `None` draws an outlined box.
+    pub source: Option<Point>,
+    pub size: Size,
+    /// Offset of the glyph from the pen position at the top of the line.
+    pub offset: Point,
+    pub advance: u32,
+}
@@ -0,0 +1,129 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::Baseline;
+use embedded_graphics::text::renderer::{CharacterStyle, TextMetrics, TextRenderer};
+
+use super::font::{Font, Glyph};
+
+/// Text style for drawing UTF-8 text with a `Font`.
+#[derive(Copy, Clone)]
+pub struct FontTextStyle<'a, C> {
+    font: &'a dyn Font,
+    text_color: Option<C>,
+    background_color: Option<C>,
+}
+
+impl<'a, C: PixelColor> FontTextStyle<'a, C> {
+    pub fn new(font: &'a dyn Font, text_color: C) -> Self {
+        FontTextStyle { font, text_color: Some(text_color), background_color: None }
+    }
+
+    pub fn font(&self) -> &'a dyn Font {
+        self.font
+    }
+
+    fn glyph(&self, c: char) -> Glyph {
+        self.font.glyph(c).unwrap_or_else(|| self.font.fallback_glyph())
+    }
+
+    fn line_top(&self, position: Point, baseline: Baseline) -> Point {
+        let offset = match baseline {
+            Baseline::Top => 0,
+            Baseline::Bottom => self.font.line_height().saturating_sub(1),
+            Baseline::Middle => self.font.line_height().saturating_sub(1) / 2,
+            Baseline::Alphabetic => self.font.baseline(),
+        };
+
+        position - Point::new(0, offset as i32)
+    }
+
+    fn fill_background<D>(&self, top_left: Point, width: u32, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        match self.background_color {
+            Some(color) => {
+                let area = Rectangle::new(top_left, Size::new(width, self.font.line_height()));
+                target.fill_solid(&area, color)
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn draw_glyph<D>(&self, glyph: &Glyph, pen: Point, color: C, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let area = Rectangle::new(pen + glyph.offset, glyph.size);
+
+        if glyph.source.is_none() {
+            return area.into_styled(PrimitiveStyle::with_stroke(color, 1)).draw(target);
+        }
+
+        let pixels = area.points()
+            .filter(|point| self.font.glyph_pixel(glyph, *point - area.top_left))
+            .map(|point| Pixel(point, color));
+
+        target.draw_iter(pixels)
+    }
+}
+
+impl<C: PixelColor> TextRenderer for FontTextStyle<'_, C> {
+    type Color = C;
+
+    fn draw_string<D>(&self, text: &str, position: Point, baseline: Baseline, target: &mut D) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let mut pen = self.line_top(position, baseline);
+
+        for c in text.chars() {
+            let glyph = self.glyph(c);
+
+            self.fill_background(pen, glyph.advance, target)?;
+
+            if let Some(color) = self.text_color {
+                self.draw_glyph(&glyph, pen, color, target)?;
+            }
+
+            pen.x += glyph.advance as i32;
+        }
+
+        Ok(Point::new(pen.x, position.y))
+    }
+
+    fn draw_whitespace<D>(&self, width: u32, position: Point, baseline: Baseline, target: &mut D) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        self.fill_background(self.line_top(position, baseline), width, target)?;
+
+        Ok(position + Point::new(width as i32, 0))
+    }
+
+    fn measure_string(&self, text: &str, position: Point, baseline: Baseline) -> TextMetrics {
+        let width: u32 = text.chars().map(|c| self.glyph(c).advance).sum();
+        let top_left = self.line_top(position, baseline);
+
+        TextMetrics {
+            bounding_box: Rectangle::new(top_left, Size::new(width, self.font.line_height())),
+            next_position: position + Point::new(width as i32, 0),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        self.font.line_height()
+    }
+}
+
+impl<C: PixelColor> CharacterStyle for FontTextStyle<'_, C> {
+    type Color = C;
+
+    fn set_text_color(&mut self, text_color: Option<Self::Color>) {
+        self.text_color = text_color;
+    }
+
+    fn set_background_color(&mut self, background_color: Option<Self::Color>) {
+        self.background_color = background_color;
+    }
+}
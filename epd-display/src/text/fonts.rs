@@ -0,0 +1,13 @@
+use embedded_graphics::mono_font::iso_8859_15::{FONT_6X10 as MONO_6X10, FONT_8X13 as MONO_8X13, FONT_10X20 as MONO_10X20};
+
+use super::mono_font_face::MonoFontFace;
+
+// ISO-8859-15 covers ISO-8859-1 except for a few rarely used symbols, and adds the euro sign.
+
+pub static FONT_6X10: MonoFontFace = MonoFontFace::new(&MONO_6X10);
+pub static FONT_8X13: MonoFontFace = MonoFontFace::new(&MONO_8X13);
+pub static FONT_10X20: MonoFontFace = MonoFontFace::new(&MONO_10X20);
+
+pub static FONT_6X10_PROPORTIONAL: MonoFontFace = MonoFontFace::proportional(&MONO_6X10);
+pub static FONT_8X13_PROPORTIONAL: MonoFontFace = MonoFontFace::proportional(&MONO_8X13);
+pub static FONT_10X20_PROPORTIONAL: MonoFontFace = MonoFontFace::proportional(&MONO_10X20);
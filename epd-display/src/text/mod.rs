@@ -0,0 +1,6 @@
+pub mod bitmap_font;
+pub mod font;
+pub mod font_text_style;
+pub mod fonts;
+pub mod mono_font_face;
+pub mod text_layout;
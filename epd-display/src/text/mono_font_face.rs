@@ -0,0 +1,103 @@
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::mono_font::MonoFont;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+
+use super::font::{Font, Glyph};
+
+const REPLACEMENT_CHAR: char = '?';
+
+/// `Font` using the glyphs of an embedded-graphics `MonoFont`.
+///
+/// Characters the mono font maps to its replacement glyph are reported as missing, so they are
+/// drawn with the fallback glyph. In proportional mode the empty columns left and right of each
+/// glyph are removed.
+pub struct MonoFontFace<'a> {
+    font: &'a MonoFont<'a>,
+    proportional: bool,
+}
+
+impl<'a> MonoFontFace<'a> {
+    pub const fn new(font: &'a MonoFont<'a>) -> Self {
+        MonoFontFace { font, proportional: false }
+    }
+
+    pub const fn proportional(font: &'a MonoFont<'a>) -> Self {
+        MonoFontFace { font, proportional: true }
+    }
+
+    fn glyph_origin(&self, c: char) -> Option<Point> {
+        let mapping = self.font.glyph_mapping;
+        let index = mapping.index(c);
+
+        if c != REPLACEMENT_CHAR && index == mapping.index(REPLACEMENT_CHAR) {
+            return None;
+        }
+
+        let char_size = self.font.character_size;
+        let glyphs_per_row = self.font.image.size().width / char_size.width;
+        let row = index as u32 / glyphs_per_row;
+        let column = index as u32 % glyphs_per_row;
+
+        Some(Point::new((column * char_size.width) as i32, (row * char_size.height) as i32))
+    }
+
+    fn ink_columns(&self, origin: Point) -> Option<(u32, u32)> {
+        let char_size = self.font.character_size;
+        let is_ink = |x: u32| (0..char_size.height).any(|y| {
+            self.font.image.pixel(origin + Point::new(x as i32, y as i32)) == Some(BinaryColor::On)
+        });
+
+        let first = (0..char_size.width).find(|&x| is_ink(x))?;
+        let last = (0..char_size.width).rev().find(|&x| is_ink(x))?;
+
+        Some((first, last))
+    }
+}
+
+impl Font for MonoFontFace<'_> {
+    fn line_height(&self) -> u32 {
+        self.font.character_size.height
+    }
+
+    fn baseline(&self) -> u32 {
+        self.font.baseline
+    }
+
+    fn glyph(&self, c: char) -> Option<Glyph> {
+        let origin = self.glyph_origin(c)?;
+        let char_size = self.font.character_size;
+        let spacing = self.font.character_spacing;
+
+        if !self.proportional {
+            return Some(Glyph {
+                source: Some(origin),
+                size: char_size,
+                offset: Point::zero(),
+                advance: char_size.width + spacing,
+            });
+        }
+
+        match self.ink_columns(origin) {
+            Some((first, last)) => Some(Glyph {
+                source: Some(origin + Point::new(first as i32, 0)),
+                size: Size::new(last - first + 1, char_size.height),
+                offset: Point::zero(),
+                advance: last - first + 1 + spacing.max(1),
+            }),
+            None => Some(Glyph {
+                source: Some(origin),
+                size: Size::zero(),
+                offset: Point::zero(),
+                advance: char_size.width / 2 + spacing,
+            }),
+        }
+    }
+
+    fn glyph_pixel(&self, glyph: &Glyph, point: Point) -> bool {
+        match glyph.source {
+            Some(source) => self.font.image.pixel(source + point) == Some(BinaryColor::On),
+            None => false,
+        }
+    }
+}
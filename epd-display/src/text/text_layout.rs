@@ -0,0 +1,209 @@
+use core::convert::Infallible;
+use core::marker::PhantomData;
+
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::{Alignment, Baseline, Text};
+use embedded_graphics::text::renderer::TextRenderer;
+
+const ELLIPSIS: &str = "...";
+
+/// Word-wrapping text layout inside a bounding rectangle.
+///
+/// Paragraphs are wrapped at spaces to the width of the bounds and stacked from top to bottom.
+/// If the text doesn't fit into the bounds, the last visible line is ended with an ellipsis.
+pub struct TextLayout {
+    bounds: Rectangle,
+    alignment: Alignment,
+    line_spacing: u32,
+}
+
+impl TextLayout {
+    pub fn new(bounds: Rectangle, alignment: Alignment) -> Self {
+        TextLayout { bounds, alignment, line_spacing: 0 }
+    }
+
+    pub fn with_line_spacing(mut self, line_spacing: u32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    /// Draws the paragraphs and returns the height of the drawn text.
+    pub fn draw<'a, S, D, I>(&self, paragraphs: I, target: &mut D) -> Result<u32, D::Error>
+    where
+        S: TextRenderer<Color = D::Color> + Clone,
+        D: DrawTarget,
+        I: IntoIterator<Item = (&'a str, S)>,
+    {
+        let top = self.bounds.top_left.y;
+        let bottom = top.saturating_add_unsigned(self.bounds.size.height);
+        let max_width = self.bounds.size.width;
+        let mut paragraphs = paragraphs.into_iter().peekable();
+        let mut y = top;
+        let fits = |y: i32, height: u32| y.checked_add_unsigned(height).is_some_and(|end| end <= bottom);
+
+        while let Some((text, style)) = paragraphs.next() {
+            let line_height = style.line_height();
+            let mut lines = LineBreaker::new(text, max_width, |s| text_width(&style, s)).peekable();
+
+            while let Some(line) = lines.next() {
+                if !fits(y, line_height) {
+                    return Ok(height_between(top, y));
+                }
+
+                let next_y = y.saturating_add_unsigned(line_height.saturating_add(self.line_spacing));
+                let next_height = if lines.peek().is_some() {
+                    Some(line_height)
+                } else {
+                    paragraphs.peek().map(|(_, next_style)| next_style.line_height())
+                };
+
+                if next_height.is_some_and(|h| !fits(next_y, h)) {
+                    let line = truncate(line, max_width, |s| text_width(&style, s));
+                    let width = text_width(&style, line) + text_width(&style, ELLIPSIS);
+                    let position = Point::new(self.line_x(width), y);
+                    let next = Text::with_baseline(line, position, style.clone(), Baseline::Top).draw(target)?;
+                    Text::with_baseline(ELLIPSIS, next, style.clone(), Baseline::Top).draw(target)?;
+
+                    return Ok(height_between(top, y + line_height as i32));
+                }
+
+                let position = Point::new(self.line_x(text_width(&style, line)), y);
+                Text::with_baseline(line, position, style.clone(), Baseline::Top).draw(target)?;
+
+                y = next_y;
+            }
+        }
+
+        Ok(height_between(top, y).saturating_sub(self.line_spacing))
+    }
+
+    /// Returns the height the paragraphs would take when drawn.
+    pub fn measure<'a, S, I>(&self, paragraphs: I) -> u32
+    where
+        S: TextRenderer + Clone,
+        I: IntoIterator<Item = (&'a str, S)>,
+    {
+        let Ok(height) = self.draw(paragraphs, &mut NullTarget(PhantomData));
+        height
+    }
+
+    fn line_x(&self, line_width: u32) -> i32 {
+        let left = self.bounds.top_left.x;
+        let free = self.bounds.size.width.saturating_sub(line_width);
+
+        match self.alignment {
+            Alignment::Left => left,
+            Alignment::Center => left.saturating_add_unsigned(free / 2),
+            Alignment::Right => left.saturating_add_unsigned(free),
+        }
+    }
+}
+
+/// Splits a paragraph into lines no wider than `max_width`.
+///
+/// Lines are broken at spaces and explicit newlines. Words that are wider than a whole line
+/// are broken between characters.
+pub struct LineBreaker<'a, F> {
+    rest: Option<&'a str>,
+    max_width: u32,
+    measure: F,
+}
+
+impl<'a, F> LineBreaker<'a, F>
+where
+    F: Fn(&str) -> u32,
+{
+    pub fn new(text: &'a str, max_width: u32, measure: F) -> Self {
+        LineBreaker { rest: Some(text), max_width, measure }
+    }
+}
+
+impl<'a, F> Iterator for LineBreaker<'a, F>
+where
+    F: Fn(&str) -> u32,
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let text = self.rest?;
+        let mut width = 0;
+        let mut break_at = None;
+
+        for (i, c) in text.char_indices() {
+            if c == '\n' {
+                self.rest = Some(&text[i + 1..]);
+                return Some(&text[..i]);
+            }
+
+            if c == ' ' {
+                break_at = Some(i);
+            }
+
+            width += (self.measure)(&text[i..i + c.len_utf8()]);
+
+            if width > self.max_width {
+                let end = match break_at {
+                    Some(end) if end > 0 => end,
+                    _ if i == 0 => c.len_utf8(),
+                    _ => i,
+                };
+
+                let rest = text[end..].trim_start_matches(' ');
+                self.rest = if rest.is_empty() { None } else { Some(rest) };
+                return Some(text[..end].trim_end_matches(' '));
+            }
+        }
+
+        self.rest = None;
+        Some(text)
+    }
+}
+
+fn truncate<F>(line: &str, max_width: u32, measure: F) -> &str
+where
+    F: Fn(&str) -> u32,
+{
+    let available = max_width.saturating_sub(measure(ELLIPSIS));
+    let mut width = 0;
+
+    for (i, c) in line.char_indices() {
+        width += measure(&line[i..i + c.len_utf8()]);
+
+        if width > available {
+            return line[..i].trim_end_matches(' ');
+        }
+    }
+
+    line
+}
+
+// Draw target that discards all pixels, used for measuring.
+struct NullTarget<C>(PhantomData<C>);
+
+impl<C> OriginDimensions for NullTarget<C> {
+    fn size(&self) -> Size {
+        Size::zero()
+    }
+}
+
+impl<C: PixelColor> DrawTarget for NullTarget<C> {
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, _pixels: I) -> Result<(), Infallible>
+    where
+        I: IntoIterator<Item = Pixel<C>>,
+    {
+        Ok(())
+    }
+}
+
+pub(crate) fn text_width<S: TextRenderer>(style: &S, text: &str) -> u32 {
+    let metrics = style.measure_string(text, Point::zero(), Baseline::Top);
+    metrics.next_position.x.max(0) as u32
+}
+
+fn height_between(top: i32, bottom: i32) -> u32 {
+    bottom.saturating_sub(top).max(0) as u32
+}
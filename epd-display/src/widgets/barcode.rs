@@ -0,0 +1,276 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+
+use crate::epd::three_color::ThreeColor;
+use crate::text::font::Font;
+use crate::text::font_text_style::FontTextStyle;
+use crate::text::fonts::FONT_8X13;
+use super::{code128, ean13};
+
+const MAX_MODULES: usize = 512;
+const CODE128_QUIET_ZONE: u32 = 10;
+const EAN13_QUIET_ZONE_LEFT: u32 = 11;
+const EAN13_QUIET_ZONE_RIGHT: u32 = 7;
+const DEFAULT_BAR_HEIGHT: u32 = 40;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BarcodeError {
+    InvalidCharacter,
+    InvalidLength,
+    InvalidCheckDigit,
+    TooLong,
+}
+
+/// Modules of a linear barcode, a set bit is a bar.
+#[derive(Clone)]
+pub struct Bars {
+    modules: [u8; MAX_MODULES / 8],
+    len: usize,
+}
+
+impl Bars {
+    fn new() -> Self {
+        Bars { modules: [0; MAX_MODULES / 8], len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_bar(&self, module: usize) -> bool {
+        module < self.len && self.modules[module / 8] & (0x80 >> (module % 8)) != 0
+    }
+
+    /// Appends alternating bars and spaces of the given widths, starting with a bar.
+    pub(crate) fn push_widths(&mut self, widths: &[u8]) -> Result<(), BarcodeError> {
+        self.push_alternating(widths, true)
+    }
+
+    /// Appends alternating spaces and bars of the given widths, starting with a space.
+    pub(crate) fn push_spaced_widths(&mut self, widths: &[u8]) -> Result<(), BarcodeError> {
+        self.push_alternating(widths, false)
+    }
+
+    fn push_alternating(&mut self, widths: &[u8], mut is_bar: bool) -> Result<(), BarcodeError> {
+        for &width in widths {
+            for _ in 0..width {
+                if self.len >= MAX_MODULES {
+                    return Err(BarcodeError::TooLong);
+                }
+
+                if is_bar {
+                    self.modules[self.len / 8] |= 0x80 >> (self.len % 8);
+                }
+
+                self.len += 1;
+            }
+
+            is_bar = !is_bar;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+enum Label<'a> {
+    Text(&'a str),
+    Ean13([u8; 13]),
+}
+
+/// Linear barcode with the human readable text below the bars.
+///
+/// The quiet zones and the background are drawn white.
+#[derive(Clone)]
+pub struct Barcode<'a> {
+    bars: Bars,
+    label: Label<'a>,
+    top_left: Point,
+    module_width: u32,
+    bar_height: u32,
+    color: ThreeColor,
+    font: &'a dyn Font,
+}
+
+impl<'a> Barcode<'a> {
+    pub fn code128(text: &'a str, top_left: Point) -> Result<Self, BarcodeError> {
+        let mut bars = Bars::new();
+        code128::encode(text, &mut bars)?;
+
+        Ok(Self::new(bars, Label::Text(text), top_left))
+    }
+
+    /// Creates an EAN-13 barcode from 12 digits, or 13 digits including the check digit.
+    pub fn ean13(digits: &str, top_left: Point) -> Result<Self, BarcodeError> {
+        let mut bars = Bars::new();
+        let digits = ean13::encode(digits, &mut bars)?;
+
+        Ok(Self::new(bars, Label::Ean13(digits), top_left))
+    }
+
+    fn new(bars: Bars, label: Label<'a>, top_left: Point) -> Self {
+        Barcode {
+            bars,
+            label,
+            top_left,
+            module_width: 1,
+            bar_height: DEFAULT_BAR_HEIGHT,
+            color: ThreeColor::Black,
+            font: &FONT_8X13,
+        }
+    }
+
+    pub fn with_module_width(mut self, module_width: u32) -> Self {
+        self.module_width = module_width.max(1);
+        self
+    }
+
+    pub fn with_bar_height(mut self, bar_height: u32) -> Self {
+        self.bar_height = bar_height;
+        self
+    }
+
+    pub fn with_color(mut self, color: ThreeColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_font(mut self, font: &'a dyn Font) -> Self {
+        self.font = font;
+        self
+    }
+
+    /// Uses the widest modules for which the barcode including the quiet zones fits into `width`.
+    pub fn scale_to_fit(self, width: u32) -> Self {
+        let module_count = self.module_count();
+        self.with_module_width(width / module_count)
+    }
+
+    pub fn bars(&self) -> &Bars {
+        &self.bars
+    }
+
+    /// Number of modules including the quiet zones.
+    pub fn module_count(&self) -> u32 {
+        let (left, right) = self.quiet_zones();
+        left + self.bars.len() as u32 + right
+    }
+
+    fn quiet_zones(&self) -> (u32, u32) {
+        match self.label {
+            Label::Text(_) => (CODE128_QUIET_ZONE, CODE128_QUIET_ZONE),
+            Label::Ean13(_) => (EAN13_QUIET_ZONE_LEFT, EAN13_QUIET_ZONE_RIGHT),
+        }
+    }
+
+    fn module_x(&self, module: u32) -> i32 {
+        self.top_left.x + ((self.quiet_zones().0 + module) * self.module_width) as i32
+    }
+
+    fn draw_bars<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        let guard_height = self.bar_height + self.font.line_height() / 2;
+        let mut module = 0;
+
+        while module < self.bars.len() {
+            let start = module;
+
+            while module < self.bars.len() && self.bars.is_bar(module) == self.bars.is_bar(start) {
+                module += 1;
+            }
+
+            if self.bars.is_bar(start) {
+                let height = if self.is_guard(start) { guard_height } else { self.bar_height };
+                let width = (module - start) as u32 * self.module_width;
+                let bar = Rectangle::new(Point::new(self.module_x(start as u32), self.top_left.y), Size::new(width, height));
+
+                target.fill_solid(&bar, self.color)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_guard(&self, module: usize) -> bool {
+        match self.label {
+            Label::Text(_) => false,
+            Label::Ean13(_) => module < 3 || (45..50).contains(&module) || module >= 92,
+        }
+    }
+
+    fn draw_label<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        let style = FontTextStyle::new(self.font, self.color);
+        let text_style = TextStyleBuilder::new().alignment(Alignment::Center).baseline(Baseline::Top).build();
+        let y = self.top_left.y + self.bar_height as i32 + 1;
+        let center = |first: u32, last: u32| (self.module_x(first) + self.module_x(last)) / 2;
+
+        match self.label {
+            Label::Text(text) => {
+                let position = Point::new(center(0, self.bars.len() as u32), y);
+                Text::with_text_style(text, position, style, text_style).draw(target)?;
+            }
+            Label::Ean13(digits) => {
+                let mut buffer = [0; 13];
+
+                for (byte, digit) in buffer.iter_mut().zip(digits) {
+                    *byte = b'0' + digit;
+                }
+
+                let text = core::str::from_utf8(&buffer).unwrap_or_default();
+                let first_digit = Point::new((self.top_left.x + self.module_x(0)) / 2, y);
+
+                Text::with_text_style(&text[..1], first_digit, style, text_style).draw(target)?;
+                Text::with_text_style(&text[1..7], Point::new(center(3, 45), y), style, text_style).draw(target)?;
+                Text::with_text_style(&text[7..], Point::new(center(50, 92), y), style, text_style).draw(target)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Dimensions for Barcode<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        let width = self.module_count() * self.module_width;
+        let height = self.bar_height + 1 + self.font.line_height();
+
+        Rectangle::new(self.top_left, Size::new(width, height))
+    }
+}
+
+impl Transform for Barcode<'_> {
+    fn translate(&self, by: Point) -> Self {
+        let mut barcode = self.clone();
+        barcode.translate_mut(by);
+        barcode
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl Drawable for Barcode<'_> {
+    type Color = ThreeColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        target.fill_solid(&self.bounding_box(), ThreeColor::White)?;
+        self.draw_bars(target)?;
+        self.draw_label(target)
+    }
+}
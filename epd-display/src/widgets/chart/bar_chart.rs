@@ -0,0 +1,122 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+
+use crate::epd::three_color::ThreeColor;
+use super::scale::{value_color, Scale};
+
+const DEFAULT_SPACING: u32 = 2;
+
+/// Bar chart of a data series with a base line at zero, or at the edge of the scale closest to
+/// zero.
+///
+/// Bars of values above the threshold are filled chromatic.
+#[derive(Copy, Clone)]
+pub struct BarChart<'a> {
+    values: &'a [f32],
+    bounds: Rectangle,
+    scale: Scale,
+    threshold: Option<f32>,
+    color: ThreeColor,
+    spacing: u32,
+}
+
+impl<'a> BarChart<'a> {
+    pub fn new(values: &'a [f32], bounds: Rectangle) -> Self {
+        BarChart {
+            values,
+            bounds,
+            scale: Scale::fit(values).including(0.0),
+            threshold: None,
+            color: ThreeColor::Black,
+            spacing: DEFAULT_SPACING,
+        }
+    }
+
+    pub fn with_bounds(mut self, bounds: Rectangle) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_color(mut self, color: ThreeColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Space between the bars, reduced if the bars would get narrower than one pixel.
+    pub fn with_spacing(mut self, spacing: u32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    fn y(&self, value: f32) -> i32 {
+        let height = self.bounds.size.height.saturating_sub(1);
+        self.bounds.top_left.y.saturating_add_unsigned(height - self.scale.pixels(value, height))
+    }
+}
+
+impl Dimensions for BarChart<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+}
+
+impl Transform for BarChart<'_> {
+    fn translate(&self, by: Point) -> Self {
+        let mut bar_chart = *self;
+        bar_chart.translate_mut(by);
+        bar_chart
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.bounds.top_left += by;
+        self
+    }
+}
+
+impl Drawable for BarChart<'_> {
+    type Color = ThreeColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        let count = self.values.len() as u64;
+        let width = self.bounds.size.width;
+
+        if count == 0 || width == 0 {
+            return Ok(());
+        }
+
+        let base = self.y(0.0);
+        let left = self.bounds.top_left.x;
+        let x = |index: usize| left.saturating_add_unsigned((index as u64 * width as u64 / count) as u32);
+        let spacing = self.spacing.min(((width as u64 / count) as u32).saturating_sub(1));
+
+        for (index, &value) in self.values.iter().enumerate() {
+            let start = x(index);
+            let end = x(index + 1).saturating_sub_unsigned(spacing);
+
+            if end <= start {
+                continue;
+            }
+
+            let top = self.y(value);
+            let bar = Rectangle::with_corners(Point::new(start, base.min(top)), Point::new(end - 1, base.max(top)));
+
+            target.fill_solid(&bar, value_color(value, self.threshold, self.color))?;
+        }
+
+        target.fill_solid(&Rectangle::new(Point::new(left, base), Size::new(width, 1)), self.color)
+    }
+}
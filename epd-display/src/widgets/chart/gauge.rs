@@ -0,0 +1,123 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Arc, PrimitiveStyle, Rectangle};
+
+use crate::epd::three_color::ThreeColor;
+use super::scale::{value_color, Scale};
+
+// The gauge opens downwards, angles are clockwise from the 3 o'clock position.
+const START_ANGLE: f32 = 135.0;
+const SWEEP: f32 = 270.0;
+const DEFAULT_STROKE_WIDTH: u32 = 8;
+
+/// Arc gauge showing a single value as the filled part of a 270° track.
+///
+/// The value arc is drawn chromatic for values above the threshold, the track beyond the threshold
+/// is drawn chromatic to mark the critical range.
+#[derive(Copy, Clone)]
+pub struct Gauge {
+    value: f32,
+    center: Point,
+    diameter: u32,
+    scale: Scale,
+    threshold: Option<f32>,
+    color: ThreeColor,
+    stroke_width: u32,
+}
+
+impl Gauge {
+    pub fn new(value: f32, center: Point, diameter: u32) -> Self {
+        Gauge {
+            value,
+            center,
+            diameter,
+            scale: Scale::new(0.0, 100.0),
+            threshold: None,
+            color: ThreeColor::Black,
+            stroke_width: DEFAULT_STROKE_WIDTH,
+        }
+    }
+
+    /// Centers the gauge in the bounds with the largest diameter that fits.
+    pub fn with_bounds(mut self, bounds: Rectangle) -> Self {
+        self.center = bounds.center();
+        self.diameter = bounds.size.width.min(bounds.size.height);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_color(mut self, color: ThreeColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_stroke_width(mut self, stroke_width: u32) -> Self {
+        self.stroke_width = stroke_width.max(1);
+        self
+    }
+
+    fn arc(&self, from: f32, to: f32) -> Arc {
+        let start = START_ANGLE + SWEEP * self.scale.fraction(from);
+        let sweep = SWEEP * (self.scale.fraction(to) - self.scale.fraction(from));
+        // The stroke is centered on the arc, keep the value arc inside the bounding box.
+        let diameter = self.diameter.saturating_sub(self.stroke_width);
+
+        Arc::with_center(self.center, diameter, start.deg(), sweep.deg())
+    }
+}
+
+impl Dimensions for Gauge {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::with_center(self.center, Size::new_equal(self.diameter))
+    }
+}
+
+impl Transform for Gauge {
+    fn translate(&self, by: Point) -> Self {
+        let mut gauge = *self;
+        gauge.translate_mut(by);
+        gauge
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.center += by;
+        self
+    }
+}
+
+impl Drawable for Gauge {
+    type Color = ThreeColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        let (min, max) = (self.scale.min(), self.scale.max());
+        let track_end = self.threshold.map_or(max, |threshold| threshold.clamp(min, max));
+
+        self.arc(min, track_end)
+            .into_styled(PrimitiveStyle::with_stroke(self.color, 1))
+            .draw(target)?;
+
+        if track_end < max {
+            self.arc(track_end, max)
+                .into_styled(PrimitiveStyle::with_stroke(ThreeColor::Chromatic, 1))
+                .draw(target)?;
+        }
+
+        let color = value_color(self.value, self.threshold, self.color);
+
+        self.arc(min, self.value)
+            .into_styled(PrimitiveStyle::with_stroke(color, self.stroke_width))
+            .draw(target)
+    }
+}
@@ -0,0 +1,4 @@
+pub mod bar_chart;
+pub mod gauge;
+pub mod scale;
+pub mod sparkline;
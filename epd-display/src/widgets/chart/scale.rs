@@ -0,0 +1,62 @@
+use crate::epd::three_color::ThreeColor;
+
+/// Value range of a chart, values outside of the range are clamped.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Scale {
+    min: f32,
+    max: f32,
+}
+
+impl Scale {
+    pub const fn new(min: f32, max: f32) -> Self {
+        if min <= max { Scale { min, max } } else { Scale { min: max, max: min } }
+    }
+
+    /// Smallest scale containing all values, `0.0..1.0` for an empty series.
+    pub fn fit(values: &[f32]) -> Self {
+        let mut values = values.iter().copied().filter(|value| value.is_finite());
+
+        match values.next() {
+            Some(first) => {
+                let (min, max) = values.fold((first, first), |(min, max), value| (min.min(value), max.max(value)));
+                Scale { min, max }
+            }
+            None => Scale { min: 0.0, max: 1.0 },
+        }
+    }
+
+    pub fn including(self, value: f32) -> Self {
+        Scale { min: self.min.min(value), max: self.max.max(value) }
+    }
+
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    /// Position of the value in the range, from 0.0 at `min` to 1.0 at `max`.
+    pub fn fraction(&self, value: f32) -> f32 {
+        if self.max > self.min {
+            ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+        } else {
+            0.5
+        }
+    }
+
+    /// Scales the fraction of the value to `0..=length` pixels.
+    pub(crate) fn pixels(&self, value: f32, length: u32) -> u32 {
+        // f32 rounds large lengths up.
+        ((self.fraction(value) * length as f32 + 0.5) as u32).min(length)
+    }
+}
+
+/// Chromatic for values above the threshold, `color` otherwise.
+pub(crate) fn value_color(value: f32, threshold: Option<f32>, color: ThreeColor) -> ThreeColor {
+    match threshold {
+        Some(threshold) if value > threshold => ThreeColor::Chromatic,
+        _ => color,
+    }
+}
@@ -0,0 +1,114 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+
+use crate::epd::three_color::ThreeColor;
+use super::scale::{value_color, Scale};
+
+/// Line chart of a data series without axes, spread over the width of the bounds.
+///
+/// Segments ending in a value above the threshold are drawn chromatic.
+#[derive(Copy, Clone)]
+pub struct Sparkline<'a> {
+    values: &'a [f32],
+    bounds: Rectangle,
+    scale: Scale,
+    threshold: Option<f32>,
+    color: ThreeColor,
+    stroke_width: u32,
+}
+
+impl<'a> Sparkline<'a> {
+    pub fn new(values: &'a [f32], bounds: Rectangle) -> Self {
+        Sparkline {
+            values,
+            bounds,
+            scale: Scale::fit(values),
+            threshold: None,
+            color: ThreeColor::Black,
+            stroke_width: 1,
+        }
+    }
+
+    pub fn with_bounds(mut self, bounds: Rectangle) -> Self {
+        self.bounds = bounds;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    pub fn with_color(mut self, color: ThreeColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_stroke_width(mut self, stroke_width: u32) -> Self {
+        self.stroke_width = stroke_width.max(1);
+        self
+    }
+
+    fn point(&self, index: usize) -> Point {
+        let Size { width, height } = self.bounds.size;
+        let steps = self.values.len().saturating_sub(1).max(1) as u64;
+        let x = (index as u64 * width.saturating_sub(1) as u64 / steps) as u32;
+        let y = height.saturating_sub(1) - self.scale.pixels(self.values[index], height.saturating_sub(1));
+
+        Point::new(self.bounds.top_left.x.saturating_add_unsigned(x), self.bounds.top_left.y.saturating_add_unsigned(y))
+    }
+}
+
+impl Dimensions for Sparkline<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        self.bounds
+    }
+}
+
+impl Transform for Sparkline<'_> {
+    fn translate(&self, by: Point) -> Self {
+        let mut sparkline = *self;
+        sparkline.translate_mut(by);
+        sparkline
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.bounds.top_left += by;
+        self
+    }
+}
+
+impl Drawable for Sparkline<'_> {
+    type Color = ThreeColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        if self.values.len() == 1 {
+            let color = value_color(self.values[0], self.threshold, self.color);
+            let start = self.point(0);
+            let end = Point::new(self.bounds.top_left.x.saturating_add_unsigned(self.bounds.size.width.saturating_sub(1)), start.y);
+
+            return Line::new(start, end)
+                .into_styled(PrimitiveStyle::with_stroke(color, self.stroke_width))
+                .draw(target);
+        }
+
+        for index in 1..self.values.len() {
+            let color = value_color(self.values[index], self.threshold, self.color);
+
+            Line::new(self.point(index - 1), self.point(index))
+                .into_styled(PrimitiveStyle::with_stroke(color, self.stroke_width))
+                .draw(target)?;
+        }
+
+        Ok(())
+    }
+}
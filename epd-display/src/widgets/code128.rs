@@ -0,0 +1,106 @@
+use super::barcode::{BarcodeError, Bars};
+
+// Bar and space widths of the symbol values, starting with a bar.
+const PATTERNS: [[u8; 6]; 106] = [
+    [2, 1, 2, 2, 2, 2], [2, 2, 2, 1, 2, 2], [2, 2, 2, 2, 2, 1], [1, 2, 1, 2, 2, 3], [1, 2, 1, 3, 2, 2],
+    [1, 3, 1, 2, 2, 2], [1, 2, 2, 2, 1, 3], [1, 2, 2, 3, 1, 2], [1, 3, 2, 2, 1, 2], [2, 2, 1, 2, 1, 3],
+    [2, 2, 1, 3, 1, 2], [2, 3, 1, 2, 1, 2], [1, 1, 2, 2, 3, 2], [1, 2, 2, 1, 3, 2], [1, 2, 2, 2, 3, 1],
+    [1, 1, 3, 2, 2, 2], [1, 2, 3, 1, 2, 2], [1, 2, 3, 2, 2, 1], [2, 2, 3, 2, 1, 1], [2, 2, 1, 1, 3, 2],
+    [2, 2, 1, 2, 3, 1], [2, 1, 3, 2, 1, 2], [2, 2, 3, 1, 1, 2], [3, 1, 2, 1, 3, 1], [3, 1, 1, 2, 2, 2],
+    [3, 2, 1, 1, 2, 2], [3, 2, 1, 2, 2, 1], [3, 1, 2, 2, 1, 2], [3, 2, 2, 1, 1, 2], [3, 2, 2, 2, 1, 1],
+    [2, 1, 2, 1, 2, 3], [2, 1, 2, 3, 2, 1], [2, 3, 2, 1, 2, 1], [1, 1, 1, 3, 2, 3], [1, 3, 1, 1, 2, 3],
+    [1, 3, 1, 3, 2, 1], [1, 1, 2, 3, 1, 3], [1, 3, 2, 1, 1, 3], [1, 3, 2, 3, 1, 1], [2, 1, 1, 3, 1, 3],
+    [2, 3, 1, 1, 1, 3], [2, 3, 1, 3, 1, 1], [1, 1, 2, 1, 3, 3], [1, 1, 2, 3, 3, 1], [1, 3, 2, 1, 3, 1],
+    [1, 1, 3, 1, 2, 3], [1, 1, 3, 3, 2, 1], [1, 3, 3, 1, 2, 1], [3, 1, 3, 1, 2, 1], [2, 1, 1, 3, 3, 1],
+    [2, 3, 1, 1, 3, 1], [2, 1, 3, 1, 1, 3], [2, 1, 3, 3, 1, 1], [2, 1, 3, 1, 3, 1], [3, 1, 1, 1, 2, 3],
+    [3, 1, 1, 3, 2, 1], [3, 3, 1, 1, 2, 1], [3, 1, 2, 1, 1, 3], [3, 1, 2, 3, 1, 1], [3, 3, 2, 1, 1, 1],
+    [3, 1, 4, 1, 1, 1], [2, 2, 1, 4, 1, 1], [4, 3, 1, 1, 1, 1], [1, 1, 1, 2, 2, 4], [1, 1, 1, 4, 2, 2],
+    [1, 2, 1, 1, 2, 4], [1, 2, 1, 4, 2, 1], [1, 4, 1, 1, 2, 2], [1, 4, 1, 2, 2, 1], [1, 1, 2, 2, 1, 4],
+    [1, 1, 2, 4, 1, 2], [1, 2, 2, 1, 1, 4], [1, 2, 2, 4, 1, 1], [1, 4, 2, 1, 1, 2], [1, 4, 2, 2, 1, 1],
+    [2, 4, 1, 2, 1, 1], [2, 2, 1, 1, 1, 4], [4, 1, 3, 1, 1, 1], [2, 4, 1, 1, 1, 2], [1, 3, 4, 1, 1, 1],
+    [1, 1, 1, 2, 4, 2], [1, 2, 1, 1, 4, 2], [1, 2, 1, 2, 4, 1], [1, 1, 4, 2, 1, 2], [1, 2, 4, 1, 1, 2],
+    [1, 2, 4, 2, 1, 1], [4, 1, 1, 2, 1, 2], [4, 2, 1, 1, 1, 2], [4, 2, 1, 2, 1, 1], [2, 1, 2, 1, 4, 1],
+    [2, 1, 4, 1, 2, 1], [4, 1, 2, 1, 2, 1], [1, 1, 1, 1, 4, 3], [1, 1, 1, 3, 4, 1], [1, 3, 1, 1, 4, 1],
+    [1, 1, 4, 1, 1, 3], [1, 1, 4, 3, 1, 1], [4, 1, 1, 1, 1, 3], [4, 1, 1, 3, 1, 1], [1, 1, 3, 1, 4, 1],
+    [1, 1, 4, 1, 3, 1], [3, 1, 1, 1, 4, 1], [4, 1, 1, 1, 3, 1], [2, 1, 1, 4, 1, 2], [2, 1, 1, 2, 1, 4],
+    [2, 1, 1, 2, 3, 2],
+];
+const STOP_PATTERN: [u8; 7] = [2, 3, 3, 1, 1, 1, 2];
+
+const CODE_C: u8 = 99;
+const CODE_B: u8 = 100;
+const START_B: u8 = 104;
+const START_C: u8 = 105;
+
+#[derive(Copy, Clone, PartialEq)]
+enum CodeSet {
+    B,
+    C,
+}
+
+struct Encoder<'a> {
+    bars: &'a mut Bars,
+    checksum: u32,
+    position: u32,
+}
+
+impl Encoder<'_> {
+    fn push(&mut self, value: u8) -> Result<(), BarcodeError> {
+        self.checksum += value as u32 * self.position.max(1);
+        self.position += 1;
+        self.bars.push_widths(&PATTERNS[value as usize])
+    }
+}
+
+/// Encodes printable ASCII text, runs of digits are packed into code set C.
+pub fn encode(text: &str, bars: &mut Bars) -> Result<(), BarcodeError> {
+    let bytes = text.as_bytes();
+
+    if bytes.is_empty() {
+        return Err(BarcodeError::InvalidLength);
+    }
+
+    if !bytes.iter().all(|b| (b' '..=b'~').contains(b)) {
+        return Err(BarcodeError::InvalidCharacter);
+    }
+
+    let digits_at = |i: usize| bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let leading_digits = digits_at(0);
+    let mut code_set = if leading_digits >= 4 || (leading_digits == bytes.len() && leading_digits % 2 == 0) {
+        CodeSet::C
+    } else {
+        CodeSet::B
+    };
+
+    let mut encoder = Encoder { bars, checksum: 0, position: 0 };
+    encoder.push(if code_set == CodeSet::C { START_C } else { START_B })?;
+
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let digits = digits_at(i);
+
+        match code_set {
+            CodeSet::C if digits >= 2 => {
+                encoder.push((bytes[i] - b'0') * 10 + (bytes[i + 1] - b'0'))?;
+                i += 2;
+            }
+            CodeSet::C => {
+                encoder.push(CODE_B)?;
+                code_set = CodeSet::B;
+            }
+            CodeSet::B if digits >= 4 && digits % 2 == 0 => {
+                encoder.push(CODE_C)?;
+                code_set = CodeSet::C;
+            }
+            CodeSet::B => {
+                encoder.push(bytes[i] - b' ')?;
+                i += 1;
+            }
+        }
+    }
+
+    let checksum = (encoder.checksum % 103) as u8;
+    encoder.push(checksum)?;
+    encoder.bars.push_widths(&STOP_PATTERN)
+}
@@ -0,0 +1,77 @@
+use super::barcode::{BarcodeError, Bars};
+
+// Bar and space widths of the left hand odd parity digits, starting with a space.
+const L_PATTERNS: [[u8; 4]; 10] = [
+    [3, 2, 1, 1], [2, 2, 2, 1], [2, 1, 2, 2], [1, 4, 1, 1], [1, 1, 3, 2],
+    [1, 2, 3, 1], [1, 1, 1, 4], [1, 3, 1, 2], [1, 2, 1, 3], [3, 1, 1, 2],
+];
+
+// Even parity patterns of digits 2 to 7 selected by the first digit, bit 5 is digit 2.
+const PARITY: [u8; 10] = [
+    0b000000, 0b001011, 0b001101, 0b001110, 0b010011,
+    0b011001, 0b011100, 0b010101, 0b010110, 0b011010,
+];
+
+const GUARD: [u8; 3] = [1, 1, 1];
+const CENTER_GUARD: [u8; 5] = [1, 1, 1, 1, 1];
+
+/// Encodes 12 digits, or 13 digits including the check digit, and returns all 13 digits.
+pub fn encode(text: &str, bars: &mut Bars) -> Result<[u8; 13], BarcodeError> {
+    let bytes = text.as_bytes();
+
+    if bytes.len() != 12 && bytes.len() != 13 {
+        return Err(BarcodeError::InvalidLength);
+    }
+
+    if !bytes.iter().all(u8::is_ascii_digit) {
+        return Err(BarcodeError::InvalidCharacter);
+    }
+
+    let mut digits = [0; 13];
+
+    for (digit, byte) in digits.iter_mut().zip(bytes) {
+        *digit = byte - b'0';
+    }
+
+    let check_digit = check_digit(&digits[..12]);
+
+    if bytes.len() == 13 && digits[12] != check_digit {
+        return Err(BarcodeError::InvalidCheckDigit);
+    }
+
+    digits[12] = check_digit;
+
+    let parity = PARITY[digits[0] as usize];
+    bars.push_widths(&GUARD)?;
+
+    for (i, &digit) in digits[1..7].iter().enumerate() {
+        let widths = L_PATTERNS[digit as usize];
+
+        if parity & (0b100000 >> i) != 0 {
+            let mut reversed = widths;
+            reversed.reverse();
+            bars.push_spaced_widths(&reversed)?;
+        } else {
+            bars.push_spaced_widths(&widths)?;
+        }
+    }
+
+    bars.push_spaced_widths(&CENTER_GUARD)?;
+
+    for &digit in &digits[7..] {
+        bars.push_widths(&L_PATTERNS[digit as usize])?;
+    }
+
+    bars.push_widths(&GUARD)?;
+
+    Ok(digits)
+}
+
+fn check_digit(digits: &[u8]) -> u8 {
+    let sum: u32 = digits.iter()
+        .enumerate()
+        .map(|(i, &digit)| digit as u32 * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+
+    ((10 - sum % 10) % 10) as u8
+}
@@ -0,0 +1,5 @@
+pub mod barcode;
+pub mod chart;
+mod code128;
+mod ean13;
+pub mod qr_code;
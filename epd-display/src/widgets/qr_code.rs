@@ -0,0 +1,153 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use qrcodegen_no_heap::{QrCode as QrEncoder, QrCodeEcc, Version};
+
+use crate::epd::three_color::ThreeColor;
+
+// Version 10 has 57x57 modules and holds at least 213 bytes with medium error correction.
+const MAX_VERSION: Version = Version::new(10);
+const BUFFER_LEN: usize = MAX_VERSION.buffer_len();
+const DEFAULT_QUIET_ZONE: u32 = 4;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum QrCodeError {
+    DataTooLong,
+}
+
+/// QR code drawn with square modules of `scale` pixels.
+///
+/// The light modules and the quiet zone around the code are drawn white.
+#[derive(Clone)]
+pub struct QrCode {
+    modules: [u8; BUFFER_LEN],
+    size: u32,
+    top_left: Point,
+    scale: u32,
+    quiet_zone: u32,
+    color: ThreeColor,
+}
+
+impl QrCode {
+    pub fn new(text: &str, top_left: Point) -> Result<Self, QrCodeError> {
+        let mut temp_buffer = [0; BUFFER_LEN];
+        let mut out_buffer = [0; BUFFER_LEN];
+
+        let encoded = QrEncoder::encode_text(
+            text,
+            &mut temp_buffer,
+            &mut out_buffer,
+            QrCodeEcc::Medium,
+            Version::MIN,
+            MAX_VERSION,
+            None,
+            true,
+        ).map_err(|_| QrCodeError::DataTooLong)?;
+
+        let size = encoded.size();
+        let mut modules = [0; BUFFER_LEN];
+
+        for y in 0..size {
+            for x in 0..size {
+                if encoded.get_module(x, y) {
+                    let index = (y * size + x) as usize;
+                    modules[index / 8] |= 0x80 >> (index % 8);
+                }
+            }
+        }
+
+        Ok(QrCode {
+            modules,
+            size: size as u32,
+            top_left,
+            scale: 1,
+            quiet_zone: DEFAULT_QUIET_ZONE,
+            color: ThreeColor::Black,
+        })
+    }
+
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn with_quiet_zone(mut self, quiet_zone: u32) -> Self {
+        self.quiet_zone = quiet_zone;
+        self
+    }
+
+    pub fn with_color(mut self, color: ThreeColor) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Uses the largest scale for which the code including the quiet zone fits into `size`, `None` if
+    /// it doesn't fit even with a scale of 1.
+    pub fn scale_to_fit(self, size: Size) -> Option<Self> {
+        let modules = self.size + 2 * self.quiet_zone;
+
+        match size.width.min(size.height) / modules {
+            0 => None,
+            scale => Some(self.with_scale(scale)),
+        }
+    }
+
+    /// Number of modules per side, without the quiet zone.
+    pub fn module_count(&self) -> u32 {
+        self.size
+    }
+
+    pub fn module(&self, x: u32, y: u32) -> bool {
+        if x >= self.size || y >= self.size {
+            return false;
+        }
+
+        let index = (y * self.size + x) as usize;
+        self.modules[index / 8] & (0x80 >> (index % 8)) != 0
+    }
+}
+
+impl Dimensions for QrCode {
+    fn bounding_box(&self) -> Rectangle {
+        let side = (self.size + 2 * self.quiet_zone) * self.scale;
+        Rectangle::new(self.top_left, Size::new(side, side))
+    }
+}
+
+impl Transform for QrCode {
+    fn translate(&self, by: Point) -> Self {
+        let mut qr_code = self.clone();
+        qr_code.translate_mut(by);
+        qr_code
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl Drawable for QrCode {
+    type Color = ThreeColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = ThreeColor>,
+    {
+        target.fill_solid(&self.bounding_box(), ThreeColor::White)?;
+
+        let origin = self.top_left + Point::new_equal((self.quiet_zone * self.scale) as i32);
+        let module_size = Size::new_equal(self.scale);
+
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.module(x, y) {
+                    let offset = Point::new((x * self.scale) as i32, (y * self.scale) as i32);
+                    target.fill_solid(&Rectangle::new(origin + offset, module_size), self.color)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
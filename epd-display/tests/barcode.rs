@@ -0,0 +1,182 @@
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::widgets::barcode::{Barcode, BarcodeError};
+
+use epd_display::simulator::SimulatedDisplay;
+
+const ORIENTATIONS: [DisplayOrientation; 4] = [
+    DisplayOrientation::Portrait,
+    DisplayOrientation::Landscape,
+    DisplayOrientation::PortraitFlipped,
+    DisplayOrientation::LandscapeFlipped,
+];
+
+const CODE128: [&str; 106] = [
+    "11011001100", "11001101100", "11001100110", "10010011000", "10010001100", "10001001100",
+    "10011001000", "10011000100", "10001100100", "11001001000", "11001000100", "11000100100",
+    "10110011100", "10011011100", "10011001110", "10111001100", "10011101100", "10011100110",
+    "11001110010", "11001011100", "11001001110", "11011100100", "11001110100", "11101101110",
+    "11101001100", "11100101100", "11100100110", "11101100100", "11100110100", "11100110010",
+    "11011011000", "11011000110", "11000110110", "10100011000", "10001011000", "10001000110",
+    "10110001000", "10001101000", "10001100010", "11010001000", "11000101000", "11000100010",
+    "10110111000", "10110001110", "10001101110", "10111011000", "10111000110", "10001110110",
+    "11101110110", "11010001110", "11000101110", "11011101000", "11011100010", "11011101110",
+    "11101011000", "11101000110", "11100010110", "11101101000", "11101100010", "11100011010",
+    "11101111010", "11001000010", "11110001010", "10100110000", "10100001100", "10010110000",
+    "10010000110", "10000101100", "10000100110", "10110010000", "10110000100", "10011010000",
+    "10011000010", "10000110100", "10000110010", "11000010010", "11001010000", "11110111010",
+    "11000010100", "10001111010", "10100111100", "10010111100", "10010011110", "10111100100",
+    "10011110100", "10011110010", "11110100100", "11110010100", "11110010010", "11011011110",
+    "11011110110", "11110110110", "10101111000", "10100011110", "10001011110", "10111101000",
+    "10111100010", "11110101000", "11110100010", "10111011110", "10111101110", "11101011110",
+    "11110101110", "11010000100", "11010010000", "11010011100",
+];
+const CODE128_STOP: &str = "1100011101011";
+
+const EAN_L: [&str; 10] = [
+    "0001101", "0011001", "0010011", "0111101", "0100011",
+    "0110001", "0101111", "0111011", "0110111", "0001011",
+];
+const EAN_PARITY: [&str; 10] = [
+    "LLLLLL", "LLGLGG", "LLGGLG", "LLGGGL", "LGLLGG",
+    "LGGLLG", "LGGGLL", "LGLGLG", "LGLGGL", "LGGLGL",
+];
+
+/// Reads the modules of a drawn barcode along a row through the bars, without the quiet zones.
+fn scan(display: &SimulatedDisplay, barcode: &Barcode, module_width: u32) -> String {
+    let area = barcode.bounding_box();
+    let y = area.top_left.y + 10;
+    let modules: String = (0..area.size.width / module_width)
+        .map(|module| area.top_left.x + (module * module_width + module_width / 2) as i32)
+        .map(|x| if display.pixel(Point::new(x, y)) == Some(ThreeColor::Black) { '1' } else { '0' })
+        .collect();
+
+    modules.trim_matches('0').to_string()
+}
+
+fn decode_code128(modules: &str) -> String {
+    let symbols = modules.strip_suffix(CODE128_STOP).expect("stop pattern");
+    assert_eq!(symbols.len() % 11, 0);
+
+    let values: Vec<usize> = (0..symbols.len() / 11)
+        .map(|i| CODE128.iter().position(|p| *p == &symbols[i * 11..(i + 1) * 11]).expect("symbol"))
+        .collect();
+    let (checksum, values) = values.split_last().unwrap();
+    let sum: usize = values.iter().enumerate().map(|(i, v)| v * i.max(1)).sum();
+    assert_eq!(sum % 103, *checksum);
+
+    let mut code_c = match values[0] {
+        104 => false,
+        105 => true,
+        start => panic!("unexpected start symbol {start}"),
+    };
+    let mut text = String::new();
+
+    for &value in &values[1..] {
+        match (value, code_c) {
+            (99, false) => code_c = true,
+            (100, true) => code_c = false,
+            (value, true) => text.push_str(&format!("{value:02}")),
+            (value, false) => text.push((b' ' + value as u8) as char),
+        }
+    }
+
+    text
+}
+
+fn decode_ean13(modules: &str) -> String {
+    assert_eq!(modules.len(), 95);
+    assert_eq!(&modules[..3], "101");
+    assert_eq!(&modules[45..50], "01010");
+    assert_eq!(&modules[92..], "101");
+
+    let invert = |s: &str| s.chars().map(|c| if c == '1' { '0' } else { '1' }).collect::<String>();
+    let mut parity = String::new();
+    let mut digits = String::new();
+
+    for i in 0..6 {
+        let symbol = &modules[3 + i * 7..10 + i * 7];
+        let even: String = invert(symbol).chars().rev().collect();
+
+        if let Some(digit) = EAN_L.iter().position(|p| *p == symbol) {
+            parity.push('L');
+            digits.push_str(&digit.to_string());
+        } else {
+            let digit = EAN_L.iter().position(|p| *p == even).expect("left digit");
+            parity.push('G');
+            digits.push_str(&digit.to_string());
+        }
+    }
+
+    for i in 0..6 {
+        let symbol = invert(&modules[50 + i * 7..57 + i * 7]);
+        let digit = EAN_L.iter().position(|p| *p == symbol).expect("right digit");
+        digits.push_str(&digit.to_string());
+    }
+
+    let first = EAN_PARITY.iter().position(|p| *p == parity).expect("parity");
+
+    format!("{first}{digits}")
+}
+
+fn draw(orientation: DisplayOrientation, barcode: impl Fn(u32) -> Barcode<'static>) -> (SimulatedDisplay, Barcode<'static>) {
+    let mut display = SimulatedDisplay::simulated();
+    display.set_orientation(orientation);
+
+    let barcode = barcode(display.width() as u32);
+    barcode.draw(&mut display).unwrap();
+
+    (display, barcode)
+}
+
+#[test]
+fn code128_decodes_in_all_orientations() {
+    for text in ["EPD-2024", "12345678", "Room 4711", "x"] {
+        for orientation in ORIENTATIONS {
+            let (display, barcode) = draw(orientation, |width| {
+                Barcode::code128(text, Point::new(0, 4)).unwrap().scale_to_fit(width)
+            });
+            let module_width = barcode.bounding_box().size.width / barcode.module_count();
+
+            assert!(barcode.bounding_box().size.width <= display.width() as u32);
+            assert_eq!(decode_code128(&scan(&display, &barcode, module_width)), text, "{orientation:?}");
+        }
+    }
+}
+
+#[test]
+fn code128_packs_digits() {
+    let digits = Barcode::code128("123456789012", Point::zero()).unwrap();
+    let letters = Barcode::code128("ABCDEFGHIJKL", Point::zero()).unwrap();
+
+    assert!(digits.bars().len() < letters.bars().len());
+}
+
+#[test]
+fn code128_rejects_invalid_text() {
+    assert_eq!(Barcode::code128("", Point::zero()).err(), Some(BarcodeError::InvalidLength));
+    assert_eq!(Barcode::code128("ä", Point::zero()).err(), Some(BarcodeError::InvalidCharacter));
+    assert_eq!(Barcode::code128(&"A".repeat(64), Point::zero()).err(), Some(BarcodeError::TooLong));
+}
+
+#[test]
+fn ean13_decodes_in_all_orientations() {
+    for orientation in ORIENTATIONS {
+        let (display, barcode) = draw(orientation, |width| {
+            Barcode::ean13("400638133393", Point::new(0, 4)).unwrap().scale_to_fit(width)
+        });
+        let module_width = barcode.bounding_box().size.width / barcode.module_count();
+
+        assert_eq!(decode_ean13(&scan(&display, &barcode, module_width)), "4006381333931", "{orientation:?}");
+    }
+}
+
+#[test]
+fn ean13_validates_digits() {
+    assert!(Barcode::ean13("4006381333931", Point::zero()).is_ok());
+    assert_eq!(Barcode::ean13("4006381333932", Point::zero()).err(), Some(BarcodeError::InvalidCheckDigit));
+    assert_eq!(Barcode::ean13("40063813339", Point::zero()).err(), Some(BarcodeError::InvalidLength));
+    assert_eq!(Barcode::ean13("40063813339x", Point::zero()).err(), Some(BarcodeError::InvalidCharacter));
+}
@@ -0,0 +1,90 @@
+use epd_display::epd::bitmap_buffer::BitmapBuffer;
+
+macro_rules! buffer {
+    ($width:expr, $height:expr) => {
+        BitmapBuffer::<$width, $height, { ($width as usize).div_ceil(8) * $height }>::new()
+    };
+}
+
+// Sets each pixel alone and checks that exactly this pixel reads back and only its bit is set.
+macro_rules! assert_pixels_independent {
+    ($width:expr, $height:expr) => {{
+        let mut bitmap = buffer!($width, $height);
+        let stride = ($width as usize).div_ceil(8);
+
+        for y in 0..$height {
+            for x in 0..$width {
+                bitmap.set_pixel(x, y);
+
+                let set: Vec<_> = (0..$height)
+                    .flat_map(|y| (0..$width).map(move |x| (x, y)))
+                    .filter(|&(x, y)| bitmap.get_pixel(x, y))
+                    .collect();
+                assert_eq!(set, [(x, y)], "width {}", $width);
+
+                let bytes: Vec<_> = bitmap.buffer.iter().enumerate().filter(|(_, byte)| **byte != 0).collect();
+                assert_eq!(bytes, [(y * stride + x / 8, &(0x80 >> (x % 8)))], "width {} at {:?}", $width, (x, y));
+
+                bitmap.clear_pixel(x, y);
+                assert!(bitmap.buffer.iter().all(|&byte| byte == 0));
+            }
+        }
+    }};
+}
+
+#[test]
+fn stride_is_rounded_up() {
+    assert_eq!(BitmapBuffer::<8, 3, 3>::STRIDE, 1);
+    assert_eq!(BitmapBuffer::<13, 3, 6>::STRIDE, 2);
+    assert_eq!(BitmapBuffer::<152, 296, { 19 * 296 }>::STRIDE, 19);
+    assert_eq!(buffer!(17, 4).buffer.len(), 12);
+}
+
+#[test]
+fn pixels_are_independent() {
+    assert_pixels_independent!(1, 3);
+    assert_pixels_independent!(7, 4);
+    assert_pixels_independent!(8, 4);
+    assert_pixels_independent!(13, 5);
+    assert_pixels_independent!(17, 3);
+    assert_pixels_independent!(24, 2);
+}
+
+#[test]
+fn rows_start_at_byte_boundaries() {
+    let mut bitmap = buffer!(13, 4);
+
+    for y in 0..4 {
+        bitmap.set_pixel(0, y);
+    }
+
+    assert_eq!(bitmap.buffer, [0x80, 0x00, 0x80, 0x00, 0x80, 0x00, 0x80, 0x00]);
+}
+
+#[test]
+fn pixels_outside_are_ignored() {
+    let mut bitmap = buffer!(13, 4);
+
+    bitmap.set_pixel(13, 0);
+    bitmap.set_pixel(0, 4);
+    assert!(bitmap.buffer.iter().all(|&byte| byte == 0));
+    assert!(!bitmap.get_pixel(13, 0));
+
+    bitmap.fill(0xff);
+    bitmap.clear_pixel(13, 0);
+    bitmap.clear_pixel(0, 4);
+    assert!(bitmap.buffer.iter().all(|&byte| byte == 0xff));
+    assert!(!bitmap.get_pixel(0, 4));
+}
+
+#[test]
+fn areas_are_copied_by_rows() {
+    let mut bitmap = buffer!(24, 3);
+
+    bitmap.copy_area(8, 1, 16, &[0x01, 0x02, 0x03, 0x04]);
+    assert_eq!(bitmap.buffer, [0, 0, 0, 0, 0x01, 0x02, 0, 0x03, 0x04]);
+
+    // Bytes right of and below the bitmap are dropped.
+    bitmap.copy_area(16, 2, 16, &[0xf0, 0x0f, 0xff, 0xff]);
+    assert_eq!(bitmap.buffer, [0, 0, 0, 0, 0x01, 0x02, 0, 0x03, 0xf0]);
+}
@@ -0,0 +1,163 @@
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+use epd_display::epd::maintenance::MaintenancePolicy;
+use epd_display::simulator::ssd16xx::{BusyTiming, EmulatedDisplay, Ssd16xx};
+use epd_display::simulator::time::{block_on, elapsed_since, run_for};
+
+// Reset timing of the driver: RST high for 20 ms, low for 2 ms and 200 ms until the controller is up.
+const HW_RESET: Duration = Duration::from_millis(222);
+
+const TIMING: BusyTiming = BusyTiming { reset: Duration::from_millis(12), refresh: Duration::from_millis(1234) };
+
+fn display(timing: BusyTiming) -> (Ssd16xx, EmulatedDisplay) {
+    let controller = Ssd16xx::new(152, 296);
+    controller.set_timing(timing);
+    let display = controller.display();
+
+    (controller, display)
+}
+
+// Virtual time the future takes.
+async fn measure(future: impl Future) -> Duration {
+    let start = Instant::now();
+    future.await;
+    elapsed_since(start)
+}
+
+#[test]
+fn init_waits_for_reset() {
+    let (controller, mut display) = display(TIMING);
+
+    let elapsed = block_on(measure(display.init()));
+
+    // BUSY is polled every 10 ms.
+    assert_eq!(elapsed, HW_RESET + Duration::from_millis(20));
+    assert_eq!(controller.violations(), 0);
+}
+
+#[test]
+fn refresh_waits_for_panel() {
+    let (controller, mut display) = display(TIMING);
+
+    block_on(display.init());
+    let elapsed = block_on(measure(display.refresh()));
+
+    assert_eq!(elapsed, Duration::from_millis(1240));
+    assert_eq!(controller.activations(), 1);
+    assert_eq!(controller.violations(), 0);
+}
+
+#[test]
+fn full_cycle_with_panel_timing() {
+    let (controller, mut display) = display(BusyTiming::EPD_2IN66B);
+
+    let elapsed = block_on(measure(async {
+        display.init().await;
+        display.refresh().await;
+        display.sleep().await;
+    }));
+
+    assert_eq!(elapsed, HW_RESET + Duration::from_millis(10) + Duration::from_secs(15));
+    assert!(controller.is_sleeping());
+    assert_eq!(controller.violations(), 0);
+}
+
+#[test]
+fn stuck_busy_blocks_refresh() {
+    let (controller, mut display) = display(TIMING);
+
+    block_on(display.init());
+    controller.set_stuck_busy(true);
+
+    assert!(run_for(Duration::from_secs(60), display.refresh()).is_none());
+    assert_eq!(controller.activations(), 1);
+    assert_eq!(controller.violations(), 0);
+}
+
+#[test]
+fn timeout_cancels_refresh() {
+    let (controller, mut display) = display(TIMING);
+
+    block_on(display.init());
+    controller.set_stuck_busy(true);
+
+    let (result, elapsed) = block_on(async {
+        let start = Instant::now();
+        let result = with_timeout(Duration::from_secs(30), display.refresh()).await;
+        (result, elapsed_since(start))
+    });
+
+    assert!(result.is_err());
+    assert_eq!(elapsed, Duration::from_secs(30));
+
+    // The driver can be used again once the panel recovers.
+    controller.set_stuck_busy(false);
+    block_on(async {
+        display.init().await;
+        display.refresh().await;
+    });
+
+    assert_eq!(controller.activations(), 2);
+    assert_eq!(controller.violations(), 0);
+}
+
+#[test]
+fn cleaning_becomes_due_with_refreshes() {
+    let (controller, mut display) = display(TIMING);
+    display.set_maintenance_policy(MaintenancePolicy::after_refreshes(2));
+
+    block_on(async {
+        display.init().await;
+        display.refresh().await;
+        display.refresh().await;
+    });
+
+    assert_eq!(controller.activations(), 2);
+    assert_eq!(display.maintenance().refreshes(), 2);
+
+    block_on(display.refresh());
+
+    // Black, white and the image, the refresh after the cleaning isn't counted.
+    assert_eq!(controller.activations(), 5);
+    assert_eq!(display.maintenance().refreshes(), 0);
+
+    block_on(async {
+        display.refresh().await;
+        display.refresh().await;
+    });
+
+    assert_eq!(controller.activations(), 7);
+    assert_eq!(display.maintenance().refreshes(), 2);
+
+    block_on(display.refresh());
+
+    assert_eq!(controller.activations(), 10);
+    assert_eq!(controller.violations(), 0);
+}
+
+#[test]
+fn cleaning_becomes_due_with_time() {
+    let (controller, mut display) = display(TIMING);
+    display.set_maintenance_policy(MaintenancePolicy::NEVER.or_after(Duration::from_secs(60 * 60)));
+
+    block_on(async {
+        display.init().await;
+        display.clean().await;
+    });
+
+    let activations = controller.activations();
+    block_on(async {
+        Timer::after(Duration::from_secs(59 * 60)).await;
+        display.refresh().await;
+    });
+
+    assert_eq!(controller.activations(), activations + 1);
+
+    block_on(async {
+        Timer::after(Duration::from_secs(2 * 60)).await;
+        display.refresh().await;
+    });
+
+    // Black, white and the image.
+    assert_eq!(controller.activations(), activations + 4);
+    assert_eq!(controller.violations(), 0);
+}
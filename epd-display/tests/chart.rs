@@ -0,0 +1,174 @@
+use std::convert::Infallible;
+
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use embedded_graphics::geometry::AnchorX;
+use embedded_graphics::primitives::Rectangle;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::simulator::SimulatedDisplay;
+use epd_display::widgets::chart::bar_chart::BarChart;
+use epd_display::widgets::chart::scale::Scale;
+use epd_display::widgets::chart::sparkline::Sparkline;
+
+const BOUNDS: Rectangle = Rectangle::new(Point::new(2, 3), Size::new(12, 9));
+
+fn draw(chart: impl Drawable<Color = ThreeColor>) -> SimulatedDisplay {
+    let mut display = SimulatedDisplay::simulated();
+    chart.draw(&mut display).unwrap();
+    display
+}
+
+/// Colors of the pixels in a column of the bounds, from top to bottom.
+fn column(display: &SimulatedDisplay, x: i32) -> String {
+    (0..BOUNDS.size.height as i32)
+        .map(|y| match display.pixel(BOUNDS.top_left + Point::new(x, y)) {
+            Some(ThreeColor::Black) => 'B',
+            Some(ThreeColor::Chromatic) => 'R',
+            _ => '.',
+        })
+        .collect()
+}
+
+fn is_blank(display: &SimulatedDisplay) -> bool {
+    display.bounding_box().points().all(|point| display.pixel(point) == Some(ThreeColor::White))
+}
+
+/// Large target that records the filled areas and the rightmost pixel.
+#[derive(Default)]
+struct Recorder {
+    areas: Vec<Rectangle>,
+    right: i32,
+}
+
+impl OriginDimensions for Recorder {
+    fn size(&self) -> Size {
+        Size::new_equal(i32::MAX as u32)
+    }
+}
+
+impl DrawTarget for Recorder {
+    type Color = ThreeColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Infallible>
+    where
+        I: IntoIterator<Item = Pixel<ThreeColor>>,
+    {
+        for Pixel(point, _) in pixels {
+            self.right = self.right.max(point.x);
+        }
+
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, _color: ThreeColor) -> Result<(), Infallible> {
+        self.areas.push(*area);
+        Ok(())
+    }
+}
+
+#[test]
+fn scales_bars_to_the_bounds() {
+    let display = draw(BarChart::new(&[1.0, 2.0, 4.0], BOUNDS));
+
+    // Bars of 2 of the 4 pixels per value, with the base line at the bottom.
+    assert_eq!(column(&display, 0), "......BBB");
+    assert_eq!(column(&display, 1), "......BBB");
+    assert_eq!(column(&display, 2), "........B");
+    assert_eq!(column(&display, 4), "....BBBBB");
+    assert_eq!(column(&display, 8), "BBBBBBBBB");
+    assert_eq!(column(&display, 11), "........B");
+}
+
+#[test]
+fn draws_bars_below_the_base_line() {
+    let display = draw(BarChart::new(&[-4.0, 4.0], BOUNDS).with_spacing(0));
+
+    assert_eq!(column(&display, 0), "....BBBBB");
+    assert_eq!(column(&display, 6), "BBBBB....");
+}
+
+#[test]
+fn fills_bars_above_the_threshold_chromatic() {
+    let display = draw(BarChart::new(&[1.0, 2.0, 4.0], BOUNDS).with_threshold(1.5));
+
+    assert_eq!(column(&display, 0), "......BBB");
+    assert_eq!(column(&display, 4), "....RRRRB");
+    assert_eq!(column(&display, 8), "RRRRRRRRB");
+}
+
+#[test]
+fn draws_nothing_without_values() {
+    assert!(is_blank(&draw(BarChart::new(&[], BOUNDS))));
+    assert!(is_blank(&draw(Sparkline::new(&[], BOUNDS))));
+}
+
+#[test]
+fn draws_a_single_value() {
+    let display = draw(BarChart::new(&[3.0], BOUNDS).with_spacing(0));
+    assert!((0..12).all(|x| column(&display, x) == "BBBBBBBBB"));
+
+    // The scale of a single value has no range, the value sits in the middle.
+    let display = draw(Sparkline::new(&[3.0], BOUNDS));
+    assert!((0..12).all(|x| column(&display, x) == "....B...."));
+}
+
+#[test]
+fn scales_sparklines_to_the_bounds() {
+    let display = draw(Sparkline::new(&[0.0, 8.0, 4.0], BOUNDS.resized_width(11, AnchorX::Left)));
+
+    assert_eq!(column(&display, 0), "........B");
+    assert_eq!(column(&display, 5), "B........");
+    assert_eq!(column(&display, 10), "....B....");
+}
+
+#[test]
+fn draws_sparkline_segments_above_the_threshold_chromatic() {
+    let display = draw(Sparkline::new(&[0.0, 8.0, 4.0], BOUNDS.resized_width(11, AnchorX::Left)).with_threshold(6.0));
+
+    assert_eq!(column(&display, 2), "....RR...");
+    assert_eq!(column(&display, 7), "..B......");
+}
+
+#[test]
+fn ignores_values_that_are_not_finite() {
+    let values = [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 4.0];
+
+    assert_eq!(Scale::fit(&values), Scale::new(4.0, 4.0));
+
+    let display = draw(BarChart::new(&values, BOUNDS).with_spacing(0));
+    assert_eq!(column(&display, 0), "........B");
+    assert_eq!(column(&display, 3), "BBBBBBBBB");
+    assert_eq!(column(&display, 6), "........B");
+    assert_eq!(column(&display, 9), "BBBBBBBBB");
+
+    // Not a number is drawn at the bottom, the infinities at the edges of the scale.
+    let display = draw(Sparkline::new(&values, BOUNDS).with_scale(Scale::new(0.0, 8.0)));
+    assert_eq!(column(&display, 0), ".......BB");
+    assert_eq!(column(&display, 3), "BB.......");
+    assert_eq!(column(&display, 7), "........B");
+    assert_eq!(column(&display, 11), "....B....");
+}
+
+#[test]
+fn spreads_bars_over_large_bounds() {
+    let values = [1.0; 48];
+    let bounds = Rectangle::new(Point::new(-5, 0), Size::new(200_000_000, 1000));
+    let mut recorder = Recorder::default();
+    BarChart::new(&values, bounds).draw(&mut recorder).unwrap();
+
+    let (bars, base_line) = recorder.areas.split_at(48);
+    assert!(bars.windows(2).all(|bars| bars[0].bottom_right().unwrap().x < bars[1].top_left.x));
+    assert_eq!(bars[47].bottom_right().unwrap().x, bounds.bottom_right().unwrap().x - 2);
+    assert_eq!(base_line, [Rectangle::new(Point::new(-5, 999), Size::new(200_000_000, 1))]);
+}
+
+#[test]
+fn spreads_sparklines_with_many_values() {
+    let values: Vec<f32> = (0..100_000).map(|i| (i % 7) as f32).collect();
+    let bounds = Rectangle::new(Point::new(-5, 0), Size::new(45_000, 100));
+    let mut recorder = Recorder::default();
+    Sparkline::new(&values, bounds).draw(&mut recorder).unwrap();
+
+    assert_eq!(recorder.right, bounds.bottom_right().unwrap().x);
+}
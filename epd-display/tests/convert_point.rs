@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::simulator::SimulatedDisplay;
+
+const ORIENTATIONS: [DisplayOrientation; 4] = [
+    DisplayOrientation::Portrait,
+    DisplayOrientation::Landscape,
+    DisplayOrientation::PortraitFlipped,
+    DisplayOrientation::LandscapeFlipped,
+];
+
+fn display(orientation: DisplayOrientation) -> SimulatedDisplay {
+    let mut display = SimulatedDisplay::simulated();
+    display.set_orientation(orientation);
+    display
+}
+
+fn panel() -> Rectangle {
+    display(DisplayOrientation::Portrait).bounding_box()
+}
+
+#[test]
+fn orientation_swaps_size() {
+    let panel = panel().size;
+
+    for orientation in ORIENTATIONS {
+        let size = display(orientation).bounding_box().size;
+        let expected = match orientation {
+            DisplayOrientation::Portrait | DisplayOrientation::PortraitFlipped => panel,
+            DisplayOrientation::Landscape | DisplayOrientation::LandscapeFlipped => Size::new(panel.height, panel.width),
+        };
+
+        assert_eq!(size, expected, "{:?}", orientation);
+    }
+}
+
+#[test]
+fn maps_display_onto_panel() {
+    let panel = panel();
+
+    for orientation in ORIENTATIONS {
+        let display = display(orientation);
+        let mut mapped = HashSet::new();
+
+        for point in display.bounding_box().points() {
+            let converted = display.convert_point(point);
+
+            assert!(panel.contains(converted), "{:?}: {:?} -> {:?}", orientation, point, converted);
+            assert!(mapped.insert(converted), "{:?}: {:?} mapped twice", orientation, converted);
+        }
+
+        assert_eq!(mapped.len() as u32, panel.size.width * panel.size.height, "{:?}", orientation);
+    }
+}
+
+#[test]
+fn keeps_neighbours_adjacent() {
+    for orientation in ORIENTATIONS {
+        let display = display(orientation);
+
+        for point in display.bounding_box().points() {
+            let converted = display.convert_point(point);
+
+            for neighbour in [point + Point::new(1, 0), point + Point::new(0, 1)] {
+                let distance = display.convert_point(neighbour) - converted;
+                assert_eq!(distance.x.abs() + distance.y.abs(), 1, "{:?}: {:?}", orientation, point);
+            }
+        }
+    }
+}
+
+#[test]
+fn maps_outside_points_outside() {
+    let panel = panel();
+
+    for orientation in ORIENTATIONS {
+        let display = display(orientation);
+        let Size { width, height } = display.bounding_box().size;
+        let outside = [
+            Point::new(-1, 0),
+            Point::new(0, -1),
+            Point::new(width as i32, 0),
+            Point::new(0, height as i32),
+            Point::new(width as i32, height as i32),
+        ];
+
+        for point in outside {
+            assert!(!panel.contains(display.convert_point(point)), "{:?}: {:?}", orientation, point);
+        }
+    }
+}
+
+#[test]
+fn flipped_orientations_are_rotated_by_half_a_turn() {
+    let bottom_right = panel().bottom_right().unwrap();
+    let pairs = [
+        (DisplayOrientation::Portrait, DisplayOrientation::PortraitFlipped),
+        (DisplayOrientation::Landscape, DisplayOrientation::LandscapeFlipped),
+    ];
+
+    for (orientation, flipped) in pairs {
+        let (display, flipped) = (display(orientation), display(flipped));
+
+        for point in display.bounding_box().points() {
+            assert_eq!(flipped.convert_point(point), bottom_right - display.convert_point(point), "{:?}", orientation);
+        }
+    }
+}
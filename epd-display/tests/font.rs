@@ -0,0 +1,96 @@
+use embedded_graphics::mock_display::MockDisplay;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::mono_font::iso_8859_15::FONT_10X20 as MONO_10X20;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Baseline, Text};
+use epd_display::text::font::{Font, Glyph};
+use epd_display::text::font_text_style::FontTextStyle;
+use epd_display::text::fonts::{FONT_6X10, FONT_8X13_PROPORTIONAL, FONT_10X20, FONT_10X20_PROPORTIONAL};
+
+/// Font without glyphs, drawing every character with the fallback glyph.
+struct EmptyFont {
+    baseline: u32,
+}
+
+impl Font for EmptyFont {
+    fn line_height(&self) -> u32 {
+        self.baseline
+    }
+
+    fn baseline(&self) -> u32 {
+        self.baseline
+    }
+
+    fn glyph(&self, _c: char) -> Option<Glyph> {
+        None
+    }
+
+    fn glyph_pixel(&self, _glyph: &Glyph, _point: Point) -> bool {
+        false
+    }
+}
+
+#[test]
+fn fallback_glyph_sits_on_the_baseline() {
+    let glyph = EmptyFont { baseline: 8 }.fallback_glyph();
+
+    assert_eq!(glyph.size, Size::new(4, 6));
+    assert_eq!(glyph.offset, Point::new(1, 2));
+    assert_eq!(glyph.advance, 6);
+}
+
+#[test]
+fn fallback_glyph_of_a_font_without_baseline() {
+    let glyph = EmptyFont { baseline: 0 }.fallback_glyph();
+
+    assert_eq!(glyph.size, Size::new(1, 1));
+    assert_eq!(glyph.offset, Point::new(1, 0));
+}
+
+fn draw(font: &dyn Font, text: &str) -> MockDisplay<BinaryColor> {
+    let mut display = MockDisplay::new();
+    Text::with_baseline(text, Point::zero(), FontTextStyle::new(font, BinaryColor::On), Baseline::Top).draw(&mut display).unwrap();
+    display
+}
+
+/// Display with the outlined box of the fallback glyph.
+fn fallback(font: &dyn Font) -> MockDisplay<BinaryColor> {
+    let glyph = font.fallback_glyph();
+    let mut display = MockDisplay::new();
+    Rectangle::new(glyph.offset, glyph.size).into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1)).draw(&mut display).unwrap();
+    display
+}
+
+#[test]
+fn draws_latin9_characters_with_the_glyphs_of_the_mono_font() {
+    for text in ["ä", "ß", "€"] {
+        let mut expected = MockDisplay::new();
+        Text::with_baseline(text, Point::zero(), MonoTextStyle::new(&MONO_10X20, BinaryColor::On), Baseline::Top).draw(&mut expected).unwrap();
+
+        assert_eq!(draw(&FONT_10X20, text), expected, "{}", text);
+    }
+}
+
+#[test]
+fn finds_latin9_glyphs_in_all_faces() {
+    let fonts: [&dyn Font; 4] = [&FONT_6X10, &FONT_10X20, &FONT_8X13_PROPORTIONAL, &FONT_10X20_PROPORTIONAL];
+
+    for font in fonts {
+        for c in ['ä', 'ß', '€'] {
+            assert!(font.glyph(c).is_some_and(|glyph| glyph.source.is_some()), "{}", c);
+            assert_ne!(draw(font, &c.to_string()), fallback(font), "{}", c);
+        }
+    }
+}
+
+#[test]
+fn draws_unsupported_characters_with_the_fallback_glyph() {
+    let fonts: [&dyn Font; 3] = [&FONT_6X10, &FONT_10X20, &FONT_10X20_PROPORTIONAL];
+
+    for font in fonts {
+        assert!(font.glyph('中').is_none());
+        assert_eq!(draw(font, "中"), fallback(font));
+    }
+}
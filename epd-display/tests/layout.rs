@@ -0,0 +1,143 @@
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::layout::leaf::Paragraph;
+use epd_display::layout::node::{Length, Node, Padding};
+use epd_display::simulator::SimulatedDisplay;
+use epd_display::text::font_text_style::FontTextStyle;
+use epd_display::text::fonts::FONT_6X10;
+
+const BOUNDS: Rectangle = Rectangle::new(Point::zero(), Size::new(12, 4));
+
+/// Leaf that fills its whole bounds with the color.
+fn fill(color: ThreeColor) -> Node<'static> {
+    Node::line(color, 1000)
+}
+
+fn draw(node: &Node<'_>, bounds: Rectangle) -> SimulatedDisplay {
+    let mut display = SimulatedDisplay::simulated();
+    node.draw(bounds, &mut display).unwrap();
+    display
+}
+
+/// Colors of the pixels in a row of the display, from `x` 0 to one past the bounds.
+fn row(display: &SimulatedDisplay, y: i32) -> String {
+    (0..=BOUNDS.size.width as i32)
+        .map(|x| match display.pixel(Point::new(x, y)) {
+            Some(ThreeColor::Black) => 'B',
+            Some(ThreeColor::Chromatic) => 'R',
+            _ => '.',
+        })
+        .collect()
+}
+
+#[test]
+fn divides_flex_space_by_weight() {
+    let children = [
+        fill(ThreeColor::Black).with_length(Length::Flex(1)),
+        fill(ThreeColor::Chromatic).with_length(Length::Flex(2)),
+        fill(ThreeColor::Black).with_length(Length::Fixed(3)),
+    ];
+    let display = draw(&Node::row(&children), BOUNDS);
+
+    assert_eq!(row(&display, 0), "BBBRRRRRRBBB.");
+}
+
+#[test]
+fn divides_flex_space_by_extreme_weights() {
+    let children = [
+        fill(ThreeColor::Black).with_length(Length::Flex(u32::MAX)),
+        fill(ThreeColor::Chromatic).with_length(Length::Flex(u32::MAX)),
+        fill(ThreeColor::Black).with_length(Length::Flex(u32::MAX)),
+    ];
+    let display = draw(&Node::row(&children), BOUNDS);
+
+    assert_eq!(row(&display, 0), "BBBBRRRRBBBB.");
+}
+
+#[test]
+fn lays_out_spacers_with_extreme_weights() {
+    let children = [
+        Node::spacer().with_length(Length::Flex(u32::MAX)),
+        fill(ThreeColor::Chromatic).with_length(Length::Fixed(2)),
+        Node::spacer().with_length(Length::Flex(u32::MAX)),
+    ];
+    let node = Node::row(&children);
+
+    assert_eq!(row(&draw(&node, BOUNDS), 0), ".....RR......");
+    assert!(node.fits(BOUNDS));
+}
+
+#[test]
+fn ignores_weights_without_flex_space() {
+    let children = [
+        fill(ThreeColor::Black).with_length(Length::Fixed(u32::MAX)),
+        fill(ThreeColor::Chromatic).with_length(Length::Flex(u32::MAX)),
+    ];
+    let node = Node::row(&children);
+
+    assert_eq!(row(&draw(&node, BOUNDS), 0), "BBBBBBBBBBBB.");
+    assert!(!node.fits(BOUNDS));
+}
+
+#[test]
+fn shrinks_bounds_by_padding() {
+    let children = [fill(ThreeColor::Black)];
+    let display = draw(&Node::row(&children).with_padding(Padding::new(1, 2, 0, 3)), BOUNDS);
+
+    assert_eq!(row(&display, 0), ".............");
+    assert_eq!(row(&display, 1), "...BBBBBBB...");
+}
+
+#[test]
+fn clips_extreme_padding() {
+    let children = [fill(ThreeColor::Black)];
+
+    for padding in [Padding::all(u32::MAX), Padding::symmetric(0, u32::MAX), Padding::new(0, u32::MAX, 0, 1)] {
+        let node = Node::row(&children).with_padding(padding);
+
+        assert_eq!(row(&draw(&node, BOUNDS), 0), ".............");
+        assert!(!node.fits(BOUNDS));
+    }
+}
+
+#[test]
+fn measures_content_with_extreme_padding() {
+    let children = [
+        Node::row(&[]).with_length(Length::Content).with_padding(Padding::all(u32::MAX)),
+        fill(ThreeColor::Black).with_length(Length::Flex(1)),
+    ];
+    let node = Node::column(&children);
+
+    assert_eq!(row(&draw(&node, BOUNDS), 3), ".............");
+    assert!(!node.fits(BOUNDS));
+}
+
+#[test]
+fn clips_extreme_spacing() {
+    let children = [fill(ThreeColor::Black), fill(ThreeColor::Chromatic), fill(ThreeColor::Black)];
+    let node = Node::row(&children).with_spacing(u32::MAX);
+
+    assert_eq!(row(&draw(&node, BOUNDS), 0), "BBBBBBBBBBBB.");
+    assert!(!node.fits(BOUNDS));
+}
+
+#[test]
+fn measures_text_with_extreme_line_spacing() {
+    let paragraphs: [Paragraph<'_>; 2] = [
+        ("one", FontTextStyle::new(&FONT_6X10, ThreeColor::Black)),
+        ("two", FontTextStyle::new(&FONT_6X10, ThreeColor::Black)),
+    ];
+    let children = [
+        Node::text(&paragraphs, Alignment::Left).with_spacing(u32::MAX),
+        fill(ThreeColor::Chromatic).with_length(Length::Flex(1)),
+    ];
+    let bounds = Rectangle::new(Point::zero(), Size::new(12, 30));
+    let display = draw(&Node::column(&children), bounds);
+
+    // The second line never fits, the text is one line high.
+    assert_ne!(display.pixel(Point::new(0, 9)), Some(ThreeColor::Chromatic));
+    assert_eq!(display.pixel(Point::new(0, 10)), Some(ThreeColor::Chromatic));
+}
@@ -0,0 +1,102 @@
+use embassy_time::{Duration, Instant};
+use epd_display::epd::maintenance::{Maintenance, MaintenancePolicy};
+
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+fn at(secs: u64) -> Instant {
+    Instant::from_secs(secs)
+}
+
+fn refreshed(policy: MaintenancePolicy, refreshes: u32) -> Maintenance {
+    let mut maintenance = Maintenance::new(policy);
+
+    for _ in 0..refreshes {
+        maintenance.record_refresh();
+    }
+
+    maintenance
+}
+
+#[test]
+fn never_due_without_policy() {
+    let maintenance = refreshed(MaintenancePolicy::NEVER, 1000);
+
+    assert!(!maintenance.is_due(at(365 * 24 * 60 * 60)));
+    assert_eq!(maintenance.next_due(), None);
+    assert_eq!(MaintenancePolicy::default(), MaintenancePolicy::NEVER);
+}
+
+#[test]
+fn due_after_refresh_count() {
+    let policy = MaintenancePolicy::after_refreshes(3);
+
+    assert!(!refreshed(policy, 2).is_due(at(0)));
+    assert!(refreshed(policy, 3).is_due(at(0)));
+    assert!(refreshed(policy, 4).is_due(at(0)));
+    assert_eq!(refreshed(policy, 3).next_due(), None);
+}
+
+#[test]
+fn due_after_interval() {
+    let maintenance = refreshed(MaintenancePolicy::DAILY, 0);
+
+    assert_eq!(maintenance.next_due(), Some(at(24 * 60 * 60)));
+    assert!(!maintenance.is_due(at(24 * 60 * 60 - 1)));
+    assert!(maintenance.is_due(at(24 * 60 * 60)));
+}
+
+#[test]
+fn due_by_whichever_comes_first() {
+    let policy = MaintenancePolicy::after_refreshes(3).or_after(HOUR);
+
+    assert!(!refreshed(policy, 2).is_due(at(60)));
+    assert!(refreshed(policy, 3).is_due(at(60)));
+    assert!(refreshed(policy, 0).is_due(at(60 * 60)));
+    assert_eq!(refreshed(policy, 0).next_due(), Some(at(60 * 60)));
+}
+
+#[test]
+fn cleaning_resets_count_and_deadline() {
+    let mut maintenance = refreshed(MaintenancePolicy::after_refreshes(3).or_after(HOUR), 5);
+    assert!(maintenance.is_due(at(2 * 60 * 60)));
+
+    maintenance.record_cleaning(at(2 * 60 * 60));
+
+    assert_eq!(maintenance.refreshes(), 0);
+    assert_eq!(maintenance.next_due(), Some(at(3 * 60 * 60)));
+    assert!(!maintenance.is_due(at(3 * 60 * 60 - 1)));
+    assert!(maintenance.is_due(at(3 * 60 * 60)));
+
+    // The refresh after the cleaning shows the image again and isn't counted.
+    maintenance.record_refresh();
+    assert_eq!(maintenance.refreshes(), 0);
+    maintenance.record_refresh();
+    assert_eq!(maintenance.refreshes(), 1);
+}
+
+#[test]
+fn cleans_after_exactly_the_refresh_count() {
+    let mut maintenance = Maintenance::new(MaintenancePolicy::after_refreshes(3));
+    let mut cleanings = Vec::new();
+
+    // Refreshes the way the driver does, cleaning first when due.
+    for refresh in 1..=12 {
+        if maintenance.is_due(at(0)) {
+            maintenance.record_cleaning(at(0));
+            cleanings.push(refresh);
+        }
+
+        maintenance.record_refresh();
+    }
+
+    assert_eq!(cleanings, [4, 8, 12]);
+}
+
+#[test]
+fn policy_can_be_changed() {
+    let mut maintenance = refreshed(MaintenancePolicy::NEVER, 5);
+    maintenance.set_policy(MaintenancePolicy::after_refreshes(5));
+
+    assert_eq!(maintenance.policy(), MaintenancePolicy::after_refreshes(5));
+    assert!(maintenance.is_due(at(0)));
+}
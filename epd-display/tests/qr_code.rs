@@ -0,0 +1,88 @@
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::simulator::SimulatedDisplay;
+use epd_display::widgets::qr_code::{QrCode, QrCodeError};
+use qrcodegen_no_heap::{QrCode as QrEncoder, QrCodeEcc, Version};
+
+const ORIENTATIONS: [DisplayOrientation; 4] = [
+    DisplayOrientation::Portrait,
+    DisplayOrientation::Landscape,
+    DisplayOrientation::PortraitFlipped,
+    DisplayOrientation::LandscapeFlipped,
+];
+
+/// Modules of the text encoded directly with the QR code library, row by row.
+fn encode(text: &str) -> Vec<Vec<bool>> {
+    let mut temp_buffer = [0; Version::MAX.buffer_len()];
+    let mut out_buffer = [0; Version::MAX.buffer_len()];
+    let encoded = QrEncoder::encode_text(text, &mut temp_buffer, &mut out_buffer, QrCodeEcc::Medium, Version::MIN, Version::new(10), None, true).unwrap();
+
+    (0..encoded.size()).map(|y| (0..encoded.size()).map(|x| encoded.get_module(x, y)).collect()).collect()
+}
+
+/// Reads the modules back from the centers of the drawn squares.
+fn scan(display: &SimulatedDisplay, origin: Point, count: u32, scale: u32, color: ThreeColor) -> Vec<Vec<bool>> {
+    let center = |module: u32| (module * scale + scale / 2) as i32;
+
+    (0..count)
+        .map(|y| (0..count).map(|x| display.pixel(origin + Point::new(center(x), center(y))) == Some(color)).collect())
+        .collect()
+}
+
+#[test]
+fn draws_the_modules_in_all_orientations() {
+    for text in ["Hello", "https://example.com/rooms/4711", "WIFI:S:guest;T:WPA;P:correct horse battery staple;;"] {
+        for orientation in ORIENTATIONS {
+            let mut display = SimulatedDisplay::simulated();
+            display.set_orientation(orientation);
+
+            let qr_code = QrCode::new(text, Point::new(3, 5)).unwrap()
+                .with_color(ThreeColor::Chromatic)
+                .scale_to_fit(display.bounding_box().size - Size::new(3, 5))
+                .unwrap();
+            qr_code.draw(&mut display).unwrap();
+
+            let count = qr_code.module_count();
+            let scale = qr_code.bounding_box().size.width / (count + 8);
+            let origin = Point::new(3, 5) + Point::new_equal((4 * scale) as i32);
+
+            assert!(scale > 1);
+            assert_eq!(scan(&display, origin, count, scale, ThreeColor::Chromatic), encode(text), "{orientation:?}");
+        }
+    }
+}
+
+#[test]
+fn draws_the_quiet_zone_white() {
+    let mut display = SimulatedDisplay::simulated();
+    DrawTarget::clear(&mut display, ThreeColor::Black).unwrap();
+
+    let qr_code = QrCode::new("Hello", Point::zero()).unwrap().with_scale(2);
+    qr_code.draw(&mut display).unwrap();
+
+    let area = qr_code.bounding_box();
+    let code = Rectangle::new(Point::new_equal(8), Size::new_equal(21 * 2));
+
+    assert_eq!(area.size, Size::new_equal(29 * 2));
+    assert!(area.points().filter(|point| !code.contains(*point)).all(|point| display.pixel(point) == Some(ThreeColor::White)));
+    assert_eq!(display.pixel(area.top_left + area.size), Some(ThreeColor::Black));
+}
+
+#[test]
+fn scales_to_fit_the_bounds() {
+    let qr_code = || QrCode::new("Hello", Point::zero()).unwrap();
+
+    assert_eq!(qr_code().scale_to_fit(Size::new(29, 100)).unwrap().bounding_box().size, Size::new_equal(29));
+    assert_eq!(qr_code().scale_to_fit(Size::new(100, 90)).unwrap().bounding_box().size, Size::new_equal(87));
+    assert!(qr_code().scale_to_fit(Size::new(28, 100)).is_none());
+    assert!(qr_code().with_quiet_zone(0).scale_to_fit(Size::new_equal(21)).is_some());
+    assert!(qr_code().scale_to_fit(Size::zero()).is_none());
+}
+
+#[test]
+fn rejects_text_too_long_for_the_code() {
+    assert_eq!(QrCode::new(&"x".repeat(300), Point::zero()).err(), Some(QrCodeError::DataTooLong));
+}
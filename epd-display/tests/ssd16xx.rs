@@ -0,0 +1,158 @@
+use embedded_graphics::image::GetPixel;
+use embedded_graphics::prelude::*;
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::simulator::ssd16xx::{EmulatedDisplay, Ssd16xx};
+use epd_display::simulator::time::block_on;
+
+const WIDTH: i32 = 152;
+const HEIGHT: i32 = 296;
+
+const ORIENTATIONS: [DisplayOrientation; 4] = [
+    DisplayOrientation::Portrait,
+    DisplayOrientation::Landscape,
+    DisplayOrientation::PortraitFlipped,
+    DisplayOrientation::LandscapeFlipped,
+];
+
+// Position of the logical origin on the panel and the panel directions of the logical x and y axis.
+fn panel_axes(orientation: DisplayOrientation) -> (Point, Point, Point) {
+    match orientation {
+        DisplayOrientation::Portrait => (Point::new(0, 0), Point::new(1, 0), Point::new(0, 1)),
+        DisplayOrientation::Landscape => (Point::new(0, HEIGHT - 1), Point::new(0, -1), Point::new(1, 0)),
+        DisplayOrientation::PortraitFlipped => (Point::new(WIDTH - 1, HEIGHT - 1), Point::new(-1, 0), Point::new(0, -1)),
+        DisplayOrientation::LandscapeFlipped => (Point::new(WIDTH - 1, 0), Point::new(0, 1), Point::new(-1, 0)),
+    }
+}
+
+fn panel_point(orientation: DisplayOrientation, point: Point) -> Point {
+    let (origin, x_axis, y_axis) = panel_axes(orientation);
+    origin + x_axis * point.x + y_axis * point.y
+}
+
+// Irregular pattern, so that shifted or mirrored rows don't match.
+fn pattern(point: Point) -> ThreeColor {
+    let hash = (point.x as u32).wrapping_mul(2_654_435_761) ^ (point.y as u32).wrapping_mul(40_503).rotate_left(7);
+
+    match hash % 5 {
+        0 | 1 => ThreeColor::White,
+        2 | 3 => ThreeColor::Black,
+        _ => ThreeColor::Chromatic,
+    }
+}
+
+fn draw_pattern(display: &mut EmulatedDisplay) {
+    let pixels: Vec<_> = display.bounding_box().points().map(|point| Pixel(point, pattern(point))).collect();
+    display.draw_iter(pixels).unwrap();
+}
+
+fn assert_pattern_on_screen(controller: &Ssd16xx, orientation: DisplayOrientation, size: Size) {
+    let screen = controller.screen();
+
+    for point in embedded_graphics::primitives::Rectangle::new(Point::zero(), size).points() {
+        assert_eq!(
+            screen.pixel(panel_point(orientation, point)),
+            Some(pattern(point)),
+            "{:?} at {:?}", orientation, point
+        );
+    }
+}
+
+#[test]
+fn init_sets_up_controller() {
+    let controller = Ssd16xx::new(WIDTH as usize, HEIGHT as usize);
+    let mut display = controller.display();
+
+    block_on(display.init());
+
+    assert_eq!(controller.data_entry_mode(), 0b011);
+    assert_eq!(controller.activations(), 0);
+    assert_eq!(controller.violations(), 0);
+}
+
+#[test]
+fn refresh_shows_image_in_every_orientation() {
+    for orientation in ORIENTATIONS {
+        let controller = Ssd16xx::new(WIDTH as usize, HEIGHT as usize);
+        let mut display = controller.display();
+        display.set_orientation(orientation);
+        draw_pattern(&mut display);
+
+        block_on(async {
+            display.init().await;
+            display.refresh().await;
+        });
+
+        assert_eq!(controller.activations(), 1);
+        assert_eq!(controller.violations(), 0);
+        assert_pattern_on_screen(&controller, orientation, display.bounding_box().size);
+    }
+}
+
+#[test]
+fn corners_of_landscape_image() {
+    let controller = Ssd16xx::new(WIDTH as usize, HEIGHT as usize);
+    let mut display = controller.display();
+    display.set_orientation(DisplayOrientation::Landscape);
+    display.draw_iter([
+        Pixel(Point::new(0, 0), ThreeColor::Chromatic),
+        Pixel(Point::new(HEIGHT - 1, WIDTH - 1), ThreeColor::Black),
+    ]).unwrap();
+
+    block_on(async {
+        display.init().await;
+        display.refresh().await;
+    });
+
+    let screen = controller.screen();
+    assert_eq!(screen.pixel(Point::new(0, HEIGHT - 1)), Some(ThreeColor::Chromatic));
+    assert_eq!(screen.pixel(Point::new(WIDTH - 1, 0)), Some(ThreeColor::Black));
+    assert_eq!(screen.pixel(Point::new(0, 0)), Some(ThreeColor::White));
+}
+
+#[test]
+fn clean_keeps_buffers() {
+    let controller = Ssd16xx::new(WIDTH as usize, HEIGHT as usize);
+    let mut display = controller.display();
+    draw_pattern(&mut display);
+
+    block_on(async {
+        display.init().await;
+        display.clean().await;
+    });
+
+    let screen = controller.screen();
+    assert_eq!(controller.activations(), 2);
+    assert!(screen.bounding_box().points().all(|point| screen.pixel(point) == Some(ThreeColor::White)));
+
+    block_on(display.refresh());
+
+    assert_eq!(controller.violations(), 0);
+    assert_pattern_on_screen(&controller, DisplayOrientation::Landscape, display.bounding_box().size);
+}
+
+#[test]
+fn sleep_needs_init() {
+    let controller = Ssd16xx::new(WIDTH as usize, HEIGHT as usize);
+    let mut display = controller.display();
+
+    block_on(async {
+        display.init().await;
+        display.sleep().await;
+    });
+
+    assert!(controller.is_sleeping());
+
+    block_on(display.refresh());
+
+    assert!(controller.violations() > 0);
+    assert_eq!(controller.activations(), 0);
+
+    block_on(async {
+        display.init().await;
+        display.refresh().await;
+    });
+
+    assert!(!controller.is_sleeping());
+    assert_eq!(controller.activations(), 1);
+}
@@ -0,0 +1,111 @@
+use embedded_graphics::mock_display::MockDisplay;
+use embedded_graphics::mono_font::ascii::FONT_6X10;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::{Alignment, Baseline, Text};
+use epd_display::text::text_layout::{LineBreaker, TextLayout};
+
+const STYLE: MonoTextStyle<'static, BinaryColor> = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+
+/// Width of `text` in characters of a monospaced font.
+fn chars(text: &str) -> u32 {
+    text.chars().count() as u32
+}
+
+fn lines(text: &str, max_width: u32) -> Vec<&str> {
+    LineBreaker::new(text, max_width, chars).collect()
+}
+
+/// Draws the text layout into a fresh mock display.
+fn layout(text: &str, bounds: Rectangle, alignment: Alignment) -> (MockDisplay<BinaryColor>, u32) {
+    let mut display = MockDisplay::new();
+    let height = TextLayout::new(bounds, alignment).draw([(text, STYLE)], &mut display).unwrap();
+    (display, height)
+}
+
+/// Draws single lines of text at the given positions into a fresh mock display.
+fn expected(lines: &[(&str, Point)]) -> MockDisplay<BinaryColor> {
+    let mut display = MockDisplay::new();
+    for (line, position) in lines {
+        Text::with_baseline(line, *position, STYLE, Baseline::Top).draw(&mut display).unwrap();
+    }
+    display
+}
+
+#[test]
+fn breaks_lines_at_the_last_space_that_fits() {
+    assert_eq!(lines("the quick brown fox", 10), ["the quick", "brown fox"]);
+    assert_eq!(lines("the quick brown fox", 9), ["the quick", "brown fox"]);
+    assert_eq!(lines("the quick brown fox", 8), ["the", "quick", "brown", "fox"]);
+}
+
+#[test]
+fn keeps_text_that_fits_on_one_line() {
+    assert_eq!(lines("fits", 4), ["fits"]);
+    assert_eq!(lines("", 4), [""]);
+}
+
+#[test]
+fn breaks_at_newlines() {
+    assert_eq!(lines("one\ntwo\n\nthree", 20), ["one", "two", "", "three"]);
+}
+
+#[test]
+fn drops_the_spaces_at_line_breaks() {
+    assert_eq!(lines("one   two", 5), ["one", "two"]);
+}
+
+#[test]
+fn breaks_overlong_words_between_characters() {
+    assert_eq!(lines("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+    assert_eq!(lines("a abcdefgh", 4), ["a", "abcd", "efgh"]);
+}
+
+#[test]
+fn places_at_least_one_character_per_line() {
+    assert_eq!(lines("abc", 0), ["a", "b", "c"]);
+}
+
+#[test]
+fn stacks_lines_from_the_top() {
+    let (display, height) = layout("one two", Rectangle::new(Point::zero(), Size::new(24, 30)), Alignment::Left);
+
+    display.assert_eq(&expected(&[("one", Point::new(0, 0)), ("two", Point::new(0, 10))]));
+    assert_eq!(height, 20);
+}
+
+#[test]
+fn ends_the_last_visible_line_with_an_ellipsis() {
+    let bounds = Rectangle::new(Point::zero(), Size::new(60, 10));
+    let (display, height) = layout("aaaa bbbb cccc", bounds, Alignment::Left);
+
+    // 10 characters fit, 3 of them are taken by the ellipsis.
+    display.assert_eq(&expected(&[("aaaa bb...", Point::zero())]));
+    assert_eq!(height, 10);
+}
+
+#[test]
+fn aligns_lines_left() {
+    let bounds = Rectangle::new(Point::new(2, 0), Size::new(60, 10));
+    let (display, _) = layout("ab", bounds, Alignment::Left);
+
+    display.assert_eq(&expected(&[("ab", Point::new(2, 0))]));
+}
+
+#[test]
+fn aligns_lines_center() {
+    let bounds = Rectangle::new(Point::new(2, 0), Size::new(60, 10));
+    let (display, _) = layout("ab", bounds, Alignment::Center);
+
+    display.assert_eq(&expected(&[("ab", Point::new(2 + 24, 0))]));
+}
+
+#[test]
+fn aligns_lines_right() {
+    let bounds = Rectangle::new(Point::new(2, 0), Size::new(60, 10));
+    let (display, _) = layout("ab", bounds, Alignment::Right);
+
+    display.assert_eq(&expected(&[("ab", Point::new(2 + 48, 0))]));
+}
@@ -0,0 +1,42 @@
+//! Parses arbitrary bytes as bitmap upload and decodes the planes into the display, once as a whole
+//! and once in chunks as they arrive from a socket. An accepted upload has to decode the same either
+//! way, and raw planes exactly into the area.
+
+#![no_main]
+
+use display_core::protocol::bitmap::{parse_header, BitmapDecoder, Encoding, BITMAP_HEADER_LEN};
+use epd_display::epd::epd_2in66b::{HEIGHT, WIDTH};
+use epd_display::simulator::SimulatedDisplay;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(header) = parse_header(data, data.len()) else { return };
+    let area = header.area;
+
+    assert!(area.x.is_multiple_of(8) && area.width.is_multiple_of(8));
+    assert!(area.x + area.width <= WIDTH && area.y + area.height <= HEIGHT);
+    assert_eq!(header.plane_len(), area.width / 8 * area.height);
+
+    let planes = &data[BITMAP_HEADER_LEN..];
+    let mut whole = SimulatedDisplay::simulated();
+    let mut decoder = BitmapDecoder::new(header);
+    decoder.decode(planes, &mut whole);
+
+    if header.encoding == Encoding::Raw {
+        assert!(decoder.is_complete());
+    }
+
+    // The first byte of the planes picks the chunk size.
+    let chunk_size = usize::from(planes.first().copied().unwrap_or(0) % 16) + 1;
+    let mut chunked = SimulatedDisplay::simulated();
+    let mut chunked_decoder = BitmapDecoder::new(header);
+
+    for chunk in planes.chunks(chunk_size) {
+        chunked_decoder.decode(chunk, &mut chunked);
+    }
+
+    assert_eq!(chunked_decoder.is_complete(), decoder.is_complete());
+    assert_eq!(chunked_decoder.is_too_long(), decoder.is_too_long());
+    assert!(whole.planes_mut().0.buffer == chunked.planes_mut().0.buffer);
+    assert!(whole.planes_mut().1.buffer == chunked.planes_mut().1.buffer);
+});
@@ -0,0 +1,99 @@
+//! Feeds an arbitrary byte stream in arbitrary chunks through the connection handler, which
+//! splits the stream into newline-delimited messages. The messages have to come out the same
+//! however the stream is chunked, and every message is answered.
+
+#![no_main]
+
+use std::convert::Infallible;
+
+use display_core::data::display_cmd::DisplayCmd;
+use display_core::protocol::connection::{serve, Handler};
+use display_core::protocol::{parse_message, MessageError};
+use embassy_futures::block_on;
+use embedded_io_async::{ErrorType, Read, Write};
+use epd_display::simulator::SimulatedDisplay;
+use libfuzzer_sys::fuzz_target;
+
+const BUFFER_SIZE: usize = 512;
+
+struct Connection<'a> {
+    data: &'a [u8],
+    chunk_sizes: &'a [u8],
+    written: Vec<u8>,
+}
+
+impl ErrorType for Connection<'_> {
+    type Error = Infallible;
+}
+
+impl Read for Connection<'_> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+        let (&size, rest) = self.chunk_sizes.split_first().unwrap_or((&u8::MAX, &[]));
+        self.chunk_sizes = rest;
+
+        let n = (size as usize).max(1).min(buf.len()).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+
+        Ok(n)
+    }
+}
+
+impl Write for Connection<'_> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+}
+
+#[derive(Default)]
+struct Recorder {
+    results: Vec<Result<(), MessageError>>,
+    refreshes: usize,
+}
+
+impl Handler for Recorder {
+    fn show(&mut self, _cmd: DisplayCmd) -> u32 {
+        self.results.push(Ok(()));
+        self.results.len() as u32
+    }
+
+    async fn refreshed(&mut self, _sequence: u32) {
+        self.refreshes += 1;
+    }
+
+    type Planes<'a> = &'a mut SimulatedDisplay;
+
+    async fn planes(&mut self) -> &mut SimulatedDisplay {
+        unreachable!("the TCP protocol has no bitmap uploads");
+    }
+
+    fn rejected(&mut self, error: MessageError) {
+        self.results.push(Err(error));
+    }
+}
+
+// The first byte is the number of chunk sizes that follow, the rest is the stream.
+fuzz_target!(|input: &[u8]| {
+    let Some((&count, rest)) = input.split_first() else { return };
+    let (chunk_sizes, data) = rest.split_at((count as usize).min(rest.len()));
+
+    let mut connection = Connection { data, chunk_sizes, written: Vec::new() };
+    let mut buf = [0; BUFFER_SIZE];
+    let mut recorder = Recorder::default();
+
+    let Ok(()) = block_on(serve(&mut connection, &mut buf, &mut recorder));
+
+    // The same split in one go: lines shorter than the buffer are parsed without surrounding whitespace.
+    let expected: Vec<_> = data
+        .split(|&byte| byte == b'\n')
+        .filter_map(|line| match line.trim_ascii() {
+            _ if line.len() >= BUFFER_SIZE => Some(Err(MessageError::TooLong)),
+            [] => None,
+            message => Some(parse_message(message).map(|_| ())),
+        })
+        .collect();
+
+    assert_eq!(recorder.results, expected);
+    assert_eq!(connection.written.iter().filter(|&&byte| byte == b'\n').count(), expected.len() + recorder.refreshes);
+});
@@ -0,0 +1,42 @@
+//! Parses arbitrary bytes as message and draws the result. The capacities of the heapless
+//! containers must hold for every parsed message.
+
+#![no_main]
+
+use display_core::data::display_cmd::{DisplayCmd, MAX_BODY_LINES, MAX_CHART_VALUES, QR_TEXT_CAPACITY, STRING_CAPACITY, UNIT_CAPACITY};
+use display_core::data::screen::{MAX_CHILDREN, MAX_SCREEN_WIDGETS};
+use display_core::protocol::parse_message;
+use display_core::render::draw;
+use epd_display::epd::display_orientation::DisplayOrientation;
+use epd_display::simulator::SimulatedDisplay;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(cmd) = parse_message(data) else { return };
+
+    match &cmd {
+        DisplayCmd::TextPanel(content) => {
+            assert!(content.title().text().len() <= STRING_CAPACITY);
+            assert!(content.body_len() <= MAX_BODY_LINES);
+            assert!((0..content.body_len()).all(|i| content.body_line(i).is_some_and(|line| line.text().len() <= STRING_CAPACITY)));
+            assert!(content.qr_code().is_none_or(|qr_code| qr_code.text().len() <= QR_TEXT_CAPACITY));
+        }
+        DisplayCmd::Chart(content) => {
+            assert!(content.title().text().len() <= STRING_CAPACITY);
+            assert!(content.values().len() <= MAX_CHART_VALUES);
+            assert!(content.unit().len() <= UNIT_CAPACITY);
+        }
+        DisplayCmd::Screen(screen) => {
+            assert!(screen.widgets.len() <= MAX_SCREEN_WIDGETS);
+            assert!(screen.widgets.iter().all(|widget| widget.children.len() <= MAX_CHILDREN));
+        }
+        DisplayCmd::None | DisplayCmd::Bitmap | DisplayCmd::Clear | DisplayCmd::ShowStatus => {}
+        DisplayCmd::Sleep | DisplayCmd::Refresh(_) | DisplayCmd::SetOrientation(_) => {}
+    }
+
+    for orientation in [DisplayOrientation::Landscape, DisplayOrientation::Portrait] {
+        let mut display = SimulatedDisplay::simulated();
+        display.set_orientation(orientation);
+        let _ = draw(&mut display, &cmd);
+    }
+});
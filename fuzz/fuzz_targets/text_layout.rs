@@ -0,0 +1,50 @@
+//! Wraps arbitrary text into arbitrary bounds. Lines must fit the bounds unless a single
+//! character is wider, no text may be lost and the layout may not be higher than its bounds.
+
+#![no_main]
+
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::Alignment;
+use embedded_graphics::text::renderer::TextRenderer;
+use embedded_graphics::text::Baseline;
+use epd_display::epd::three_color::ThreeColor;
+use epd_display::simulator::SimulatedDisplay;
+use epd_display::text::font_text_style::FontTextStyle;
+use epd_display::text::fonts::{FONT_10X20_PROPORTIONAL, FONT_6X10};
+use epd_display::text::text_layout::{LineBreaker, TextLayout};
+use libfuzzer_sys::fuzz_target;
+
+// Width, height, line spacing and alignment come from the first bytes, the rest is the text.
+fuzz_target!(|input: &[u8]| {
+    let [width, height, spacing, flags, text @ ..] = input else { return };
+    let text = String::from_utf8_lossy(text);
+    let font = if flags & 1 == 0 { &FONT_6X10 } else { &FONT_10X20_PROPORTIONAL };
+    let style = FontTextStyle::new(font, ThreeColor::Black);
+    let alignment = match flags >> 1 & 0b11 {
+        0 => Alignment::Left,
+        1 => Alignment::Center,
+        _ => Alignment::Right,
+    };
+
+    let measure = |s: &str| style.measure_string(s, Point::zero(), Baseline::Top).next_position.x as u32;
+    let max_width = *width as u32;
+    let mut joined = String::new();
+
+    for line in LineBreaker::new(&text, max_width, measure) {
+        assert!(measure(line) <= max_width || line.chars().count() == 1, "{:?} is wider than {}", line, max_width);
+        joined.push_str(line);
+    }
+
+    let without_breaks = |s: &str| s.chars().filter(|c| *c != ' ' && *c != '\n').collect::<String>();
+    assert_eq!(without_breaks(&joined), without_breaks(&text));
+
+    let bounds = Rectangle::new(Point::new(3, 5), Size::new(max_width, *height as u32));
+    let layout = TextLayout::new(bounds, alignment).with_line_spacing(*spacing as u32 % 16);
+    let paragraphs = text.split('\n').map(|paragraph| (paragraph, style));
+
+    assert!(layout.measure(paragraphs.clone()) <= bounds.size.height);
+
+    let mut display = SimulatedDisplay::simulated();
+    let _ = layout.draw(paragraphs, &mut display);
+});
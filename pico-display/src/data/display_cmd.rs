@@ -1,75 +1,110 @@
 use core::cell::RefCell;
+use defmt::{info, warn};
 use embassy_sync::blocking_mutex::Mutex;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::channel::Channel;
-use heapless::{Vec, String};
-use epd_display::epd::three_color::ThreeColor;
+use embassy_rp::peripherals::SPI1;
+use embassy_sync::mutex::{Mutex as AsyncMutex, MutexGuard};
+use embassy_sync::once_lock::OnceLock;
+use embassy_sync::watch::Watch;
+use embassy_time::Instant;
+use epd_display::EpdType;
+use epd_display::epd::epd_2in66b::PlaneBuffer;
+use display_core::data::display_cmd::DisplayCmd;
+use display_core::protocol::MessageError;
+use display_core::protocol::bitmap::PlaneBuffers;
+use display_core::protocol::connection::{is_refreshed, Handler};
+use display_core::protocol::http::{DisplayStatus, HttpHandler};
 
-const STRING_CAPACITY: usize = 80;
-const MAX_BODY_LINES: usize = 10;
+/// Command for the display with its sequence number, which counts up with every command.
+pub struct SharedDisplayCmd {
+    /// The latest command until the display task takes it.
+    pub cmd: Option<DisplayCmd>,
+    pub sequence: u32,
+    /// Sequence number of the latest command the display task handled.
+    pub refreshed: u32,
+    /// No maintenance refreshes until the next command.
+    pub sleeping: bool,
+}
 
-pub static SHARED_DISPLAY_CMD: Mutex<CriticalSectionRawMutex, RefCell<DisplayCmd>> = Mutex::new(RefCell::new(DisplayCmd::None));
+pub static SHARED_DISPLAY_CMD: Mutex<CriticalSectionRawMutex, RefCell<SharedDisplayCmd>> = Mutex::new(RefCell::new(SharedDisplayCmd {
+    cmd: None,
+    sequence: 0,
+    refreshed: 0,
+    sleeping: false,
+}));
+/// The display, locked by the display task while it draws and refreshes, and by bitmap uploads while
+/// they are decoded into its buffers. Decoding an upload takes a while, so the mutex doesn't block
+/// interrupts.
+pub static SHARED_DISPLAY: OnceLock<AsyncMutex<CriticalSectionRawMutex, EpdType<SPI1>>> = OnceLock::new();
 pub static DISPLAY_CMD_READY: Channel<CriticalSectionRawMutex, (), 1> = Channel::new();
+/// Sequence number of the command on the display after each refresh, watched by the TCP, HTTP and
+/// MQTT tasks.
+pub static DISPLAY_REFRESHED: Watch<CriticalSectionRawMutex, u32, 4> = Watch::new();
+
+/// Passes the command on to the display task and returns its sequence number.
+pub fn send_display_cmd(display_cmd: DisplayCmd) -> u32 {
+    let sequence = SHARED_DISPLAY_CMD.lock(|shared| {
+        let mut shared = shared.borrow_mut();
+        shared.cmd = Some(display_cmd);
+        shared.sequence = shared.sequence.wrapping_add(1);
+        shared.sequence
+    });
 
-pub struct TextLine {
-    text: String<STRING_CAPACITY>,
-    color: ThreeColor
+    let _ = DISPLAY_CMD_READY.try_send(());
+    sequence
 }
 
-impl TextLine {
-    pub fn new(text: &str, color: ThreeColor) -> Self {
-        let text = Self::string_from(text).unwrap_or(String::new());
-        TextLine { text, color }
-    }
+/// Passes the commands of the TCP and HTTP servers on to the display task.
+pub struct DisplayHandler;
 
-    pub fn text(&self) -> &str {
-        &self.text
+impl Handler for DisplayHandler {
+    fn show(&mut self, cmd: DisplayCmd) -> u32 {
+        info!("Parsed message.");
+        send_display_cmd(cmd)
     }
 
-    pub fn color(&self) -> ThreeColor {
-        self.color
+    async fn refreshed(&mut self, sequence: u32) {
+        // All receivers are taken only if the tasks leak them.
+        let Some(mut receiver) = DISPLAY_REFRESHED.receiver() else { return };
+        receiver.changed_and(|&refreshed| is_refreshed(refreshed, sequence)).await;
     }
 
-    fn string_from<const CAPACITY: usize>(slice: &str) -> Option<String<CAPACITY>> {
-        if slice.len() <= CAPACITY {
-            String::try_from(slice).ok()
-        } else {
-            let last = slice.floor_char_boundary(CAPACITY);
-            String::try_from(&slice[0..last]).ok()
-        }
-    }
-}
+    type Planes<'a> = DisplayPlanes;
 
-pub struct TextPanelContent {
-    title: TextLine,
-    body: Vec<TextLine, MAX_BODY_LINES>,
-}
+    async fn planes(&mut self) -> DisplayPlanes {
+        info!("Bitmap upload.");
+        DisplayPlanes(SHARED_DISPLAY.get().await.lock().await)
+    }
 
-impl TextPanelContent {
-    pub fn new(title: TextLine) -> Self {
-        TextPanelContent {
-            title, body: Vec::new(),
+    fn rejected(&mut self, error: MessageError) {
+        match error {
+            MessageError::TooLong => warn!("Message longer than the buffer dropped."),
+            _ => info!("Couldn't parse message."),
         }
     }
+}
 
-    pub fn title(&self) -> &TextLine {
-        &self.title
-    }
+/// Buffers of the locked display that a bitmap upload is decoded into.
+pub struct DisplayPlanes(MutexGuard<'static, CriticalSectionRawMutex, EpdType<SPI1>>);
 
-    pub fn body_len(&self) -> usize {
-        self.body.len()
+impl PlaneBuffers for DisplayPlanes {
+    fn planes_mut(&mut self) -> (&mut PlaneBuffer, &mut PlaneBuffer) {
+        self.0.planes_mut()
     }
+}
 
-    pub fn body_line(&self, i: usize) -> Option<&TextLine> {
-        self.body.get(i)
-    }
+impl HttpHandler for DisplayHandler {
+    fn status(&mut self) -> DisplayStatus {
+        SHARED_DISPLAY_CMD.lock(|shared| {
+            let shared = shared.borrow();
 
-    pub fn add_body_line(&mut self, body_line: TextLine) -> Result<(), ()>{
-        self.body.push(body_line).or(Err(()))
+            DisplayStatus {
+                sequence: shared.sequence,
+                refreshed: shared.refreshed,
+                sleeping: shared.sleeping,
+                uptime: Instant::now().as_secs(),
+            }
+        })
     }
 }
-
-pub enum DisplayCmd {
-    None,
-    TextPanel(TextPanelContent)
-}
@@ -1 +1 @@
-pub mod display_cmd;
\ No newline at end of file
+pub mod display_cmd;
@@ -1,68 +1,112 @@
 use embassy_rp::peripherals::{PIN_8, PIN_9, PIN_10, PIN_11, PIN_12, PIN_13, DMA_CH1, SPI1};
-use static_cell::StaticCell;
+use defmt::info;
+use embassy_sync::mutex::Mutex as AsyncMutex;
+use embassy_time::{with_deadline, Duration, Instant};
 use epd_display::{EpdPeripherals, EpdType};
 
-use embedded_graphics::prelude::*;
-use embedded_graphics::text::{Alignment, Text};
-use embedded_graphics::mono_font::MonoTextStyle;
-use embedded_graphics::mono_font::ascii::FONT_10X20;
-use embedded_graphics::primitives::{Line, PrimitiveStyle};
-use epd_display::epd::three_color::ThreeColor;
-use crate::data::display_cmd::{DisplayCmd, TextPanelContent, DISPLAY_CMD_READY, SHARED_DISPLAY_CMD};
+use epd_display::epd::maintenance::MaintenancePolicy;
+use display_core::data::display_cmd::{DisplayCmd, RefreshMode, StatusContent};
+use display_core::render::{draw, draw_status};
+use crate::data::display_cmd::{DISPLAY_CMD_READY, DISPLAY_REFRESHED, SHARED_DISPLAY, SHARED_DISPLAY_CMD};
+
+// Clean the display against ghosting after 20 refreshes, and at least once a day.
+const MAINTENANCE_POLICY: MaintenancePolicy = MaintenancePolicy::after_refreshes(20).or_after(Duration::from_secs(24 * 60 * 60));
 
 pub type DisplayPeripherals = EpdPeripherals<PIN_9, PIN_10, PIN_11, PIN_8, PIN_12, PIN_13, SPI1, DMA_CH1>;
 
 #[embassy_executor::task]
 pub async fn run_display(peripherals: DisplayPeripherals) {
-    static DISPLAY: StaticCell<EpdType<SPI1>> = StaticCell::new();
-    let epd = EpdType::from_peripherals(peripherals);
-    let display = DISPLAY.init(epd);
+    let shared_display = SHARED_DISPLAY.get_or_init(|| AsyncMutex::new(EpdType::from_peripherals(peripherals)));
+    shared_display.lock().await.set_maintenance_policy(MAINTENANCE_POLICY);
+
+    // The latest command that replaced the image, drawn again in a new orientation.
+    let mut content = DisplayCmd::None;
+    let mut sleeping = false;
 
     loop {
-        DISPLAY_CMD_READY.receive().await;
+        // Without a new command the display wakes up when the next cleaning is due, the refresh
+        // then cleans the display and shows the current image again. A sleeping display only waits
+        // for the next command.
+        let next_due = shared_display.lock().await.maintenance().next_due();
+
+        let cmd_ready = match next_due.filter(|_| !sleeping) {
+            Some(due) => with_deadline(due, DISPLAY_CMD_READY.receive()).await.is_ok(),
+            None => {
+                DISPLAY_CMD_READY.receive().await;
+                true
+            }
+        };
 
-        display.init().await;
-        display.clear();
+        let (cmd, sequence) = SHARED_DISPLAY_CMD.lock(|shared| {
+            let mut shared = shared.borrow_mut();
+            (shared.cmd.take(), shared.sequence)
+        });
+
+        if cmd.is_some() {
+            sleeping = matches!(cmd, Some(DisplayCmd::Sleep));
+        }
 
-        SHARED_DISPLAY_CMD.lock(|cmd| {
-            let cmd = cmd.borrow();
+        // Bitmap uploads wait until the display is drawn and refreshed.
+        let mut display = shared_display.lock().await;
+        let (mut redraw, mut clean) = (false, false);
 
-            match *cmd {
-                DisplayCmd::TextPanel(ref content) => draw_text_panel(display, content),
-                _ => ()
+        match cmd {
+            // The command was already taken at an earlier wake up.
+            None if cmd_ready => continue,
+            None => info!("Display maintenance after {} refreshes", display.maintenance().refreshes()),
+            // The panel is in deep sleep after every refresh already.
+            Some(DisplayCmd::Sleep) => {}
+            Some(DisplayCmd::Refresh(mode)) => clean = mode == RefreshMode::Full,
+            Some(DisplayCmd::SetOrientation(orientation)) => {
+                display.set_orientation(orientation);
+                redraw = true;
             }
-        });
+            Some(cmd) => {
+                content = cmd;
+                redraw = true;
+            }
+        }
 
-        display.refresh().await;
-        display.sleep().await;
-    }
-}
+        if !sleeping {
+            if redraw {
+                draw_content(&mut display, &content, sequence);
+            }
+
+            display.init().await;
 
-fn draw_text_panel(display: &mut EpdType<SPI1>, content: &TextPanelContent) {
-    let title = content.title();
+            if clean {
+                display.clean().await;
+            }
 
-    let _ = Text::with_alignment(
-        title.text(),
-        Point::new(2, 20),
-        MonoTextStyle::new(&FONT_10X20, title.color()),
-        Alignment::Left
-    ).draw(display);
+            display.refresh().await;
+            display.sleep().await;
+        }
 
-    if let Ok(w) = i32::try_from(display.width()) {
-        let _ = Line::new(Point::new(0, 32), Point::new(w, 32))
-            .into_styled(PrimitiveStyle::with_stroke(ThreeColor::Chromatic, 3))
-            .draw(display);
+        SHARED_DISPLAY_CMD.lock(|shared| {
+            let mut shared = shared.borrow_mut();
+            shared.refreshed = sequence;
+            shared.sleeping = sleeping;
+        });
+        DISPLAY_REFRESHED.sender().send(sequence);
     }
+}
 
-    for i in 0 .. content.body_len() {
-        let Some(body_line) = content.body_line(i)  else { continue };
-        let Ok(i) = i32::try_from(i) else { continue };
-
-        let _ = Text::with_alignment(
-            body_line.text(),
-            Point::new(2, 55 + i * 22),
-            MonoTextStyle::new(&FONT_10X20, body_line.color()),
-            Alignment::Left,
-        ).draw(display);
+/// Draws the content into the buffers, the status isn't known to `draw`. The bitmap uploads are
+/// decoded into the buffers already.
+fn draw_content(display: &mut EpdType<SPI1>, content: &DisplayCmd, sequence: u32) {
+    match content {
+        DisplayCmd::Bitmap => {}
+        DisplayCmd::ShowStatus => {
+            let status = StatusContent {
+                uptime: Instant::now().as_secs(),
+                sequence,
+                refreshes: display.maintenance().refreshes(),
+                orientation: display.orientation(),
+            };
+            let _ = draw_status(display, &status);
+        }
+        _ => {
+            let _ = draw(display, content);
+        }
     }
 }
@@ -0,0 +1,36 @@
+use defmt::{debug, trace, warn};
+use embassy_net::Stack;
+use embassy_net::tcp::TcpSocket;
+use embassy_time::Duration;
+
+use display_core::protocol::http::serve_http;
+use crate::data::display_cmd::DisplayHandler;
+
+const HTTP_PORT: u16 = 80;
+
+#[embassy_executor::task]
+pub async fn run_http_server(stack: Stack<'static>) -> ! {
+    let mut rx_buffer = [0; 1024];
+    let mut tx_buffer = [0; 512];
+    let mut buf = [0; 4096];
+
+    loop {
+        let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+        socket.set_timeout(Some(Duration::from_secs(10)));
+
+        debug!("Listening on HTTP:{}...", HTTP_PORT);
+        if let Err(e) = socket.accept(HTTP_PORT).await {
+            warn!("accept error: {:?}", e);
+            continue;
+        }
+
+        trace!("HTTP request from {:?}", socket.remote_endpoint());
+
+        if let Err(e) = serve_http(&mut socket, &mut buf, &mut DisplayHandler).await {
+            warn!("HTTP connection error: {:?}", e);
+        }
+
+        socket.close();
+        let _ = socket.flush().await;
+    }
+}
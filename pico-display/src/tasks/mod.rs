@@ -1,2 +1,5 @@
 pub mod wifi;
-pub mod display;
\ No newline at end of file
+pub mod display;
+pub mod http;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
@@ -0,0 +1,144 @@
+use core::fmt::Write;
+use defmt::{debug, warn};
+use embassy_net::Stack;
+use embassy_net::dns::DnsQueryType;
+use embassy_net::tcp::TcpSocket;
+use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::watch::Receiver;
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+use heapless::String;
+
+use display_core::data::display_cmd::DisplayCmd;
+use display_core::protocol::MessageError;
+use display_core::protocol::connection::Handler;
+use display_core::protocol::mqtt::{serve_mqtt, MqttConfig, MqttError, MqttHandler, Refresh};
+use crate::data::display_cmd::{DisplayHandler, DisplayPlanes, DISPLAY_REFRESHED};
+
+// The topics of the device are below `display/<MQTT_CLIENT_ID>`.
+const MQTT_HOST: &str = dotenvy_macro::dotenv!("MQTT_HOST");
+const MQTT_PORT: &str = dotenvy_macro::dotenv!("MQTT_PORT");
+const MQTT_USERNAME: &str = dotenvy_macro::dotenv!("MQTT_USERNAME");
+const MQTT_PASSWORD: &str = dotenvy_macro::dotenv!("MQTT_PASSWORD");
+const MQTT_CLIENT_ID: &str = dotenvy_macro::dotenv!("MQTT_CLIENT_ID");
+
+const _: () = assert!(!MQTT_HOST.is_empty(), "MQTT_HOST must be set for the mqtt feature");
+const _: () = assert!(is_topic_level(MQTT_CLIENT_ID), "MQTT_CLIENT_ID must be a topic level without '/', '+' and '#'");
+
+const DEFAULT_PORT: u16 = 1883;
+const KEEP_ALIVE: u16 = 60;
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+#[embassy_executor::task]
+pub async fn run_mqtt_client(stack: Stack<'static>) -> ! {
+    let mut rx_buffer = [0; 4096];
+    let mut tx_buffer = [0; 1024];
+    let mut buf = [0; 4096];
+
+    let port = MQTT_PORT.parse().unwrap_or(DEFAULT_PORT);
+    let mut topic: String<64> = String::new();
+    let _ = write!(topic, "display/{}", MQTT_CLIENT_ID);
+
+    let config = MqttConfig {
+        client_id: MQTT_CLIENT_ID,
+        topic: &topic,
+        username: Some(MQTT_USERNAME).filter(|username| !username.is_empty()),
+        password: Some(MQTT_PASSWORD).filter(|password| !password.is_empty()),
+        keep_alive: KEEP_ALIVE,
+    };
+
+    loop {
+        let address = match stack.dns_query(MQTT_HOST, DnsQueryType::A).await.as_deref() {
+            Ok([address, ..]) => *address,
+            _ => {
+                warn!("MQTT broker {} not found", MQTT_HOST);
+                Timer::after(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+        // The broker answers the pings at least every half keep alive.
+        socket.set_timeout(Some(Duration::from_secs(KEEP_ALIVE.into())));
+
+        debug!("Connecting to MQTT broker {}:{}...", address, port);
+        if let Err(e) = socket.connect((address, port)).await {
+            warn!("MQTT connect error: {:?}", e);
+            Timer::after(RECONNECT_DELAY).await;
+            continue;
+        }
+
+        // A new handler publishes the latest refresh again after every reconnect.
+        let Some(mut handler) = MqttDisplayHandler::new() else {
+            panic!("No receiver for display refreshes left");
+        };
+
+        match serve_mqtt(&mut socket, &mut buf, &config, &mut handler).await {
+            Ok(()) => warn!("MQTT broker closed the connection"),
+            Err(MqttError::Connection(e)) => warn!("MQTT connection error: {:?}", e),
+            Err(MqttError::Refused(return_code)) => warn!("MQTT broker refused the connection: {}", return_code),
+            Err(MqttError::SubscriptionRefused) => warn!("MQTT broker refused the subscription"),
+            Err(MqttError::Timeout) => warn!("MQTT broker stopped answering"),
+            Err(MqttError::Protocol) => warn!("MQTT protocol error"),
+        }
+
+        socket.abort();
+        let _ = socket.flush().await;
+        Timer::after(RECONNECT_DELAY).await;
+    }
+}
+
+
+/// Passes the commands of the MQTT client on to the display task and reports the refreshes.
+pub struct MqttDisplayHandler {
+    refreshed: Receiver<'static, CriticalSectionRawMutex, u32, 4>,
+}
+
+impl MqttDisplayHandler {
+    /// The first refresh reported is the latest one before the handler was created.
+    pub fn new() -> Option<Self> {
+        DISPLAY_REFRESHED.receiver().map(|refreshed| MqttDisplayHandler { refreshed })
+    }
+}
+
+impl Handler for MqttDisplayHandler {
+    fn show(&mut self, cmd: DisplayCmd) -> u32 {
+        DisplayHandler.show(cmd)
+    }
+
+    async fn refreshed(&mut self, sequence: u32) {
+        DisplayHandler.refreshed(sequence).await
+    }
+
+    type Planes<'a> = DisplayPlanes;
+
+    async fn planes(&mut self) -> DisplayPlanes {
+        DisplayHandler.planes().await
+    }
+
+    fn rejected(&mut self, error: MessageError) {
+        DisplayHandler.rejected(error)
+    }
+}
+
+impl MqttHandler for MqttDisplayHandler {
+    async fn next_refresh(&mut self, timeout: u16) -> Option<Refresh> {
+        let sequence = with_timeout(Duration::from_secs(timeout.into()), self.refreshed.changed()).await.ok()?;
+        Some(Refresh { sequence, uptime: Instant::now().as_secs() })
+    }
+}
+
+// Non-empty topic level that doesn't split the topic or match other topics.
+const fn is_topic_level(level: &str) -> bool {
+    let bytes = level.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if matches!(bytes[i], b'/' | b'+' | b'#') {
+            return false;
+        }
+
+        i += 1;
+    }
+
+    !bytes.is_empty()
+}
@@ -1,4 +1,4 @@
-use defmt::{trace, debug, info, warn};
+use defmt::{trace, debug, warn};
 use core::fmt::Write;
 use embassy_executor::Spawner;
 use embassy_rp::bind_interrupts;
@@ -9,15 +9,19 @@ use embassy_net::Config;
 use embassy_net::tcp::TcpSocket;
 use embassy_time::Duration;
 use cyw43_pio::{PioSpi, DEFAULT_CLOCK_DIVIDER};
-use heapless::{String, Vec};
-use serde::Deserialize;
+use heapless::String;
 
 use epd_display::epd::three_color::ThreeColor;
 use pico_wifi::{WifiDriver, WifiPio};
 pub use pico_wifi::WifiPeripherals;
 use pico_wifi::init::init_wifi;
 
-use crate::data::display_cmd::{DisplayCmd, TextLine, TextPanelContent, DISPLAY_CMD_READY, SHARED_DISPLAY_CMD};
+use display_core::data::display_cmd::{DisplayCmd, TextLine, TextPanelContent};
+use display_core::protocol::connection::serve;
+use crate::data::display_cmd::{send_display_cmd, DisplayHandler};
+use crate::tasks::http::run_http_server;
+#[cfg(feature = "mqtt")]
+use crate::tasks::mqtt::run_mqtt_client;
 
 macro_rules! wifi_spi {
     ($wifi_pio:expr, $dio_pin:expr, $clk_pin:expr, $dma_channel:expr) => {
@@ -45,7 +49,6 @@ bind_interrupts!(
 
 const WIFI_NETWORK: &str = dotenvy_macro::dotenv!("WIFI_NETWORK");
 const WIFI_PASSWORD: &str = dotenvy_macro::dotenv!("WIFI_PASSWORD");
-
 #[embassy_executor::task]
 pub async fn run_wifi(spawner: Spawner, peripherals: WifiPeripherals<DMA_CH0>) {
     trace!("Wifi initialization");
@@ -71,15 +74,22 @@ pub async fn run_wifi(spawner: Spawner, peripherals: WifiPeripherals<DMA_CH0>) {
             let _ = content.add_body_line(body_line);
         }
 
-        send_text_panel(content);
+        send_display_cmd(DisplayCmd::TextPanel(content));
     } else {
         let title = TextLine::new("Wifi connection failed", ThreeColor::Chromatic);
         let content = TextPanelContent::new(title);
 
-        send_text_panel(content);
+        send_display_cmd(DisplayCmd::TextPanel(content));
     }
 
+    if let Err(e) = spawner.spawn(run_http_server(driver.stack)) {
+        warn!("HTTP server task failed: {:?}", e);
+    }
 
+    #[cfg(feature = "mqtt")]
+    if let Err(e) = spawner.spawn(run_mqtt_client(driver.stack)) {
+        warn!("MQTT client task failed: {:?}", e);
+    }
 
     run_tcp_server(&mut driver).await;
 }
@@ -104,64 +114,11 @@ async fn run_tcp_server(driver: &mut WifiDriver) -> ! {
         trace!("Received connection from {:?}", socket.remote_endpoint());
         control.gpio_set(0, true).await;
 
-        loop {
-            let n = match socket.read(&mut buf).await {
-                Ok(0) => {
-                    warn!("read EOF");
-                    break;
-                }
-                Ok(n) => n,
-                Err(e) => {
-                    warn!("read error: {:?}", e);
-                    break;
-                }
-            };
-
-            if let Some(content) = parse_message(&buf[..n]) {
-                info!("Parsed message.");
-                send_text_panel(content);
-            } else {
-                info!("Couldn't parse message.")
-            }
-
-            match socket.write(&buf[..n]).await {
-                Ok(_) => {}
-                Err(e) => {
-                    warn!("write error: {:?}", e);
-                    break;
-                }
-            };
-        }
-    }
-}
-
-#[derive(Deserialize)]
-struct TextMessage {
-    pub title: String<80>,
-    pub body: Vec<String<80>,10>
-}
-
-fn parse_message(buf: &[u8]) -> Option<TextPanelContent> {
-    match serde_json_core::from_slice::<TextMessage>(buf) {
-        Ok((TextMessage {title, body}, _)) => {
-            let title = TextLine::new(&title, ThreeColor::Black);
-            let mut content = TextPanelContent::new(title);
+        let result = serve(&mut socket, &mut buf, &mut DisplayHandler).await;
 
-            for body_text in body {
-                let body_line = TextLine::new(&body_text, ThreeColor::Black);
-                content.add_body_line(body_line).ok()?;
-            }
-
-            Some(content)
-        },
-        _ => None
+        match result {
+            Ok(()) => warn!("read EOF"),
+            Err(e) => warn!("connection error: {:?}", e),
+        }
     }
 }
-
-fn send_text_panel(content: TextPanelContent) {
-    SHARED_DISPLAY_CMD.lock(|cmd| {
-        *cmd.borrow_mut() = DisplayCmd::TextPanel(content);
-    });
-
-    let _ = DISPLAY_CMD_READY.try_send(());
-}
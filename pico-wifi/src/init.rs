@@ -27,8 +27,9 @@ pub async fn init_wifi<DMA: Channel + SpawnCyw43Task>(spawner: &Spawner, pwr: Ou
         Err(_) => error!("Cyw43 runner task failed")
     };
 
-    // This allocates memory for 3 sockets. DHCP and DNS each require one socket.
-    static STACK_RESOURCES: StaticCell<StackResources<3>> = StaticCell::new();
+    // This allocates memory for 5 sockets. DHCP and DNS each require one socket, the other three are
+    // for the TCP servers and the MQTT client.
+    static STACK_RESOURCES: StaticCell<StackResources<5>> = StaticCell::new();
     let mut rng = RoscRng;
     let seed = rng.next_u64();
     let (stack, runner) = embassy_net::new(net_device, config, STACK_RESOURCES.init(StackResources::new()), seed);